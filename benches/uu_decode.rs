@@ -0,0 +1,59 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+/// Deterministic binary payload, so runs are comparable
+fn payload(len: usize) -> Vec<u8> {
+    let mut state = 0x2545_F491_4F6C_DD1Du64;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as u8
+        })
+        .collect()
+}
+
+fn uu_decode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("uu_decode");
+
+    for size in [64 * 1024, 1024 * 1024, 8 * 1024 * 1024] {
+        let encoded = format!("{}\n", uuencode::uuencode("graphic.jpg", &payload(size)));
+        assert_eq!(secsgml::uu_decode(encoded.as_bytes()).len(), size);
+        group.throughput(Throughput::Bytes(encoded.len() as u64));
+
+        group.bench_with_input(BenchmarkId::new("secsgml", size), &encoded, |b, encoded| {
+            b.iter(|| secsgml::uu_decode(black_box(encoded.as_bytes())))
+        });
+        group.bench_with_input(
+            BenchmarkId::new("uuencode", size),
+            &encoded,
+            |b, encoded| b.iter(|| uuencode::uudecode(black_box(encoded))),
+        );
+    }
+
+    group.finish();
+}
+
+/// Whole-submission parse of a filing made mostly of graphics
+fn graphic_submission(c: &mut Criterion) {
+    let mut submission = String::from("<SUBMISSION>\n<TYPE>10-K\n");
+    for i in 0..20 {
+        submission.push_str(&format!(
+            "<DOCUMENT>\n<TYPE>GRAPHIC\n<SEQUENCE>{}\n<FILENAME>g{}.jpg\n<TEXT>\n{}\n</TEXT>\n</DOCUMENT>\n",
+            i + 1,
+            i,
+            uuencode::uuencode("g.jpg", &payload(256 * 1024))
+        ));
+    }
+    submission.push_str("</SUBMISSION>\n");
+
+    let mut group = c.benchmark_group("graphic_submission");
+    group.throughput(Throughput::Bytes(submission.len() as u64));
+    group.bench_function("parse_sgml_bytes", |b| {
+        b.iter(|| secsgml::parse_sgml_bytes(black_box(submission.as_bytes())).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, uu_decode, graphic_submission);
+criterion_main!(benches);
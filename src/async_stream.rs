@@ -0,0 +1,97 @@
+use std::io::ErrorKind;
+
+use futures_util::stream::{self, Stream};
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::stream::{Event, StreamParser, CHUNK_SIZE};
+use crate::types::{ParseError, ParseOptions};
+
+/// Reader and parser state carried between polls
+struct AsyncEvents<R> {
+    reader: R,
+    parser: StreamParser,
+    buffer: Vec<u8>,
+    done: bool,
+}
+
+/// Parse a submission from an async reader, yielding the header and then
+/// each document as it completes
+pub fn parse_events_async<R: AsyncRead + Unpin>(
+    reader: R,
+) -> impl Stream<Item = Result<Event, ParseError>> {
+    parse_events_async_with_options(reader, ParseOptions::default())
+}
+
+/// Parse a submission from an async reader with explicit options
+///
+/// The stream ends after the first error.
+pub fn parse_events_async_with_options<R: AsyncRead + Unpin>(
+    reader: R,
+    options: ParseOptions,
+) -> impl Stream<Item = Result<Event, ParseError>> {
+    let state = AsyncEvents {
+        reader,
+        parser: StreamParser::with_options(options),
+        buffer: vec![0; CHUNK_SIZE],
+        done: false,
+    };
+
+    stream::unfold(Some(state), |state| async move {
+        let mut state = state?;
+        loop {
+            if let Some(event) = state.parser.next_event() {
+                return Some((Ok(event), Some(state)));
+            }
+            if state.done {
+                return None;
+            }
+
+            let result = match state.reader.read(&mut state.buffer).await {
+                Ok(0) => {
+                    state.done = true;
+                    state.parser.finish()
+                }
+                Ok(n) => state.parser.feed(&state.buffer[..n]),
+                Err(err) if err.kind() == ErrorKind::Interrupted => Ok(()),
+                Err(err) => Err(ParseError::Io(err)),
+            };
+            if let Err(err) = result {
+                return Some((Err(err), None));
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::StreamExt;
+
+    use super::*;
+    use crate::stream::{collect_events, parse_events, SAMPLE};
+
+    #[tokio::test]
+    async fn test_async_matches_sync() {
+        let events: Vec<_> = parse_events_async(SAMPLE)
+            .map(Result::unwrap)
+            .collect()
+            .await;
+        let (metadata, documents) = collect_events(events);
+
+        let sync_events = parse_events(SAMPLE).map(Result::unwrap);
+        let (expected_metadata, expected_documents) = collect_events(sync_events);
+        assert_eq!(
+            serde_json::to_value(&metadata).unwrap(),
+            serde_json::to_value(&expected_metadata).unwrap()
+        );
+        assert_eq!(documents, expected_documents);
+    }
+
+    #[tokio::test]
+    async fn test_async_stops_after_error() {
+        let events: Vec<_> = parse_events_async(&b"not a submission\n"[..])
+            .collect()
+            .await;
+        assert_eq!(events.len(), 1);
+        assert!(events[0].is_err());
+    }
+}
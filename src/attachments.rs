@@ -0,0 +1,409 @@
+use memchr::memmem;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::types::{MetadataDict, MetadataValue};
+
+/// Bytes searched at the end of a PDF for `%%EOF`
+const PDF_TAIL: usize = 1024;
+
+/// End of central directory record plus the longest archive comment
+const ZIP_EOCD_SEARCH: usize = 22 + 0xFFFF;
+
+/// Format of a decoded attachment, from its magic bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AttachmentFormat {
+    Pdf,
+    Jpeg,
+    Gif,
+    Png,
+    /// ZIP archives, including `.xlsx` and `.docx` office documents
+    Zip,
+    Unknown,
+}
+
+impl AttachmentFormat {
+    /// Detect the format from the leading bytes
+    pub fn detect(content: &[u8]) -> Self {
+        if content.starts_with(b"%PDF-") {
+            Self::Pdf
+        } else if content.starts_with(&[0xFF, 0xD8, 0xFF]) {
+            Self::Jpeg
+        } else if content.starts_with(b"GIF87a") || content.starts_with(b"GIF89a") {
+            Self::Gif
+        } else if content.starts_with(b"\x89PNG\r\n\x1a\n") {
+            Self::Png
+        } else if content.starts_with(b"PK\x03\x04") || content.starts_with(b"PK\x05\x06") {
+            Self::Zip
+        } else {
+            Self::Unknown
+        }
+    }
+
+    /// Format implied by a file extension
+    pub fn from_filename(filename: &str) -> Self {
+        let extension = filename
+            .rsplit_once('.')
+            .map(|(_, extension)| extension.to_ascii_lowercase())
+            .unwrap_or_default();
+        match extension.as_str() {
+            "pdf" => Self::Pdf,
+            "jpg" | "jpeg" => Self::Jpeg,
+            "gif" => Self::Gif,
+            "png" => Self::Png,
+            "zip" | "xlsx" | "docx" | "pptx" => Self::Zip,
+            _ => Self::Unknown,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pdf => "pdf",
+            Self::Jpeg => "jpeg",
+            Self::Gif => "gif",
+            Self::Png => "png",
+            Self::Zip => "zip",
+            Self::Unknown => "unknown",
+        }
+    }
+}
+
+/// Outcome of the structural check of an attachment
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AttachmentStatus {
+    Valid,
+    /// The payload stops before the format's end marker
+    Truncated,
+    /// The payload is not the format its name says, or its structure is broken
+    Corrupt,
+    /// Unknown format, only size and hash are recorded
+    Unverified,
+}
+
+impl AttachmentStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Valid => "valid",
+            Self::Truncated => "truncated",
+            Self::Corrupt => "corrupt",
+            Self::Unverified => "unverified",
+        }
+    }
+}
+
+/// Size, hash and integrity of a decoded attachment
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AttachmentCheck {
+    pub format: AttachmentFormat,
+    pub size: usize,
+    /// Lowercase hex SHA-256 of the decoded bytes
+    pub sha256: String,
+    pub status: AttachmentStatus,
+    /// Why the attachment is truncated or corrupt
+    pub problem: Option<String>,
+}
+
+impl AttachmentCheck {
+    /// Metadata dict stored under a document's `attachment` key
+    pub fn to_metadata(&self) -> MetadataDict {
+        let mut dict = MetadataDict::default();
+        let mut insert = |key: &str, value: String| {
+            dict.insert(key.to_string(), MetadataValue::Text(value));
+        };
+        insert("format", self.format.as_str().to_string());
+        insert("size", self.size.to_string());
+        insert("sha256", self.sha256.clone());
+        insert("status", self.status.as_str().to_string());
+        if let Some(problem) = &self.problem {
+            insert("problem", problem.clone());
+        }
+        dict
+    }
+}
+
+/// Verify a decoded attachment against its format's structure
+///
+/// The format comes from the magic bytes; when they are not recognised but
+/// `filename` names a known format, the attachment is corrupt. Checks are
+/// structural only: a PDF must end with `%%EOF` after a `startxref`, JPEG
+/// with EOI, GIF with its trailer, PNG with `IEND`, and a ZIP must have an
+/// end of central directory record pointing at a complete central
+/// directory.
+pub fn verify_attachment(content: &[u8], filename: Option<&str>) -> AttachmentCheck {
+    let mut format = AttachmentFormat::detect(content);
+    let expected = filename.map_or(AttachmentFormat::Unknown, AttachmentFormat::from_filename);
+
+    let result = match format {
+        _ if content.is_empty() => Err((AttachmentStatus::Truncated, "empty payload".to_string())),
+        AttachmentFormat::Unknown if expected != AttachmentFormat::Unknown => {
+            format = expected;
+            Err((
+                AttachmentStatus::Corrupt,
+                format!("missing {} signature", expected.as_str().to_uppercase()),
+            ))
+        }
+        AttachmentFormat::Unknown => Err((AttachmentStatus::Unverified, String::new())),
+        AttachmentFormat::Pdf => check_pdf(content),
+        AttachmentFormat::Jpeg => check_trailer(content, &[0xFF, 0xD9], "JPEG end of image"),
+        AttachmentFormat::Gif => check_trailer(content, &[0x3B], "GIF trailer"),
+        AttachmentFormat::Png => check_trailer(content, b"IEND\xAE\x42\x60\x82", "PNG IEND chunk"),
+        AttachmentFormat::Zip => check_zip(content),
+    };
+
+    let (status, problem) = match result {
+        Ok(()) => (AttachmentStatus::Valid, None),
+        Err((AttachmentStatus::Unverified, _)) => (AttachmentStatus::Unverified, None),
+        Err((status, problem)) => (status, Some(problem)),
+    };
+
+    AttachmentCheck {
+        format,
+        size: content.len(),
+        sha256: Sha256::digest(content)
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect(),
+        status,
+        problem,
+    }
+}
+
+/// Verify a decoded document and store the result under `attachment`
+pub(crate) fn add_attachment_check(doc_metadata: &mut MetadataDict, content: &[u8]) {
+    let filename = doc_metadata
+        .get("filename")
+        .and_then(MetadataValue::as_text)
+        .map(String::as_str);
+    let check = verify_attachment(content, filename);
+    doc_metadata.insert(
+        "attachment".to_string(),
+        MetadataValue::Dict(check.to_metadata()),
+    );
+}
+
+type Check = Result<(), (AttachmentStatus, String)>;
+
+/// UU decoding pads the last line, so trailing NULs and whitespace are ignored
+fn trim_padding(content: &[u8]) -> &[u8] {
+    let end = content
+        .iter()
+        .rposition(|&b| b != 0 && !b.is_ascii_whitespace())
+        .map_or(0, |i| i + 1);
+    &content[..end]
+}
+
+fn check_trailer(content: &[u8], trailer: &[u8], name: &str) -> Check {
+    if trim_padding(content).ends_with(trailer) {
+        Ok(())
+    } else {
+        Err((AttachmentStatus::Truncated, format!("missing {}", name)))
+    }
+}
+
+fn check_pdf(content: &[u8]) -> Check {
+    let content = trim_padding(content);
+    let tail = &content[content.len().saturating_sub(PDF_TAIL)..];
+    let Some(eof) = memmem::rfind(tail, b"%%EOF") else {
+        return Err((AttachmentStatus::Truncated, "missing %%EOF".to_string()));
+    };
+    if memmem::rfind(&tail[..eof], b"startxref").is_none() {
+        return Err((
+            AttachmentStatus::Corrupt,
+            "missing startxref before %%EOF".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn check_zip(content: &[u8]) -> Check {
+    let search_start = content.len().saturating_sub(ZIP_EOCD_SEARCH);
+    let Some(eocd) =
+        memmem::rfind(&content[search_start..], b"PK\x05\x06").map(|i| search_start + i)
+    else {
+        return Err((
+            AttachmentStatus::Truncated,
+            "missing end of central directory".to_string(),
+        ));
+    };
+    let record = &content[eocd..];
+    if record.len() < 22 {
+        return Err((
+            AttachmentStatus::Truncated,
+            "short end of central directory".to_string(),
+        ));
+    }
+
+    let u16_at = |data: &[u8], at: usize| u16::from_le_bytes([data[at], data[at + 1]]) as usize;
+    let u32_at = |data: &[u8], at: usize| {
+        u32::from_le_bytes([data[at], data[at + 1], data[at + 2], data[at + 3]]) as usize
+    };
+
+    let entries = u16_at(record, 10);
+    let directory_size = u32_at(record, 12);
+    let directory_offset = u32_at(record, 16);
+    // ZIP64 archives keep the real values in another record
+    if directory_offset == 0xFFFF_FFFF || entries == 0xFFFF {
+        return Ok(());
+    }
+    if directory_offset + directory_size > eocd {
+        return Err((
+            AttachmentStatus::Corrupt,
+            "central directory outside the archive".to_string(),
+        ));
+    }
+
+    let mut pos = directory_offset;
+    for _ in 0..entries {
+        let header = content.get(pos..eocd).unwrap_or_default();
+        if header.len() < 46 || !header.starts_with(b"PK\x01\x02") {
+            return Err((
+                AttachmentStatus::Corrupt,
+                format!("bad central directory entry at {}", pos),
+            ));
+        }
+        let local_offset = u32_at(header, 42);
+        if content.get(local_offset..local_offset + 4) != Some(b"PK\x03\x04") {
+            return Err((
+                AttachmentStatus::Corrupt,
+                format!("bad local header at {}", local_offset),
+            ));
+        }
+        pos += 46 + u16_at(header, 28) + u16_at(header, 30) + u16_at(header, 32);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        parse_sgml_bytes, parse_sgml_bytes_with_options, parse_sgml_into_memory_with_options,
+        ParseOptions,
+    };
+
+    /// Minimal stored ZIP archive with one empty file named `a`
+    fn zip() -> Vec<u8> {
+        let mut zip = Vec::new();
+        zip.extend_from_slice(b"PK\x03\x04");
+        zip.extend_from_slice(&[20, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        zip.extend_from_slice(&[0; 12]);
+        zip.extend_from_slice(&[1, 0, 0, 0]);
+        zip.push(b'a');
+
+        let directory_offset = zip.len() as u8;
+        zip.extend_from_slice(b"PK\x01\x02");
+        zip.extend_from_slice(&[20, 0, 20, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        zip.extend_from_slice(&[0; 12]);
+        zip.extend_from_slice(&[1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        zip.extend_from_slice(&[0, 0, 0, 0]);
+        zip.push(b'a');
+
+        let directory_size = zip.len() as u8 - directory_offset;
+        zip.extend_from_slice(b"PK\x05\x06");
+        zip.extend_from_slice(&[0, 0, 0, 0, 1, 0, 1, 0]);
+        zip.extend_from_slice(&[directory_size, 0, 0, 0, directory_offset, 0, 0, 0, 0, 0]);
+        zip
+    }
+
+    #[test]
+    fn test_verify_formats() {
+        let pdf = b"%PDF-1.4\n1 0 obj\n<<>>\nendobj\nstartxref\n9\n%%EOF\n\0\0";
+        let check = verify_attachment(pdf, Some("report.pdf"));
+        assert_eq!(check.format, AttachmentFormat::Pdf);
+        assert_eq!(check.status, AttachmentStatus::Valid);
+        assert_eq!(check.size, pdf.len());
+        assert_eq!(check.sha256.len(), 64);
+
+        let truncated = verify_attachment(&pdf[..30], Some("report.pdf"));
+        assert_eq!(truncated.status, AttachmentStatus::Truncated);
+        assert_eq!(truncated.problem.as_deref(), Some("missing %%EOF"));
+
+        let jpeg = [0xFF, 0xD8, 0xFF, 0xE0, 1, 2, 3, 0xFF, 0xD9];
+        assert_eq!(
+            verify_attachment(&jpeg, None).status,
+            AttachmentStatus::Valid
+        );
+        assert_eq!(
+            verify_attachment(&jpeg[..7], None).status,
+            AttachmentStatus::Truncated
+        );
+        assert_eq!(
+            verify_attachment(b"GIF89a\x01\x00;", None).status,
+            AttachmentStatus::Valid
+        );
+
+        let mismatched = verify_attachment(b"<html>", Some("logo.jpg"));
+        assert_eq!(mismatched.format, AttachmentFormat::Jpeg);
+        assert_eq!(mismatched.status, AttachmentStatus::Corrupt);
+        assert_eq!(
+            verify_attachment(b"plain", None).status,
+            AttachmentStatus::Unverified
+        );
+    }
+
+    #[test]
+    fn test_verify_zip() {
+        let zip = zip();
+        let check = verify_attachment(&zip, Some("Financial_Report.xlsx"));
+        assert_eq!(check.format, AttachmentFormat::Zip);
+        assert_eq!(check.status, AttachmentStatus::Valid, "{:?}", check.problem);
+
+        let truncated = verify_attachment(&zip[..zip.len() - 30], None);
+        assert_eq!(truncated.status, AttachmentStatus::Truncated);
+
+        let mut corrupt = zip.clone();
+        let directory_offset = corrupt.len() - 6;
+        corrupt[directory_offset] = 1;
+        assert_eq!(
+            verify_attachment(&corrupt, None).status,
+            AttachmentStatus::Corrupt
+        );
+    }
+
+    #[test]
+    fn test_parsers_record_attachment_checks() {
+        let jpeg = [0xFF, 0xD8, 0xFF, 0xE0, 1, 2, 3, 0xFF, 0xD9];
+        let pdf = b"%PDF-1.4\n1 0 obj\n<<>>\nendobj\n";
+        let submission = format!(
+            "<SUBMISSION>\n<TYPE>10-K\n\
+<DOCUMENT>\n<TYPE>10-K\n<FILENAME>form10-k.htm\n<TEXT>\nbody\n</TEXT>\n</DOCUMENT>\n\
+<DOCUMENT>\n<TYPE>GRAPHIC\n<FILENAME>logo.jpg\n<TEXT>\n{}\n</TEXT>\n</DOCUMENT>\n\
+<DOCUMENT>\n<TYPE>PDF\n<FILENAME>form10-k.pdf\n<TEXT>\n<PDF>\n{}\n</PDF>\n</TEXT>\n</DOCUMENT>\n\
+</SUBMISSION>\n",
+            uuencode::uuencode("logo.jpg", &jpeg),
+            uuencode::uuencode("form10-k.pdf", pdf),
+        );
+        let options = ParseOptions {
+            verify_attachments: true,
+            ..ParseOptions::default()
+        };
+
+        let (bytes_metadata, documents) =
+            parse_sgml_bytes_with_options(submission.as_bytes(), &options).unwrap();
+        let (lines_metadata, _) =
+            parse_sgml_into_memory_with_options(Some(submission.clone()), None, &options).unwrap();
+        assert_eq!(documents[1], jpeg);
+        assert_eq!(documents[2], pdf);
+
+        for metadata in [&bytes_metadata, &lines_metadata] {
+            let docs = metadata["documents"].as_list().unwrap();
+            let attachment = |i: usize| docs[i].as_dict().unwrap().get("attachment");
+            assert!(attachment(0).is_none());
+
+            let logo = attachment(1).unwrap().as_dict().unwrap();
+            assert_eq!(logo["format"].as_text().unwrap(), "jpeg");
+            assert_eq!(logo["status"].as_text().unwrap(), "valid");
+            assert_eq!(logo["size"].as_text().unwrap(), "9");
+
+            let report = attachment(2).unwrap().as_dict().unwrap();
+            assert_eq!(report["status"].as_text().unwrap(), "truncated");
+            assert_eq!(report["problem"].as_text().unwrap(), "missing %%EOF");
+        }
+
+        let (metadata, _) = parse_sgml_bytes(submission.as_bytes()).unwrap();
+        let docs = metadata["documents"].as_list().unwrap();
+        assert!(docs[1].as_dict().unwrap().get("attachment").is_none());
+    }
+}
@@ -0,0 +1,472 @@
+use std::borrow::Cow;
+
+use crate::byte_parser::{
+    build_document_index, find_tag, parse_dashed_default_header, parse_document_metadata,
+    parse_tab_header, parse_wrapped_header, process_text_content, DOCUMENT_OPEN, IMS_HEADER,
+    SEC_HEADER, TEXT_OPEN,
+};
+use crate::detect::{content_start, detect_submission_format};
+use crate::types::{
+    ContinuationJoin, FastMap, MetadataDict, MetadataValue, ParseError, ParseOptions, Span,
+    SubmissionType,
+};
+use crate::utils::{insert_metadata_value, MetadataSink};
+
+/// Metadata value borrowed from the input where possible
+///
+/// Text is owned only when it had to be built: continuation lines joined,
+/// or invalid UTF-8 replaced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValueRef<'a> {
+    Text(Cow<'a, str>),
+    Dict(DictRef<'a>),
+}
+
+impl<'a> ValueRef<'a> {
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            Self::Text(text) => Some(text),
+            Self::Dict(_) => None,
+        }
+    }
+
+    pub fn as_dict(&self) -> Option<&DictRef<'a>> {
+        match self {
+            Self::Dict(dict) => Some(dict),
+            Self::Text(_) => None,
+        }
+    }
+
+    /// Convert to an owned [`MetadataValue`] with lowercase keys
+    pub fn to_metadata(&self) -> MetadataValue {
+        match self {
+            Self::Text(text) => MetadataValue::Text(text.to_string()),
+            Self::Dict(dict) => MetadataValue::Dict(dict.to_metadata()),
+        }
+    }
+}
+
+/// Tags in input order, with keys as written
+///
+/// Repeated tags stay separate entries rather than becoming lists; lookups
+/// ignore ASCII case.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DictRef<'a> {
+    pub entries: Vec<(Cow<'a, str>, ValueRef<'a>)>,
+}
+
+impl<'a> DictRef<'a> {
+    /// First value under `key`
+    pub fn get(&self, key: &str) -> Option<&ValueRef<'a>> {
+        self.entries
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(key))
+            .map(|(_, value)| value)
+    }
+
+    /// Every value under `key`, in input order
+    pub fn get_all<'b>(&'b self, key: &'b str) -> impl Iterator<Item = &'b ValueRef<'a>> + 'b {
+        self.entries
+            .iter()
+            .filter(move |(name, _)| name.eq_ignore_ascii_case(key))
+            .map(|(_, value)| value)
+    }
+
+    /// First text value under `key`
+    pub fn text(&self, key: &str) -> Option<&str> {
+        self.get(key).and_then(ValueRef::as_text)
+    }
+
+    /// Convert to an owned [`MetadataDict`], lowercasing keys and turning
+    /// repeated keys into lists
+    pub fn to_metadata(&self) -> MetadataDict {
+        let mut dict = MetadataDict::with_capacity(self.entries.len());
+        for (key, value) in &self.entries {
+            insert_metadata_value(&mut dict, key.to_ascii_lowercase(), value.to_metadata());
+        }
+        dict
+    }
+
+    fn push(&mut self, key: Cow<'a, str>, value: ValueRef<'a>) {
+        self.entries.push((key, value));
+    }
+}
+
+/// Document borrowed from the input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocumentRef<'a> {
+    pub metadata: DictRef<'a>,
+    /// Text content, owned only when UU-decoded or rejoined around `</TEXT>`
+    pub content: Cow<'a, [u8]>,
+}
+
+impl DocumentRef<'_> {
+    /// Whether the content points into the input
+    pub fn is_borrowed(&self) -> bool {
+        matches!(self.content, Cow::Borrowed(_))
+    }
+}
+
+/// Submission parsed without copying, tied to the input buffer
+///
+/// Produces the same header, document metadata and content as
+/// [`parse_sgml_bytes`](crate::parse_sgml_bytes), see
+/// [`to_owned_parts`](Self::to_owned_parts). `-index-headers.html` pages are
+/// not supported, as their header has to be unescaped first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedSubmission<'a> {
+    pub header: DictRef<'a>,
+    pub documents: Vec<DocumentRef<'a>>,
+}
+
+impl<'a> ParsedSubmission<'a> {
+    pub fn parse(data: &'a [u8]) -> Result<Self, ParseError> {
+        Self::parse_with_options(data, &ParseOptions::default())
+    }
+
+    /// Parse with explicit options
+    ///
+    /// `submission_type`, `continuation_join` and `limits` apply;
+    /// `always_list` and `verify_attachments` only affect owned metadata
+    /// and are ignored.
+    pub fn parse_with_options(data: &'a [u8], options: &ParseOptions) -> Result<Self, ParseError> {
+        let index = SubmissionIndex::build_with_options(data, options)?;
+        let documents = (0..index.len())
+            .filter_map(|doc_idx| index.document(doc_idx))
+            .map(|doc| {
+                options.limits.check_decoded_size(doc.content.len())?;
+                Ok(doc)
+            })
+            .collect::<Result<_, ParseError>>()?;
+        Ok(Self {
+            header: index.header,
+            documents,
+        })
+    }
+
+    /// Convert to the owned metadata and documents of
+    /// [`parse_sgml_bytes`](crate::parse_sgml_bytes)
+    pub fn to_owned_parts(&self) -> (MetadataDict, Vec<Vec<u8>>) {
+        let mut metadata = self.header.to_metadata();
+        let doc_metadata = self
+            .documents
+            .iter()
+            .map(|doc| MetadataValue::Dict(doc.metadata.to_metadata()))
+            .collect();
+        metadata.insert("documents".to_string(), MetadataValue::List(doc_metadata));
+
+        let documents = self
+            .documents
+            .iter()
+            .map(|doc| doc.content.to_vec())
+            .collect();
+        (metadata, documents)
+    }
+}
+
+/// Byte ranges of one document within the input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct DocumentSpan {
+    /// Lines between `<DOCUMENT>` and `<TEXT>`
+    metadata: Span,
+    /// Content between `<TEXT>` and `</TEXT>`
+    text: Span,
+}
+
+/// Header and document positions of a submission, decoded on demand
+///
+/// Building the index finds the document boundaries and parses the header
+/// only. Document metadata and content are parsed when asked for, so the
+/// documents a caller never touches are neither UU-decoded nor copied.
+#[derive(Debug, Clone)]
+pub struct SubmissionIndex<'a> {
+    data: &'a [u8],
+    pub header: DictRef<'a>,
+    documents: Vec<DocumentSpan>,
+    join: ContinuationJoin,
+}
+
+impl<'a> SubmissionIndex<'a> {
+    pub fn build(data: &'a [u8]) -> Result<Self, ParseError> {
+        Self::build_with_options(data, &ParseOptions::default())
+    }
+
+    /// Build with explicit options, see [`ParsedSubmission::parse_with_options`]
+    ///
+    /// Documents are decoded on demand, so `max_decoded_size` is not
+    /// checked here.
+    pub fn build_with_options(data: &'a [u8], options: &ParseOptions) -> Result<Self, ParseError> {
+        if data.trim_ascii().is_empty() {
+            return Err(ParseError::InvalidContent("Empty content".to_string()));
+        }
+        options.limits.check_input_size(data.len())?;
+        options.limits.check_line_lengths(data)?;
+
+        let (submission_type, offset) = match &options.submission_type {
+            Some(forced) => (forced.clone(), content_start(data)),
+            None => {
+                let detection = detect_submission_format(data)?;
+                (detection.submission_type, detection.offset)
+            }
+        };
+        if submission_type == SubmissionType::IndexHeaders {
+            return Err(ParseError::InvalidContent(
+                "Index header pages cannot be parsed without copying".to_string(),
+            ));
+        }
+
+        let data = &data[offset..];
+        let doc_index = build_document_index(data);
+        options
+            .limits
+            .check_documents(doc_index.document_positions.len())?;
+
+        let limits = &options.limits;
+        let header_end = doc_index.header_end;
+        let header = match submission_type {
+            SubmissionType::DashedDefault => parse_dashed_default_header(data, header_end, limits)?,
+            SubmissionType::SecHeader => {
+                parse_wrapped_header(data, header_end, SEC_HEADER, limits)?
+            }
+            SubmissionType::ImsHeader => {
+                parse_wrapped_header(data, header_end, IMS_HEADER, limits)?
+            }
+            _ => parse_tab_header(data, header_end, &submission_type, limits)?,
+        };
+
+        let text_ends: FastMap<usize, usize> = doc_index.text_positions.iter().copied().collect();
+        let documents = doc_index
+            .document_positions
+            .iter()
+            .filter_map(|&(doc_start, doc_end)| {
+                let text_start = find_tag(data, TEXT_OPEN, doc_start)
+                    .filter(|&pos| pos < doc_end && text_ends.contains_key(&pos))?;
+                let text_end = text_ends[&text_start];
+                Some(DocumentSpan {
+                    metadata: Span::new(doc_start + DOCUMENT_OPEN.len(), text_start),
+                    text: Span::new(text_start + TEXT_OPEN.len(), text_end),
+                })
+            })
+            .collect();
+
+        Ok(Self {
+            data,
+            header,
+            documents,
+            join: options.continuation_join,
+        })
+    }
+
+    /// Number of documents
+    pub fn len(&self) -> usize {
+        self.documents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.documents.is_empty()
+    }
+
+    /// Metadata of one document
+    pub fn metadata(&self, doc_idx: usize) -> Option<DictRef<'a>> {
+        let span = self.documents.get(doc_idx)?;
+        Some(parse_document_metadata(
+            self.data,
+            span.metadata.start,
+            span.metadata.end,
+            self.join,
+        ))
+    }
+
+    /// Content of one document as written, before UU decoding
+    pub fn raw(&self, doc_idx: usize) -> Option<&'a [u8]> {
+        self.documents
+            .get(doc_idx)
+            .map(|span| span.text.slice(self.data))
+    }
+
+    /// Content of one document, UU-decoded if needed
+    pub fn decode(&self, doc_idx: usize) -> Option<Cow<'a, [u8]>> {
+        let span = self.documents.get(doc_idx)?;
+        Some(process_text_content(span.text.slice(self.data)).0)
+    }
+
+    /// Metadata and content of one document
+    pub fn document(&self, doc_idx: usize) -> Option<DocumentRef<'a>> {
+        Some(DocumentRef {
+            metadata: self.metadata(doc_idx)?,
+            content: self.decode(doc_idx)?,
+        })
+    }
+
+    /// Index of the first document whose `<TYPE>` matches, ignoring case
+    pub fn find_type(&self, doc_type: &str) -> Option<usize> {
+        (0..self.len()).find(|&doc_idx| {
+            self.metadata(doc_idx).is_some_and(|metadata| {
+                metadata
+                    .text("type")
+                    .is_some_and(|found| found.trim().eq_ignore_ascii_case(doc_type))
+            })
+        })
+    }
+}
+
+/// Borrow text, replacing invalid UTF-8 only when present
+fn text(bytes: &[u8]) -> Cow<'_, str> {
+    String::from_utf8_lossy(bytes)
+}
+
+/// Keys stay as written and repeated keys stay separate entries
+impl<'a> MetadataSink<'a> for DictRef<'a> {
+    fn push_text(&mut self, key: &'a [u8], value: Cow<'a, str>) {
+        self.push(text(key), ValueRef::Text(value));
+    }
+
+    fn push_dict(&mut self, key: &'a [u8], dict: Self) {
+        self.push(text(key), ValueRef::Dict(dict));
+    }
+
+    fn set_text(&mut self, key: &'a [u8], value: Cow<'a, str>) {
+        let key = text(key);
+        self.entries
+            .retain(|(name, _)| !name.eq_ignore_ascii_case(&key));
+        self.push(key, ValueRef::Text(value));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_sgml_bytes;
+
+    fn assert_same_as_owned(data: &[u8]) {
+        let parsed = ParsedSubmission::parse(data).unwrap();
+        let (metadata, documents) = parsed.to_owned_parts();
+        let (expected_metadata, expected_documents) = parse_sgml_bytes(data).unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&metadata).unwrap(),
+            serde_json::to_value(&expected_metadata).unwrap()
+        );
+        assert_eq!(documents, expected_documents);
+    }
+
+    #[test]
+    fn test_borrowed_dashed_submission() {
+        let data = format!(
+            "<SUBMISSION>\n<ACCEPTANCE-DATETIME>20230803180000\n<TYPE>8-K\n<ITEMS>2.02\n<ITEMS>9.01\n\
+<FILER>\n<COMPANY-DATA>\n<CONFORMED-NAME>Apple Inc.\n<CIK>0000320193\n</COMPANY-DATA>\n</FILER>\n\
+<DOCUMENT>\n<TYPE>8-K\n<SEQUENCE>1\n<FILENAME>form8-k.htm\n<DESCRIPTION>CURRENT\nREPORT\n<TEXT>\n<html>body</html>\n</TEXT>\n</DOCUMENT>\n\
+<DOCUMENT>\n<TYPE>GRAPHIC\n<FILENAME>logo.jpg\n<TEXT>\n{}\n</TEXT>\n</DOCUMENT>\n</SUBMISSION>\n",
+            uuencode::uuencode("logo.jpg", b"\xFF\xD8\xFF\xE0payload")
+        );
+        let data = data.as_bytes();
+        assert_same_as_owned(data);
+
+        let parsed = ParsedSubmission::parse(data).unwrap();
+        assert_eq!(parsed.header.text("type"), Some("8-K"));
+        assert_eq!(parsed.header.get_all("ITEMS").count(), 2);
+        let company = parsed
+            .header
+            .get("filer")
+            .and_then(ValueRef::as_dict)
+            .and_then(|filer| filer.get("company-data"))
+            .and_then(ValueRef::as_dict)
+            .unwrap();
+        assert!(matches!(
+            company.get("cik"),
+            Some(ValueRef::Text(Cow::Borrowed("0000320193")))
+        ));
+
+        let primary = &parsed.documents[0];
+        assert!(primary.is_borrowed());
+        assert!(matches!(
+            primary.content,
+            Cow::Borrowed(b"<html>body</html>\n")
+        ));
+        // Joined continuation lines are the only owned values
+        assert!(matches!(
+            primary.metadata.get("description"),
+            Some(ValueRef::Text(Cow::Owned(_)))
+        ));
+        assert!(matches!(
+            primary.metadata.get("filename"),
+            Some(ValueRef::Text(Cow::Borrowed("form8-k.htm")))
+        ));
+
+        assert!(!parsed.documents[1].is_borrowed());
+        assert_eq!(&*parsed.documents[1].content, b"\xFF\xD8\xFF\xE0payload");
+    }
+
+    #[test]
+    fn test_borrowed_tab_and_wrapped_headers() {
+        let tab = b"<SEC-DOCUMENT>0000950123-95-000001.txt : 19950103\n\
+<SEC-HEADER>0000950123-95-000001.hdr.sgml : 19950103\n\
+ACCESSION NUMBER:\t\t0000950123-95-000001\n\
+CONFORMED SUBMISSION TYPE:\t8-K\n\
+FILER:\n\
+\n\
+\tCOMPANY DATA:\t\n\
+\t\tCOMPANY CONFORMED NAME:\t\t\tEXAMPLE CORP\n\
+\t\tCENTRAL INDEX KEY:\t\t\t0000000001\n\
+\n\
+\tFILING VALUES:\n\
+\t\tFORM TYPE:\t\t8-K\n\
+</SEC-HEADER>\n\
+<DOCUMENT>\n<TYPE>8-K\n<TEXT>\nBody\n</TEXT>\n</DOCUMENT>\n</SEC-DOCUMENT>\n";
+        assert_same_as_owned(tab);
+
+        let parsed = ParsedSubmission::parse(tab).unwrap();
+        let filer = parsed
+            .header
+            .get("filer")
+            .and_then(ValueRef::as_dict)
+            .unwrap();
+        let keys: Vec<&str> = filer.entries.iter().map(|(key, _)| &**key).collect();
+        assert_eq!(keys, vec!["COMPANY DATA", "FILING VALUES"]);
+
+        let hdr_sgml = b"<SEC-HEADER>0000320193-20-000096.hdr.sgml : 20201030\n\
+<ACCESSION-NUMBER>0000320193-20-000096\n<FILER>\n<COMPANY-DATA>\n<CONFORMED-NAME>Apple Inc.\n\
+</COMPANY-DATA>\n</FILER>\n</SEC-HEADER>\n";
+        assert_same_as_owned(hdr_sgml);
+    }
+
+    #[test]
+    fn test_submission_index_decodes_on_demand() {
+        let graphic = uuencode::uuencode("logo.jpg", b"\xFF\xD8\xFF\xE0logo");
+        let data = format!(
+            "<SUBMISSION>\n<TYPE>10-K\n\
+<DOCUMENT>\n<TYPE>10-K\n<TEXT>\nAnnual report\n</TEXT>\n</DOCUMENT>\n\
+<DOCUMENT>\n<TYPE>GRAPHIC\n<FILENAME>logo.jpg\n<TEXT>\n{}\n</TEXT>\n</DOCUMENT>\n\
+<DOCUMENT>\n<TYPE>EX-21\n<TEXT>\nSubsidiaries\n</TEXT>\n</DOCUMENT>\n</SUBMISSION>\n",
+            graphic
+        );
+        let index = SubmissionIndex::build(data.as_bytes()).unwrap();
+
+        assert_eq!(index.len(), 3);
+        assert_eq!(index.header.text("type"), Some("10-K"));
+        assert_eq!(index.find_type("ex-21"), Some(2));
+        assert_eq!(index.find_type("EX-99"), None);
+        assert!(matches!(
+            index.decode(2),
+            Some(Cow::Borrowed(b"Subsidiaries\n"))
+        ));
+
+        assert!(index
+            .raw(1)
+            .unwrap()
+            .trim_ascii()
+            .starts_with(b"begin 644 logo.jpg"));
+        assert_eq!(&*index.decode(1).unwrap(), b"\xFF\xD8\xFF\xE0logo");
+        assert_eq!(
+            index.metadata(1).unwrap().text("filename"),
+            Some("logo.jpg")
+        );
+        assert!(index.document(3).is_none());
+
+        let parsed = ParsedSubmission::parse(data.as_bytes()).unwrap();
+        let documents: Vec<DocumentRef> = (0..index.len())
+            .map(|doc_idx| index.document(doc_idx).unwrap())
+            .collect();
+        assert_eq!(parsed.documents, documents);
+    }
+}
@@ -1,55 +1,42 @@
+use std::borrow::Cow;
+use std::ops::Range;
+
+use crate::attachments::add_attachment_check;
+use crate::detect::{content_start, detect_submission_format};
+use crate::schema::apply_always_list;
+use crate::tag_tree::parse_tagged_header;
 use crate::types::{
-    fast_map_with_capacity, DocumentIndex, FastMap, MetadataDict, MetadataValue, ParseError, Span,
-    SubmissionType, TagScanner, TagType,
+    fast_map_with_capacity, ContinuationJoin, DocumentIndex, MetadataDict, MetadataValue,
+    ParseError, ParseLimits, ParseOptions, SubmissionType,
 };
+use crate::utils::{extract_index_headers, map_in_order, DocumentMetadataBuilder, MetadataSink};
 use crate::uu_decoder;
-use memchr::{memchr, memmem};
-use rustc_hash::FxHashMap;
-use smallvec::SmallVec;
-use std::collections::hash_map::Entry;
+use memchr::{memchr, memmem, memrchr};
 
 // Constants for common tag pattern detection
-const DOCUMENT_OPEN: &[u8] = b"<DOCUMENT>";
-const DOCUMENT_CLOSE: &[u8] = b"</DOCUMENT>";
-const TEXT_OPEN: &[u8] = b"<TEXT>";
-const TEXT_CLOSE: &[u8] = b"</TEXT>";
-const PRIVACY_MSG: &[u8] = b"-----BEGIN PRIVACY-ENHANCED MESSAGE-----";
-const SEC_DOCUMENT: &[u8] = b"<SEC-DOCUMENT>";
-const SUBMISSION: &[u8] = b"<SUBMISSION>";
-const ANGLE_OPEN: u8 = b'<';
-const ANGLE_CLOSE: u8 = b'>';
-const NEWLINE: u8 = b'\n';
+pub(crate) const DOCUMENT_OPEN: &[u8] = b"<DOCUMENT>";
+pub(crate) const DOCUMENT_CLOSE: &[u8] = b"</DOCUMENT>";
+pub(crate) const TEXT_OPEN: &[u8] = b"<TEXT>";
+pub(crate) const TEXT_CLOSE: &[u8] = b"</TEXT>";
+pub(crate) const PRIVACY_MSG: &[u8] = b"-----BEGIN PRIVACY-ENHANCED MESSAGE-----";
+const TEXT_WRAPPERS: [(&[u8], &[u8]); 3] = [
+    (b"<PDF>", b"</PDF>"),
+    (b"<XBRL>", b"</XBRL>"),
+    (b"<XML>", b"</XML>"),
+];
+pub(crate) const SEC_HEADER: &[u8] = b"<SEC-HEADER>";
+pub(crate) const IMS_HEADER: &[u8] = b"<IMS-HEADER>";
+pub(crate) const ANGLE_OPEN: u8 = b'<';
+pub(crate) const ANGLE_CLOSE: u8 = b'>';
+pub(crate) const NEWLINE: u8 = b'\n';
 const CR: u8 = b'\r';
 const SPACE: u8 = b' ';
-const SLASH: u8 = b'/';
-const COLON: u8 = b':';
-
-/// Detect submission type from first line
-#[inline]
-fn detect_submission_type(data: &[u8]) -> Result<SubmissionType, ParseError> {
-    // Find the first line end
-    let line_end = memchr(NEWLINE, data).unwrap_or(data.len());
-    let first_line = &data[..line_end];
-
-    // Check for known submission types using fast byte comparison
-    if memmem::find(first_line, SUBMISSION).is_some() {
-        Ok(SubmissionType::DashedDefault)
-    } else if memmem::find(first_line, PRIVACY_MSG).is_some() {
-        Ok(SubmissionType::TabPrivacy)
-    } else if memmem::find(first_line, SEC_DOCUMENT).is_some() {
-        Ok(SubmissionType::TabDefault)
-    } else {
-        // Only allocate a string for error case
-        let error_str =
-            String::from_utf8_lossy(&first_line[..std::cmp::min(100, first_line.len())])
-                .to_string();
-        Err(ParseError::UnknownSubmissionType(error_str))
-    }
-}
+pub(crate) const SLASH: u8 = b'/';
+pub(crate) const COLON: u8 = b':';
 
 /// Fast line indexing
 #[inline]
-fn index_lines(data: &[u8]) -> Vec<(usize, usize)> {
+pub(crate) fn index_lines(data: &[u8]) -> Vec<(usize, usize)> {
     let mut line_indices = Vec::with_capacity(data.len() / 50); // Estimate lines
     let mut pos = 0;
     let mut line_start = 0;
@@ -79,85 +66,77 @@ fn index_lines(data: &[u8]) -> Vec<(usize, usize)> {
 
 /// Fast tag detection using memchr and byte comparison
 #[inline]
-fn find_tag(data: &[u8], tag: &[u8], start_pos: usize) -> Option<usize> {
+pub(crate) fn find_tag(data: &[u8], tag: &[u8], start_pos: usize) -> Option<usize> {
     memmem::find(&data[start_pos..], tag).map(|pos| pos + start_pos)
 }
 
 /// Optimized document structure indexing using byte operations
-fn build_document_index(data: &[u8], line_indices: &[(usize, usize)]) -> DocumentIndex {
+///
+/// Documents are read in order. The first `<TEXT>` before a document's
+/// `</DOCUMENT>` starts its text, which runs to the next `</TEXT>`; only
+/// whitespace may come between that and the `</DOCUMENT>` ending the
+/// document. Documents without text, or with anything else after it, are
+/// kept in `document_positions` so they count towards the limits, but get
+/// no entry in `text_positions`. [`StreamParser`](crate::StreamParser)
+/// follows the same rules.
+pub(crate) fn build_document_index(data: &[u8]) -> DocumentIndex {
     let mut index = DocumentIndex::new();
-    let mut doc_start: isize = -1;
-    let mut text_start: isize = -1;
 
     // Create SIMD-friendly searchers for common tags
     let doc_open_finder = memmem::Finder::new(DOCUMENT_OPEN);
     let doc_close_finder = memmem::Finder::new(DOCUMENT_CLOSE);
     let text_open_finder = memmem::Finder::new(TEXT_OPEN);
     let text_close_finder = memmem::Finder::new(TEXT_CLOSE);
+    let find = |finder: &memmem::Finder, start: usize, end: usize| {
+        finder.find(&data[start..end]).map(|pos| pos + start)
+    };
 
-    // Pre-calculate all document open/close positions
-    let all_doc_opens: Vec<_> = doc_open_finder.find_iter(data).collect();
-    let all_doc_closes: Vec<_> = doc_close_finder.find_iter(data).collect();
-    let all_text_opens: Vec<_> = text_open_finder.find_iter(data).collect();
+    // Find header end (first document tag), header-only inputs run to the end
+    index.header_end = doc_open_finder.find(data).unwrap_or(data.len());
 
-    // Find header end (first document tag)
-    if let Some(&first_doc) = all_doc_opens.first() {
-        index.header_end = first_doc;
-    }
-
-    // Build document positions with optimized algorithm
-    let mut doc_positions = Vec::with_capacity(all_doc_opens.len());
-    for (&open_pos, &close_pos) in all_doc_opens.iter().zip(all_doc_closes.iter()) {
-        if open_pos < close_pos {
-            doc_positions.push((open_pos, close_pos));
-        }
-    }
-    index.document_positions = doc_positions;
-
-    // Find text sections efficiently
-    let mut text_positions = Vec::with_capacity(all_text_opens.len());
-    for &text_start in &all_text_opens {
-        // Find the closest text end
-        if let Some(text_end_pos) = text_close_finder
-            .find(&data[text_start..])
-            .map(|pos| pos + text_start)
-        {
-            // Check if the next non-whitespace tag is document close
-            let mut pos = text_end_pos + TEXT_CLOSE.len();
-            while pos < data.len()
-                && (data[pos] == SPACE || data[pos] == NEWLINE || data[pos] == CR)
-            {
-                pos += 1;
-            }
+    let mut pos = index.header_end;
+    while let Some(doc_start) = find(&doc_open_finder, pos, data.len()) {
+        let body_start = doc_start + DOCUMENT_OPEN.len();
+        // A document cut off before `</DOCUMENT>` is dropped
+        let Some(doc_end) = find(&doc_close_finder, body_start, data.len()) else {
+            break;
+        };
+        let Some(text_start) = find(&text_open_finder, body_start, doc_end) else {
+            index.document_positions.push((doc_start, doc_end));
+            pos = doc_end + DOCUMENT_CLOSE.len();
+            continue;
+        };
+        let Some(text_end) = find(&text_close_finder, text_start + TEXT_OPEN.len(), data.len())
+        else {
+            break;
+        };
 
-            // If we're at a document close tag, record this text position
-            if pos + DOCUMENT_CLOSE.len() <= data.len()
-                && &data[pos..pos + DOCUMENT_CLOSE.len()] == DOCUMENT_CLOSE
-            {
-                // Check for text leftovers
-                if pos > text_end_pos + TEXT_CLOSE.len() {
-                    let leftover_span = Span::new(text_end_pos + TEXT_CLOSE.len(), pos);
-                    if !leftover_span.is_empty() {
-                        // Only store non-empty leftovers
-                        let slice = leftover_span.slice(data);
-                        if slice.iter().any(|&b| b != SPACE && b != NEWLINE && b != CR) {
-                            index.text_leftovers.insert(text_end_pos, leftover_span);
-                        }
-                    }
-                }
-
-                text_positions.push((text_start, text_end_pos));
-            }
+        // Check if the next non-whitespace tag is document close
+        let after_text = text_end + TEXT_CLOSE.len();
+        let close = after_text
+            + data[after_text..]
+                .iter()
+                .take_while(|&&b| b == SPACE || b == NEWLINE || b == CR)
+                .count();
+        if data[close..].starts_with(DOCUMENT_CLOSE) {
+            index.document_positions.push((doc_start, close));
+            index.text_positions.push((text_start, text_end));
+            pos = close + DOCUMENT_CLOSE.len();
+        } else {
+            let Some(doc_end) = find(&doc_close_finder, close, data.len()) else {
+                break;
+            };
+            index.document_positions.push((doc_start, doc_end));
+            pos = doc_end + DOCUMENT_CLOSE.len();
         }
     }
-    index.text_positions = text_positions;
 
     index
 }
 
 /// Parse tag and content from byte slice
 #[inline]
-fn parse_tag_content<'a>(data: &'a [u8]) -> Option<(&'a [u8], &'a [u8])> {
+pub(crate) fn parse_tag_content(data: &[u8]) -> Option<(&[u8], &[u8])> {
     // Find tag end
     let tag_end = memchr(ANGLE_CLOSE, data)?;
     if tag_end < 1 {
@@ -177,315 +156,124 @@ fn parse_tag_content<'a>(data: &'a [u8]) -> Option<(&'a [u8], &'a [u8])> {
     Some((tag, content))
 }
 
-/// Convert ASCII bytes to lowercase in place using SIMD where available
-#[inline]
-fn ascii_to_lowercase(bytes: &[u8]) -> SmallVec<[u8; 64]> {
-    // For small tags, avoid heap allocation
-    let mut result = SmallVec::<[u8; 64]>::with_capacity(bytes.len());
-    result.extend(
-        bytes
-            .iter()
-            .map(|&b| if b >= b'A' && b <= b'Z' { b + 32 } else { b }),
-    );
-    result
-}
-
 /// Parse document metadata from byte range
-fn parse_document_metadata(data: &[u8], start: usize, end: usize) -> MetadataDict {
+pub(crate) fn parse_document_metadata<'a, S: MetadataSink<'a>>(
+    data: &'a [u8],
+    start: usize,
+    end: usize,
+    join: ContinuationJoin,
+) -> S {
     let slice = &data[start..end];
-    let mut metadata = FastMap::default();
-    let mut current_key = None;
-
-    let lines = index_lines(slice);
+    let mut builder = DocumentMetadataBuilder::new(join);
 
-    for &(line_start, line_end) in &lines {
+    for (line_start, line_end) in index_lines(slice) {
         let line = &slice[line_start..line_end];
 
-        if !line.is_empty() && line[0] == ANGLE_OPEN && memchr(ANGLE_CLOSE, line).is_some() {
-            if let Some((tag, content)) = parse_tag_content(line) {
-                // Convert tag to lowercase efficiently
-                let lowercase_tag = ascii_to_lowercase(tag);
-
-                // Trim content
-                let content = content.trim_ascii();
-
-                // Store current key and content
-                let key = String::from_utf8_lossy(&lowercase_tag).into_owned();
-                current_key = Some(key.clone());
-
-                // Only allocate string for non-empty content
-                if !content.is_empty() {
-                    let content_str = String::from_utf8_lossy(content).into_owned();
-                    metadata.insert(key, MetadataValue::Text(content_str));
-                }
-            }
-        } else if let Some(ref key) = current_key {
-            // Continuation of previous content
-            if let Some(MetadataValue::Text(ref mut text)) = metadata.get_mut(key) {
-                // Only process non-empty lines
-                if !line.is_empty() {
-                    // Append to existing text with space
-                    text.push(' ');
-                    text.push_str(&String::from_utf8_lossy(line.trim_ascii()));
-                }
-            }
-        }
-    }
-
-    metadata
-}
-
-/// Trim ASCII whitespace from byte slice
-trait TrimAscii {
-    fn trim_ascii(&self) -> &Self;
-    fn trim_ascii_start(&self) -> &Self;
-    fn trim_ascii_end(&self) -> &Self;
-}
-
-impl TrimAscii for [u8] {
-    fn trim_ascii(&self) -> &Self {
-        self.trim_ascii_start().trim_ascii_end()
-    }
-
-    fn trim_ascii_start(&self) -> &Self {
-        let mut bytes = self;
-        while !bytes.is_empty()
-            && (bytes[0] == SPACE || bytes[0] == NEWLINE || bytes[0] == CR || bytes[0] == b'\t')
-        {
-            bytes = &bytes[1..];
+        match parse_tag_content(line).filter(|_| line.first() == Some(&ANGLE_OPEN)) {
+            Some((tag, content)) => builder.tag(tag, String::from_utf8_lossy(content)),
+            None => builder.continuation(String::from_utf8_lossy(line)),
         }
-        bytes
     }
 
-    fn trim_ascii_end(&self) -> &Self {
-        let mut bytes = self;
-        while !bytes.is_empty()
-            && (bytes[bytes.len() - 1] == SPACE
-                || bytes[bytes.len() - 1] == NEWLINE
-                || bytes[bytes.len() - 1] == CR
-                || bytes[bytes.len() - 1] == b'\t')
-        {
-            bytes = &bytes[..bytes.len() - 1];
-        }
-        bytes
-    }
+    builder.finish()
 }
 
 /// Process text content, handling UU encoding
-fn process_text_content(data: &[u8]) -> Vec<u8> {
-    // Skip leading whitespace
-    let data = data.trim_ascii_start();
-
-    if data.is_empty() {
-        return Vec::new();
+///
+/// Returns the content and whether it was UU-decoded. A UU block may be
+/// wrapped in `<PDF>`, `<XBRL>` or `<XML>`. Plain text is borrowed, with
+/// any such wrapper lines removed.
+pub(crate) fn process_text_content(data: &[u8]) -> (Cow<'_, [u8]>, bool) {
+    // Skip leading blank lines, keeping the indentation of the first line
+    let trimmed = data.trim_ascii_start();
+    if trimmed.is_empty() {
+        return (Cow::Borrowed(trimmed), false);
     }
+    let skipped = &data[..data.len() - trimmed.len()];
+    let text = &data[memrchr(NEWLINE, skipped).map_or(0, |pos| pos + 1)..];
+    let data = trimmed;
+
+    let unwrapped = [&b"<PDF>"[..], b"<XBRL>", b"<XML>"]
+        .iter()
+        .find_map(|tag| data.strip_prefix(*tag))
+        .map_or(data, <[u8]>::trim_ascii_start);
 
     // Check for UU encoding by comparing against "begin" prefix
-    if data.len() >= 5 && &data[0..5] == b"begin" {
+    if unwrapped.starts_with(b"begin") {
         // UU decode the content
-        uu_decoder::decode(data)
+        (Cow::Owned(uu_decoder::decode(unwrapped)), true)
     } else {
-        // For regular text, just copy the bytes
-        data.to_vec()
+        (Cow::Borrowed(&text[unwrapped_range(text)]), false)
     }
 }
 
-/// Parse dashed default header format
-fn parse_dashed_default_header(data: &[u8], end: usize) -> MetadataDict {
-    let mut header_metadata = fast_map_with_capacity(50);
-    let mut tag_stack: Vec<SmallVec<[u8; 64]>> = Vec::with_capacity(16);
-    let mut dict_stack: Vec<MetadataDict> = Vec::with_capacity(16);
-    dict_stack.push(header_metadata);
-
-    let lines = index_lines(&data[..end]);
-
-    for (i, &(line_start, line_end)) in lines.iter().enumerate() {
-        let line = &data[line_start..line_end];
-
-        // Skip empty lines or lines without angle brackets
-        if line.is_empty() || memchr(ANGLE_CLOSE, line).is_none() {
-            continue;
-        }
-
-        if let Some((tag, content)) = parse_tag_content(line) {
-            // Convert tag to lowercase efficiently
-            let lowercase_tag = ascii_to_lowercase(tag);
-
-            // Check if this is a closing tag
-            if !lowercase_tag.is_empty() && lowercase_tag[0] == SLASH {
-                let tag_name = &lowercase_tag[1..]; // Remove slash
-
-                if tag_stack.last().map_or(false, |t| t.as_slice() == tag_name) {
-                    tag_stack.pop();
-                    dict_stack.pop();
-                }
-                continue;
-            }
-
-            // Look ahead to check if this tag has a closing tag - limit search range
-            let search_end = (i + 100).min(lines.len());
-            let mut has_closing_tag = false;
-
-            let closing_tag = SmallVec::<[u8; 64]>::from_iter(
-                [SLASH].iter().cloned().chain(lowercase_tag.iter().cloned()),
-            );
-
-            for j in i + 1..search_end {
-                let (next_start, next_end) = lines[j];
-                let next_line = &data[next_start..next_end];
-
-                if let Some((next_tag, _)) = parse_tag_content(next_line) {
-                    let next_lowercase = ascii_to_lowercase(next_tag);
-                    if next_lowercase.as_slice() == closing_tag.as_slice() {
-                        has_closing_tag = true;
-                        break;
-                    }
-                }
-            }
+/// Range of `data` inside a `<PDF>`, `<XBRL>` or `<XML>` line and the last
+/// line closing it, or all of `data` when it is not wrapped
+///
+/// `data` must not start with whitespace.
+pub(crate) fn unwrapped_range(data: &[u8]) -> Range<usize> {
+    let all = 0..data.len();
+    let Some(first_end) = memchr(NEWLINE, data) else {
+        return all;
+    };
+    let first_line = data[..first_end].trim_ascii();
+    let Some(&(_, close)) = TEXT_WRAPPERS.iter().find(|(open, _)| *open == first_line) else {
+        return all;
+    };
 
-            // Get current dictionary
-            let current_dict = dict_stack.last_mut().unwrap();
-
-            if has_closing_tag {
-                // Create nested dict and handle insertion to parent dict
-                let nested_dict = fast_map_with_capacity(10);
-                let tag_str = String::from_utf8_lossy(&lowercase_tag).into_owned();
-
-                // Efficient insertion using entry API
-                match current_dict.entry(tag_str.clone()) {
-                    Entry::Occupied(mut entry) => match entry.get_mut() {
-                        MetadataValue::Dict(existing) => {
-                            let mut list = Vec::with_capacity(2);
-                            list.push(MetadataValue::Dict(existing.clone()));
-                            list.push(MetadataValue::Dict(nested_dict.clone()));
-                            entry.insert(MetadataValue::List(list));
-                        }
-                        MetadataValue::List(list) => {
-                            list.push(MetadataValue::Dict(nested_dict.clone()));
-                        }
-                        MetadataValue::Text(existing_text) => {
-                            let existing_text_clone = existing_text.clone();
-                            let list = vec![
-                                MetadataValue::Text(existing_text_clone),
-                                MetadataValue::Dict(nested_dict.clone()),
-                            ];
-                            entry.insert(MetadataValue::List(list));
-                        }
-                    },
-                    Entry::Vacant(entry) => {
-                        entry.insert(MetadataValue::Dict(nested_dict.clone()));
-                    }
-                }
-
-                // Push to stacks
-                tag_stack.push(lowercase_tag);
-                dict_stack.push(nested_dict);
-            } else {
-                // Process text content
-                let trimmed_content = content.trim_ascii();
-                if !trimmed_content.is_empty() {
-                    let content_str = String::from_utf8_lossy(trimmed_content).into_owned();
-                    let tag_str = String::from_utf8_lossy(&lowercase_tag).into_owned();
-
-                    // Efficient insertion using entry API
-                    match current_dict.entry(tag_str) {
-                        Entry::Occupied(mut entry) => match entry.get_mut() {
-                            MetadataValue::Text(existing) => {
-                                let existing_clone = existing.clone();
-                                let list = vec![
-                                    MetadataValue::Text(existing_clone),
-                                    MetadataValue::Text(content_str),
-                                ];
-                                entry.insert(MetadataValue::List(list));
-                            }
-                            MetadataValue::List(list) => {
-                                list.push(MetadataValue::Text(content_str));
-                            }
-                            MetadataValue::Dict(_) => {
-                                let existing = entry.get().clone();
-                                let list = vec![existing, MetadataValue::Text(content_str)];
-                                entry.insert(MetadataValue::List(list));
-                            }
-                        },
-                        Entry::Vacant(entry) => {
-                            entry.insert(MetadataValue::Text(content_str));
-                        }
-                    }
-                }
-            }
+    let start = first_end + 1;
+    let mut end = data.len();
+    while end > start {
+        let line_start =
+            memrchr(NEWLINE, &data[start..end - 1]).map_or(start, |pos| start + pos + 1);
+        if data[line_start..end].trim_ascii() == close {
+            return start..line_start;
         }
+        end = line_start;
     }
+    all
+}
 
-    // Return the root dictionary without cloning
-    if dict_stack.is_empty() {
-        FastMap::default()
-    } else {
-        dict_stack.remove(0)
-    }
+/// Parse dashed default header format
+pub(crate) fn parse_dashed_default_header<'a, S: MetadataSink<'a>>(
+    data: &'a [u8],
+    end: usize,
+    limits: &ParseLimits,
+) -> Result<S, ParseError> {
+    let data = &data[..end];
+    parse_tagged_header(
+        index_lines(data)
+            .into_iter()
+            .map(|(line_start, line_end)| &data[line_start..line_end]),
+        limits,
+    )
 }
 
 /// Parse tab-formatted header
-fn parse_tab_header(data: &[u8], end: usize, submission_type: &SubmissionType) -> MetadataDict {
-    let mut header_metadata = fast_map_with_capacity(50);
-
-    // Handle privacy-enhanced message if needed
+pub(crate) fn parse_tab_header<'a, S: MetadataSink<'a>>(
+    data: &'a [u8],
+    end: usize,
+    submission_type: &SubmissionType,
+    limits: &ParseLimits,
+) -> Result<S, ParseError> {
+    let mut data = &data[..end];
+    let mut header_metadata = S::default();
+
+    // Handle privacy-enhanced message if needed. Its `Proc-Type:` and
+    // `MIC-Info:` preamble lines belong to the PEM envelope, not the
+    // header, so keys are only read from the first tag line after it
     if *submission_type == SubmissionType::TabPrivacy {
-        let privacy_finder = memmem::Finder::new(PRIVACY_MSG);
-
-        if let Some(start_pos) = privacy_finder.find(&data[..end]) {
-            let mut privacy_msg = Vec::new();
-            let mut pos = start_pos + PRIVACY_MSG.len();
-
-            // Skip to next line
-            while pos < end && data[pos] != NEWLINE {
-                pos += 1;
-            }
-            pos += 1; // Skip the newline
-
-            // Read until we find a line with < and uppercase character
-            let mut line_start = pos;
-            while pos < end {
-                if data[pos] == NEWLINE {
-                    let line = &data[line_start..pos];
-
-                    // Check if line contains < and an uppercase character
-                    if memchr(ANGLE_OPEN, line).is_some()
-                        && line.iter().any(|&b| b >= b'A' && b <= b'Z')
-                    {
-                        break;
-                    }
-
-                    // Add line to privacy message
-                    privacy_msg.push(line.to_vec());
-                    line_start = pos + 1;
-                }
-                pos += 1;
-            }
-
-            // Join privacy message lines with newlines
-            if !privacy_msg.is_empty() {
-                let joined = privacy_msg
-                    .iter()
-                    .flat_map(|line| line.iter().chain([NEWLINE].iter()).cloned())
-                    .collect::<Vec<_>>();
-
-                let msg_str = String::from_utf8_lossy(&joined).into_owned();
-                header_metadata.insert(
-                    "privacy-enhanced-message".to_string(),
-                    MetadataValue::Text(msg_str),
-                );
-            }
+        if let Some((message, header_start)) = privacy_message(data) {
+            header_metadata.push_text(b"privacy-enhanced-message", Cow::Owned(message));
+            data = &data[header_start..];
         }
     }
 
-    // Use indentation to track nesting
-    let mut indent_stack: Vec<usize> = vec![0];
-    let mut dict_stack: Vec<MetadataDict> = Vec::with_capacity(16);
-    dict_stack.push(header_metadata);
+    // Use indentation to track nesting; each open dict has the indentation
+    // and tag of the line that opened it
+    let mut stack: Vec<(usize, &[u8], S)> = Vec::with_capacity(16);
 
-    let lines = index_lines(&data[..end]);
-
-    for &(line_start, line_end) in &lines {
+    for (line_start, line_end) in index_lines(data) {
         let line = &data[line_start..line_end];
         if line.is_empty() {
             continue;
@@ -499,138 +287,188 @@ fn parse_tab_header(data: &[u8], end: usize, submission_type: &SubmissionType) -
 
         // Parse tag and text
         let (tag, text) = if memchr(ANGLE_CLOSE, line).is_some() {
-            if let Some((tag, content)) = parse_tag_content(line) {
-                // Convert tag to lowercase efficiently
-                let lowercase_tag = ascii_to_lowercase(tag);
-
+            match parse_tag_content(line) {
                 // Skip closing tags
-                if !lowercase_tag.is_empty() && lowercase_tag[0] == SLASH {
-                    continue;
-                }
-
-                (lowercase_tag, content)
-            } else {
-                continue;
+                Some((tag, _)) if tag.first() == Some(&SLASH) => continue,
+                Some(tag_content) => tag_content,
+                None => continue,
             }
-        } else if memchr(COLON, line).is_some() {
+        } else if let Some(pos) = memchr(COLON, line) {
             // Handle key-value pairs with colon
-            if let Some(pos) = memchr(COLON, line) {
-                let tag = &line[..pos];
-                let content = if pos + 1 < line.len() {
-                    &line[pos + 1..]
-                } else {
-                    b""
-                };
-
-                (ascii_to_lowercase(tag), content)
-            } else {
-                continue;
-            }
+            (line[..pos].trim_ascii(), &line[pos + 1..])
         } else {
             continue;
         };
 
         // Find appropriate parent based on indentation
-        while indent_stack.len() > 1 && indent_stack.last().unwrap() >= &indent {
-            indent_stack.pop();
-            dict_stack.pop();
+        while stack.last().is_some_and(|&(last, _, _)| last >= indent) {
+            close_nested_dict(&mut stack, &mut header_metadata);
         }
 
-        let current_dict = dict_stack.last_mut().unwrap();
         let trimmed_text = text.trim_ascii();
-        let tag_str = String::from_utf8_lossy(&tag).into_owned();
-
         if !trimmed_text.is_empty() {
             // Add text value
-            let text_str = String::from_utf8_lossy(trimmed_text).into_owned();
-
-            // Use entry API for efficient insertion
-            match current_dict.entry(tag_str) {
-                Entry::Occupied(mut entry) => match entry.get_mut() {
-                    MetadataValue::Text(existing) => {
-                        let existing_clone = existing.clone();
-                        let list = vec![
-                            MetadataValue::Text(existing_clone),
-                            MetadataValue::Text(text_str),
-                        ];
-                        entry.insert(MetadataValue::List(list));
-                    }
-                    MetadataValue::List(list) => {
-                        list.push(MetadataValue::Text(text_str));
-                    }
-                    MetadataValue::Dict(_) => {
-                        let existing = entry.get().clone();
-                        let list = vec![existing, MetadataValue::Text(text_str)];
-                        entry.insert(MetadataValue::List(list));
-                    }
-                },
-                Entry::Vacant(entry) => {
-                    entry.insert(MetadataValue::Text(text_str));
-                }
-            }
+            let parent = stack
+                .last_mut()
+                .map_or(&mut header_metadata, |(_, _, dict)| dict);
+            parent.push_text(tag, String::from_utf8_lossy(trimmed_text));
         } else {
-            // Create nested dict
-            let nested_dict = fast_map_with_capacity(10);
-
-            // Use entry API for efficient insertion
-            match current_dict.entry(tag_str) {
-                Entry::Occupied(mut entry) => match entry.get_mut() {
-                    MetadataValue::Dict(existing) => {
-                        let existing_clone = existing.clone();
-                        let list = vec![
-                            MetadataValue::Dict(existing_clone),
-                            MetadataValue::Dict(nested_dict.clone()),
-                        ];
-                        entry.insert(MetadataValue::List(list));
-                    }
-                    MetadataValue::List(list) => {
-                        list.push(MetadataValue::Dict(nested_dict.clone()));
-                    }
-                    MetadataValue::Text(_) => {
-                        let existing = entry.get().clone();
-                        let list = vec![existing, MetadataValue::Dict(nested_dict.clone())];
-                        entry.insert(MetadataValue::List(list));
-                    }
-                },
-                Entry::Vacant(entry) => {
-                    entry.insert(MetadataValue::Dict(nested_dict.clone()));
-                }
-            }
+            // Nested dict is attached to its parent once indentation drops
+            // back; the header and every open dict sit above it
+            limits.check_header_depth(stack.len() + 2)?;
+            stack.push((indent, tag, S::default()));
+        }
+    }
 
-            // Push new context to stacks
-            indent_stack.push(indent);
-            dict_stack.push(nested_dict);
+    while !stack.is_empty() {
+        close_nested_dict(&mut stack, &mut header_metadata);
+    }
+
+    Ok(header_metadata)
+}
+
+/// Pop the innermost nested dict and attach it to its parent under its tag
+fn close_nested_dict<'a, S: MetadataSink<'a>>(
+    stack: &mut Vec<(usize, &'a [u8], S)>,
+    header: &mut S,
+) {
+    if let Some((_, tag, nested_dict)) = stack.pop() {
+        let parent = stack.last_mut().map_or(header, |(_, _, dict)| dict);
+        parent.push_dict(tag, nested_dict);
+    }
+}
+
+/// The privacy-enhanced message and the offset of the header after it
+///
+/// The message is the trimmed lines after the marker up to a blank or tag
+/// line. The header starts at the first tag line after the marker.
+fn privacy_message(data: &[u8]) -> Option<(String, usize)> {
+    let marker = memmem::find(data, PRIVACY_MSG)?;
+    let start = memchr(NEWLINE, &data[marker..]).map_or(data.len(), |pos| marker + pos + 1);
+
+    let mut message: Vec<&[u8]> = Vec::new();
+    let mut in_message = true;
+    let mut line_start = start;
+    while line_start < data.len() {
+        let line_end =
+            memchr(NEWLINE, &data[line_start..]).map_or(data.len(), |pos| line_start + pos);
+        let line = data[line_start..line_end].trim_ascii();
+        // A line with < and an uppercase character starts the header
+        if memchr(ANGLE_OPEN, line).is_some() && line.iter().any(u8::is_ascii_uppercase) {
+            break;
+        }
+        in_message &= !line.is_empty();
+        if in_message {
+            message.push(line);
         }
+        line_start = line_end + 1;
     }
+    let message = String::from_utf8_lossy(&message.join(&NEWLINE)).into_owned();
+    Some((message, line_start.min(data.len())))
+}
 
-    // Return the root dictionary without cloning
-    if dict_stack.is_empty() {
-        FastMap::default()
+/// Parse a header wrapped in `<SEC-HEADER>` or `<IMS-HEADER>`
+///
+/// Wrapped lines are tagged in `.hdr.sgml` files and tab-formatted in older
+/// `.txt` submissions. The wrapper line's own value is kept under its key.
+pub(crate) fn parse_wrapped_header<'a, S: MetadataSink<'a>>(
+    data: &'a [u8],
+    end: usize,
+    wrapper: &'static [u8],
+    limits: &ParseLimits,
+) -> Result<S, ParseError> {
+    let data = &data[..end];
+    let first_line_end = memchr(NEWLINE, data).map_or(data.len(), |pos| pos + 1);
+    let wrapper_name = &wrapper[1..wrapper.len() - 1];
+
+    let mut close_tag = Vec::with_capacity(wrapper.len() + 1);
+    close_tag.extend_from_slice(b"</");
+    close_tag.extend_from_slice(&wrapper[1..]);
+    let inner_end = find_tag(data, &close_tag, first_line_end).unwrap_or(data.len());
+    let inner = &data[first_line_end..inner_end];
+
+    let mut metadata: S = if is_tab_formatted(inner) {
+        parse_tab_header(inner, inner.len(), &SubmissionType::TabDefault, limits)?
     } else {
-        dict_stack.remove(0)
+        parse_dashed_default_header(inner, inner.len(), limits)?
+    };
+
+    let value = data[wrapper.len().min(first_line_end)..first_line_end].trim_ascii();
+    if !value.is_empty() {
+        metadata.set_text(wrapper_name, String::from_utf8_lossy(value));
     }
+
+    Ok(metadata)
+}
+
+/// Check whether header lines use the `KEY:<tab>value` layout
+pub(crate) fn is_tab_formatted(data: &[u8]) -> bool {
+    index_lines(data).iter().any(|&(start, end)| {
+        let line = data[start..end].trim_ascii();
+        !line.is_empty() && line[0] != ANGLE_OPEN && memchr(COLON, line).is_some()
+    })
 }
 
 /// Main parsing function - processes a byte array and returns metadata and documents
 pub fn parse_sgml_bytes(data: &[u8]) -> Result<(MetadataDict, Vec<Vec<u8>>), ParseError> {
-    if data.is_empty() {
+    parse_sgml_bytes_with_options(data, &ParseOptions::default())
+}
+
+/// Parse a byte array with explicit options
+pub fn parse_sgml_bytes_with_options(
+    data: &[u8],
+    options: &ParseOptions,
+) -> Result<(MetadataDict, Vec<Vec<u8>>), ParseError> {
+    if data.trim_ascii().is_empty() {
         return Err(ParseError::InvalidContent("Empty content".to_string()));
     }
+    options.limits.check_input_size(data.len())?;
+    options.limits.check_line_lengths(data)?;
+
+    // Detect submission type, skipping any BOM or preamble
+    let (submission_type, offset) = match &options.submission_type {
+        Some(forced) => (forced.clone(), content_start(data)),
+        None => {
+            let detection = detect_submission_format(data)?;
+            (detection.submission_type, detection.offset)
+        }
+    };
 
-    // Detect submission type
-    let submission_type = detect_submission_type(data)?;
+    // `-index-headers.html` pages carry an HTML-escaped `<SEC-HEADER>` block
+    if submission_type == SubmissionType::IndexHeaders {
+        let header = extract_index_headers(data).ok_or_else(|| {
+            ParseError::InvalidContent("No SEC header found in HTML page".to_string())
+        })?;
+        let mut header_options = options.clone();
+        header_options.submission_type = None;
+        return parse_sgml_bytes_with_options(&header, &header_options);
+    }
 
-    // Create line index
-    let lines = index_lines(data);
+    let data = &data[offset..];
 
     // Get document structure index
-    let doc_index = build_document_index(data, &lines);
+    let doc_index = build_document_index(data);
+    options
+        .limits
+        .check_documents(doc_index.document_positions.len())?;
 
     // Parse header metadata
+    let limits = &options.limits;
     let mut metadata = match submission_type {
-        SubmissionType::DashedDefault => parse_dashed_default_header(data, doc_index.header_end),
-        _ => parse_tab_header(data, doc_index.header_end, &submission_type),
+        SubmissionType::DashedDefault => {
+            parse_dashed_default_header(data, doc_index.header_end, limits)?
+        }
+        SubmissionType::SecHeader => {
+            parse_wrapped_header(data, doc_index.header_end, SEC_HEADER, limits)?
+        }
+        SubmissionType::ImsHeader => {
+            parse_wrapped_header(data, doc_index.header_end, IMS_HEADER, limits)?
+        }
+        _ => parse_tab_header(data, doc_index.header_end, &submission_type, limits)?,
     };
+    if options.always_list {
+        apply_always_list(&mut metadata);
+    }
 
     // Create fast lookup map for text positions
     let mut text_position_map = fast_map_with_capacity(doc_index.text_positions.len());
@@ -638,46 +476,45 @@ pub fn parse_sgml_bytes(data: &[u8]) -> Result<(MetadataDict, Vec<Vec<u8>>), Par
         text_position_map.insert(start, end);
     }
 
-    // Process documents using indexed positions
-    let mut documents = Vec::with_capacity(doc_index.document_positions.len());
-    let mut doc_metadata_list = Vec::with_capacity(doc_index.document_positions.len());
+    // Process documents using indexed positions, in parallel with the
+    // `parallel` feature
+    let decoded = map_in_order(&doc_index.document_positions, |&(doc_start, doc_end)| {
+        // Scan for TEXT tag within document bounds
+        let Some(text_start) = find_tag(data, TEXT_OPEN, doc_start)
+            .filter(|&pos| pos < doc_end && text_position_map.contains_key(&pos))
+        else {
+            return Ok(None);
+        };
+        let text_end = text_position_map[&text_start];
 
-    for &(doc_start, doc_end) in &doc_index.document_positions {
-        // Find corresponding text section efficiently
-        let mut text_range = None;
+        // Extract document metadata (start+len of DOCUMENT tag to start of TEXT tag)
+        let mut doc_metadata: MetadataDict = parse_document_metadata(
+            data,
+            doc_start + DOCUMENT_OPEN.len(),
+            text_start,
+            options.continuation_join,
+        );
 
-        // Scan for TEXT tag within document bounds
-        if let Some(text_start_pos) = find_tag(data, TEXT_OPEN, doc_start) {
-            if text_start_pos < doc_end && text_position_map.contains_key(&text_start_pos) {
-                let text_end = text_position_map[&text_start_pos];
-                text_range = Some((text_start_pos, text_end));
-            }
-        }
+        // Get text content (start+len of TEXT tag to end of TEXT)
+        let text_content = &data[text_start + TEXT_OPEN.len()..text_end];
+
+        let (processed_content, decoded) = process_text_content(text_content);
+        options.limits.check_decoded_size(processed_content.len())?;
 
-        if let Some((text_start, text_end)) = text_range {
-            // Extract document metadata (start+len of DOCUMENT tag to start of TEXT tag)
-            let doc_metadata =
-                parse_document_metadata(data, doc_start + DOCUMENT_OPEN.len(), text_start);
-            doc_metadata_list.push(MetadataValue::Dict(doc_metadata));
-
-            // Get text content (start+len of TEXT tag to end of TEXT)
-            let text_content = &data[text_start + TEXT_OPEN.len()..text_end];
-
-            // Handle leftovers after TEXT tag
-            let processed_content =
-                if let Some(leftover_span) = doc_index.text_leftovers.get(&text_end) {
-                    // Join text content with leftover
-                    let mut combined = Vec::with_capacity(text_content.len() + leftover_span.len());
-                    combined.extend_from_slice(text_content);
-                    combined.extend_from_slice(leftover_span.slice(data));
-                    process_text_content(&combined)
-                } else {
-                    process_text_content(text_content)
-                };
-
-            documents.push(processed_content);
+        if options.verify_attachments && decoded {
+            add_attachment_check(&mut doc_metadata, &processed_content);
         }
-    }
+        Ok(Some((
+            MetadataValue::Dict(doc_metadata),
+            processed_content.into_owned(),
+        )))
+    });
+    let (doc_metadata_list, documents): (Vec<_>, Vec<_>) = decoded
+        .into_iter()
+        .collect::<Result<Vec<_>, ParseError>>()?
+        .into_iter()
+        .flatten()
+        .unzip();
 
     // Add document metadata to the metadata dictionary
     metadata.insert(
@@ -687,3 +524,251 @@ pub fn parse_sgml_bytes(data: &[u8]) -> Result<(MetadataDict, Vec<Vec<u8>>), Par
 
     Ok((metadata, documents))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hdr_sgml_header_only() {
+        let data = b"<SEC-HEADER>0000320193-20-000096.hdr.sgml : 20201030
+<ACCESSION-NUMBER>0000320193-20-000096
+<FILER>
+<COMPANY-DATA>
+<CONFORMED-NAME>Apple Inc.
+</COMPANY-DATA>
+</FILER>
+</SEC-HEADER>
+";
+        let (metadata, documents) = parse_sgml_bytes(data).unwrap();
+
+        assert!(documents.is_empty());
+        assert_eq!(
+            metadata["sec-header"].as_text().unwrap(),
+            "0000320193-20-000096.hdr.sgml : 20201030"
+        );
+        let company = metadata["filer"].as_dict().unwrap()["company-data"]
+            .as_dict()
+            .unwrap();
+        assert_eq!(company["conformed-name"].as_text().unwrap(), "Apple Inc.");
+    }
+
+    #[test]
+    fn test_sec_header_inside_sec_document() {
+        let data = b"<SEC-DOCUMENT>0000950123-95-000001.txt : 19950103
+<SEC-HEADER>0000950123-95-000001.hdr.sgml : 19950103
+ACCESSION NUMBER:\t\t0000950123-95-000001
+CONFORMED SUBMISSION TYPE:\t8-K
+</SEC-HEADER>
+<DOCUMENT>
+<TYPE>8-K
+<TEXT>
+Body
+</TEXT>
+</DOCUMENT>
+</SEC-DOCUMENT>
+";
+        let (metadata, documents) = parse_sgml_bytes(data).unwrap();
+
+        assert_eq!(documents, vec![b"Body\n".to_vec()]);
+        assert_eq!(
+            metadata["conformed submission type"].as_text().unwrap(),
+            "8-K"
+        );
+    }
+
+    #[test]
+    fn test_forced_submission_type() {
+        let data = b"\xEF\xBB\xBF\n<TYPE>10-K\n<DOCUMENT>\n<TYPE>10-K\n<TEXT>\nBody\n</TEXT>\n</DOCUMENT>\n";
+        let options = ParseOptions {
+            submission_type: Some(SubmissionType::DashedDefault),
+            ..ParseOptions::default()
+        };
+
+        let (metadata, documents) = parse_sgml_bytes_with_options(data, &options).unwrap();
+
+        assert_eq!(metadata["type"].as_text().unwrap(), "10-K");
+        assert_eq!(documents, vec![b"Body\n".to_vec()]);
+    }
+
+    #[test]
+    fn test_many_documents_keep_order() {
+        let mut data = String::from("<SUBMISSION>\n<TYPE>10-K\n");
+        for i in 0..64 {
+            let body = if i % 3 == 0 {
+                format!(
+                    "{}\n",
+                    uuencode::uuencode("a.bin", format!("payload {}", i).as_bytes())
+                )
+            } else {
+                format!("Exhibit {}\n", i)
+            };
+            data.push_str(&format!(
+                "<DOCUMENT>\n<TYPE>EX-99.{}\n<SEQUENCE>{}\n<TEXT>\n{}</TEXT>\n</DOCUMENT>\n",
+                i, i, body
+            ));
+        }
+        data.push_str("</SUBMISSION>\n");
+
+        let (metadata, documents) = parse_sgml_bytes(data.as_bytes()).unwrap();
+        let (line_metadata, _) = crate::parse_sgml_into_memory(Some(data.clone()), None).unwrap();
+        assert_eq!(documents.len(), 64);
+        assert_eq!(
+            serde_json::to_value(&metadata["documents"]).unwrap(),
+            serde_json::to_value(&line_metadata["documents"]).unwrap()
+        );
+
+        for (i, (document, doc_metadata)) in documents
+            .iter()
+            .zip(metadata["documents"].as_list().unwrap())
+            .enumerate()
+        {
+            let sequence = &doc_metadata.as_dict().unwrap()["sequence"];
+            assert_eq!(sequence.as_text().unwrap(), &i.to_string());
+            let expected = if i % 3 == 0 {
+                format!("payload {}", i)
+            } else {
+                format!("Exhibit {}\n", i)
+            };
+            assert_eq!(document, expected.as_bytes());
+        }
+    }
+
+    #[test]
+    fn test_limits() {
+        use crate::types::ParseLimits;
+
+        let data = b"<SUBMISSION>\n<TYPE>10-K\n<FILER>\n<COMPANY-DATA>\n<CIK>0000320193\n</COMPANY-DATA>\n</FILER>\n<DOCUMENT>\n<TYPE>10-K\n<TEXT>\nBody text\n</TEXT>\n</DOCUMENT>\n<DOCUMENT>\n<TYPE>EX-99\n<TEXT>\nExhibit\n</TEXT>\n</DOCUMENT>\n";
+        let parse = |limits: ParseLimits| {
+            let options = ParseOptions {
+                limits,
+                ..Default::default()
+            };
+            (
+                parse_sgml_bytes_with_options(data, &options),
+                crate::parse_sgml_into_memory_with_options(
+                    Some(String::from_utf8_lossy(data).into_owned()),
+                    None,
+                    &options,
+                ),
+            )
+        };
+
+        // Exactly at each limit parses
+        let (bytes, lines) = parse(ParseLimits {
+            max_input_size: Some(data.len()),
+            max_documents: Some(2),
+            max_header_depth: Some(3),
+            max_decoded_size: Some(10),
+            max_line_length: Some(15),
+        });
+        assert!(bytes.is_ok() && lines.is_ok());
+
+        let (bytes, lines) = parse(ParseLimits {
+            max_input_size: Some(data.len() - 1),
+            ..Default::default()
+        });
+        assert!(matches!(bytes, Err(ParseError::InputTooLarge { .. })));
+        assert!(matches!(lines, Err(ParseError::InputTooLarge { .. })));
+
+        let (bytes, lines) = parse(ParseLimits {
+            max_documents: Some(1),
+            ..Default::default()
+        });
+        assert!(matches!(
+            bytes,
+            Err(ParseError::TooManyDocuments { limit: 1 })
+        ));
+        assert!(matches!(
+            lines,
+            Err(ParseError::TooManyDocuments { limit: 1 })
+        ));
+
+        let (bytes, lines) = parse(ParseLimits {
+            max_header_depth: Some(2),
+            ..Default::default()
+        });
+        assert!(matches!(
+            bytes,
+            Err(ParseError::HeaderTooDeep { depth: 3, .. })
+        ));
+        assert!(matches!(
+            lines,
+            Err(ParseError::HeaderTooDeep { depth: 3, .. })
+        ));
+
+        let (bytes, lines) = parse(ParseLimits {
+            max_decoded_size: Some(8),
+            ..Default::default()
+        });
+        assert!(matches!(
+            bytes,
+            Err(ParseError::DocumentTooLarge { size: 10, .. })
+        ));
+        assert!(matches!(lines, Err(ParseError::DocumentTooLarge { .. })));
+
+        let (bytes, lines) = parse(ParseLimits {
+            max_line_length: Some(14),
+            ..Default::default()
+        });
+        assert!(matches!(
+            bytes,
+            Err(ParseError::LineTooLong { length: 15, .. })
+        ));
+        assert!(matches!(
+            lines,
+            Err(ParseError::LineTooLong { length: 15, .. })
+        ));
+    }
+
+    #[test]
+    fn test_deep_header_fails_before_recursion() {
+        use crate::types::ParseLimits;
+
+        // Deep enough to overflow any recursive walk of the header
+        let depth = 200_000;
+        let mut tagged = "<SUBMISSION>\n<TYPE>10-K\n".to_string();
+        tagged.push_str(&"<A>\n".repeat(depth));
+        tagged.push_str(&"</A>\n".repeat(depth));
+        tagged.push_str("<DOCUMENT>\n<TYPE>10-K\n<TEXT>\nBody\n</TEXT>\n</DOCUMENT>\n");
+
+        let mut tab = "-----BEGIN PRIVACY-ENHANCED MESSAGE-----\n<SEC-DOCUMENT>x.txt\n".to_string();
+        for level in 0..100 {
+            tab.push_str(&format!("{}LEVEL-{}:\n", "\t".repeat(level), level));
+        }
+        tab.push_str("<DOCUMENT>\n<TYPE>10-K\n<TEXT>\nBody\n</TEXT>\n</DOCUMENT>\n");
+
+        let limited = ParseOptions {
+            limits: ParseLimits {
+                max_header_depth: Some(32),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        for (data, options) in [
+            (&tagged, &limited),
+            (&tab, &limited),
+            (&tagged, &ParseOptions::default()),
+        ] {
+            let limit = options.limits.max_header_depth.unwrap();
+            let too_deep = |result: Result<(), ParseError>| {
+                matches!(result, Err(ParseError::HeaderTooDeep { depth, limit: found })
+                    if depth == limit + 1 && found == limit)
+            };
+
+            assert!(too_deep(
+                parse_sgml_bytes_with_options(data.as_bytes(), options).map(drop)
+            ));
+            assert!(too_deep(
+                crate::parse_sgml_into_memory_with_options(Some(data.clone()), None, options)
+                    .map(drop)
+            ));
+            assert!(too_deep(
+                crate::ParsedSubmission::parse_with_options(data.as_bytes(), options).map(drop)
+            ));
+            assert!(too_deep(
+                crate::parse_header_only_with_options(data.as_bytes(), options.clone()).map(drop)
+            ));
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use memchr::{memchr, memmem};
+
+use crate::types::{ParseError, SubmissionType};
+use crate::utils::is_html_start;
+
+const BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// How far into the input detection looks for a submission marker
+const DETECTION_WINDOW: usize = 64 * 1024;
+
+/// First-line markers of each submission layout, in priority order
+const MARKERS: &[(&[u8], SubmissionType)] = &[
+    (b"<SUBMISSION>", SubmissionType::DashedDefault),
+    (
+        b"-----BEGIN PRIVACY-ENHANCED MESSAGE-----",
+        SubmissionType::TabPrivacy,
+    ),
+    (b"<SEC-DOCUMENT>", SubmissionType::TabDefault),
+    (b"<SEC-HEADER>", SubmissionType::SecHeader),
+    (b"<IMS-HEADER>", SubmissionType::ImsHeader),
+];
+
+/// Result of submission format detection
+#[derive(Debug, Clone, PartialEq)]
+pub struct Detection {
+    pub submission_type: SubmissionType,
+    /// Confidence between 0 and 1; 1 means the marker opened the input
+    pub confidence: f32,
+    /// Byte offset where the submission starts
+    pub offset: usize,
+}
+
+/// Detect the submission layout of an input
+///
+/// A UTF-8 BOM, blank lines and leading whitespace are skipped, and the first
+/// 64 KiB are scanned for a known opening marker so that preambles such as
+/// HTTP response headers are tolerated. Confidence drops when the marker is
+/// not on the first non-blank line, and again when the layout is only
+/// inferred from tags seen further in.
+pub fn detect_submission_format(data: &[u8]) -> Result<Detection, ParseError> {
+    let window = &data[..data.len().min(DETECTION_WINDOW)];
+    let mut pos = skip_bom(window);
+    let mut first_line: Option<&[u8]> = None;
+    let mut http_preamble = true;
+
+    while pos < window.len() {
+        let line_end = memchr(b'\n', &window[pos..]).map_or(window.len(), |end| pos + end);
+        let raw_line = &window[pos..line_end];
+        let line = raw_line.trim_ascii();
+        let line_offset = pos + (raw_line.len() - raw_line.trim_ascii_start().len());
+
+        if !line.is_empty() {
+            let confidence = match first_line {
+                None => 1.0,
+                Some(_) if http_preamble => 0.9,
+                Some(_) => 0.7,
+            };
+
+            if let Some((_, submission_type)) =
+                MARKERS.iter().find(|(marker, _)| line.starts_with(marker))
+            {
+                return Ok(Detection {
+                    submission_type: submission_type.clone(),
+                    confidence,
+                    offset: line_offset,
+                });
+            }
+
+            if is_html_start(line) && memmem::find(window, b"&lt;SEC-HEADER&gt;").is_some() {
+                return Ok(Detection {
+                    submission_type: SubmissionType::IndexHeaders,
+                    confidence: confidence * 0.9,
+                    offset: line_offset,
+                });
+            }
+
+            http_preamble &= is_http_preamble_line(line, first_line.is_none());
+            first_line.get_or_insert(line);
+        }
+
+        pos = line_end + 1;
+    }
+
+    // No opening marker: infer the layout from tags further in
+    let inferred = if memmem::find(window, b"ACCESSION NUMBER:").is_some() {
+        Some((SubmissionType::TabDefault, 0.5))
+    } else if memmem::find(window, b"<ACCESSION-NUMBER>").is_some() {
+        Some((SubmissionType::DashedDefault, 0.5))
+    } else if memmem::find(window, b"<DOCUMENT>").is_some() {
+        Some((SubmissionType::DashedDefault, 0.3))
+    } else {
+        None
+    };
+
+    match inferred {
+        Some((submission_type, confidence)) => Ok(Detection {
+            submission_type,
+            confidence,
+            offset: content_start(data),
+        }),
+        None => {
+            let line = first_line.unwrap_or_default();
+            Err(ParseError::UnknownSubmissionType(
+                String::from_utf8_lossy(&line[..line.len().min(100)]).into_owned(),
+            ))
+        }
+    }
+}
+
+/// Offset of the first non-whitespace byte, after any BOM
+pub fn content_start(data: &[u8]) -> usize {
+    let start = skip_bom(data);
+    start + (data.len() - start - data[start..].trim_ascii_start().len())
+}
+
+fn skip_bom(data: &[u8]) -> usize {
+    if data.starts_with(BOM) {
+        BOM.len()
+    } else {
+        0
+    }
+}
+
+/// Check for a status line (`HTTP/1.1 200 OK`) or a `Name: value` header
+fn is_http_preamble_line(line: &[u8], is_first: bool) -> bool {
+    if is_first && line.starts_with(b"HTTP/") {
+        return true;
+    }
+    match memchr(b':', line) {
+        Some(colon) if colon > 0 => line[..colon]
+            .iter()
+            .all(|&b| b.is_ascii_alphanumeric() || b == b'-'),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_marker_on_first_line() {
+        let detection = detect_submission_format(b"<SUBMISSION>\n<TYPE>10-K\n").unwrap();
+        assert_eq!(detection.submission_type, SubmissionType::DashedDefault);
+        assert_eq!(detection.confidence, 1.0);
+        assert_eq!(detection.offset, 0);
+    }
+
+    #[test]
+    fn test_bom_and_blank_lines() {
+        let data = b"\xEF\xBB\xBF\r\n\n   <SEC-DOCUMENT>0001.txt : 19950101\n";
+        let detection = detect_submission_format(data).unwrap();
+        assert_eq!(detection.submission_type, SubmissionType::TabDefault);
+        assert_eq!(detection.confidence, 1.0);
+        assert_eq!(detection.offset, 9);
+    }
+
+    #[test]
+    fn test_http_preamble() {
+        let data = b"HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\n\r\n<SUBMISSION>\n";
+        let detection = detect_submission_format(data).unwrap();
+        assert_eq!(detection.submission_type, SubmissionType::DashedDefault);
+        assert_eq!(detection.confidence, 0.9);
+        assert_eq!(&data[detection.offset..], b"<SUBMISSION>\n");
+    }
+
+    #[test]
+    fn test_inferred_from_tags() {
+        let data = b"exported by some tool\n<DOCUMENT>\n<TYPE>EX-99\n";
+        let detection = detect_submission_format(data).unwrap();
+        assert_eq!(detection.submission_type, SubmissionType::DashedDefault);
+        assert!(detection.confidence < 0.5);
+        assert_eq!(detection.offset, 0);
+    }
+
+    #[test]
+    fn test_unknown() {
+        assert!(matches!(
+            detect_submission_format(b"\n\nhello world\n"),
+            Err(ParseError::UnknownSubmissionType(line)) if line == "hello world"
+        ));
+    }
+}
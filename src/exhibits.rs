@@ -0,0 +1,338 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::{MetadataDict, MetadataValue};
+
+/// What an exhibit is, following the exhibit table of Regulation S-K Item 601
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ExhibitKind {
+    /// EX-1
+    UnderwritingAgreement,
+    /// EX-2
+    AcquisitionPlan,
+    /// EX-3
+    ArticlesOrBylaws,
+    /// EX-4
+    InstrumentDefiningRights,
+    /// EX-5
+    LegalityOpinion,
+    /// EX-8
+    TaxOpinion,
+    /// EX-10
+    MaterialContract,
+    /// EX-13
+    AnnualReportToHolders,
+    /// EX-14
+    CodeOfEthics,
+    /// EX-16
+    AccountantLetter,
+    /// EX-21
+    Subsidiaries,
+    /// EX-23
+    Consent,
+    /// EX-24
+    PowerOfAttorney,
+    /// EX-27, the pre-2001 financial data schedule
+    FinancialDataSchedule,
+    /// EX-31 and EX-32, Sarbanes-Oxley section 302 and 906 certifications
+    Certification,
+    /// EX-97
+    ClawbackPolicy,
+    /// EX-99 described as a press or news release
+    PressRelease,
+    /// Any other EX-99
+    AdditionalExhibit,
+    /// EX-100 and EX-101 XBRL instance, schema and linkbases
+    XbrlComponent,
+    /// EX-104
+    CoverPageXbrl,
+    /// EX-107 or EX-FILING FEES
+    FilingFees,
+    /// GRAPHIC documents
+    Graphic,
+    /// Any other exhibit number
+    OtherExhibit,
+    /// A document that is not an exhibit, such as the primary document
+    NotExhibit,
+}
+
+/// Exhibit number such as `10.1`, `99.2`, `101.INS` or `4(a)`
+///
+/// `major` is the Item 601 exhibit number, `minor` the filer's numbering
+/// below it and `suffix` anything after that, uppercased for XBRL
+/// component codes and kept as written otherwise.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct ExhibitNumber {
+    pub major: u32,
+    pub minor: Vec<u32>,
+    pub suffix: Option<String>,
+}
+
+impl ExhibitNumber {
+    /// Parse the part of a document type after `EX-`
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let digits = text.chars().take_while(char::is_ascii_digit).count();
+        let major = text[..digits].parse().ok()?;
+
+        let mut minor = Vec::new();
+        let mut rest = &text[digits..];
+        while let Some(after_dot) = rest.strip_prefix('.') {
+            let digits = after_dot.chars().take_while(char::is_ascii_digit).count();
+            let Ok(number) = after_dot[..digits].parse() else {
+                break;
+            };
+            minor.push(number);
+            rest = &after_dot[digits..];
+        }
+
+        let suffix = rest
+            .trim_start_matches(['.', '-', ' '])
+            .trim_start_matches('(')
+            .trim_end_matches(')')
+            .trim();
+        let suffix = (!suffix.is_empty()).then(|| {
+            if major == 101 || major == 100 {
+                suffix.to_ascii_uppercase()
+            } else {
+                suffix.to_string()
+            }
+        });
+
+        Some(Self {
+            major,
+            minor,
+            suffix,
+        })
+    }
+
+    /// Whether `self` is `other` or numbered below it, e.g. `10.1.2` under `10.1`
+    pub fn is_within(&self, other: &ExhibitNumber) -> bool {
+        self.major == other.major
+            && self.minor.starts_with(&other.minor)
+            && (other.suffix.is_none() || self.suffix == other.suffix)
+    }
+}
+
+impl fmt::Display for ExhibitNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.major)?;
+        for minor in &self.minor {
+            write!(f, ".{}", minor)?;
+        }
+        match &self.suffix {
+            Some(suffix) if self.major == 100 || self.major == 101 => write!(f, ".{}", suffix),
+            Some(suffix) => write!(f, "({})", suffix),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Classify a document by its `<TYPE>` and `<DESCRIPTION>`
+///
+/// Returns the kind and, for `EX-` types, the parsed exhibit number. The
+/// description is only used to tell press releases from other EX-99s.
+pub fn classify_exhibit(
+    doc_type: &str,
+    description: Option<&str>,
+) -> (ExhibitKind, Option<ExhibitNumber>) {
+    let doc_type = doc_type.trim().to_ascii_uppercase();
+    if doc_type == "GRAPHIC" {
+        return (ExhibitKind::Graphic, None);
+    }
+    let Some(rest) = doc_type.strip_prefix("EX-") else {
+        return (ExhibitKind::NotExhibit, None);
+    };
+    if rest.starts_with("FILING FEES") {
+        return (ExhibitKind::FilingFees, None);
+    }
+    let Some(number) = ExhibitNumber::parse(rest) else {
+        return (ExhibitKind::OtherExhibit, None);
+    };
+
+    let kind = match number.major {
+        1 => ExhibitKind::UnderwritingAgreement,
+        2 => ExhibitKind::AcquisitionPlan,
+        3 => ExhibitKind::ArticlesOrBylaws,
+        4 => ExhibitKind::InstrumentDefiningRights,
+        5 => ExhibitKind::LegalityOpinion,
+        8 => ExhibitKind::TaxOpinion,
+        10 => ExhibitKind::MaterialContract,
+        13 => ExhibitKind::AnnualReportToHolders,
+        14 => ExhibitKind::CodeOfEthics,
+        16 => ExhibitKind::AccountantLetter,
+        21 => ExhibitKind::Subsidiaries,
+        23 => ExhibitKind::Consent,
+        24 => ExhibitKind::PowerOfAttorney,
+        27 => ExhibitKind::FinancialDataSchedule,
+        31 | 32 => ExhibitKind::Certification,
+        97 => ExhibitKind::ClawbackPolicy,
+        99 if description.is_some_and(is_press_release) => ExhibitKind::PressRelease,
+        99 => ExhibitKind::AdditionalExhibit,
+        100 | 101 => ExhibitKind::XbrlComponent,
+        104 => ExhibitKind::CoverPageXbrl,
+        107 => ExhibitKind::FilingFees,
+        _ => ExhibitKind::OtherExhibit,
+    };
+    (kind, Some(number))
+}
+
+fn is_press_release(description: &str) -> bool {
+    let description = description.to_ascii_uppercase();
+    ["PRESS RELEASE", "NEWS RELEASE", "EARNINGS RELEASE"]
+        .iter()
+        .any(|phrase| description.contains(phrase))
+}
+
+/// Classified document of a submission
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Exhibit {
+    /// Position of the document in the parsed documents
+    pub index: usize,
+    pub doc_type: String,
+    pub kind: ExhibitKind,
+    pub number: Option<ExhibitNumber>,
+    pub description: Option<String>,
+    pub filename: Option<String>,
+}
+
+/// Every document of a submission, classified by exhibit kind
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExhibitIndex {
+    pub exhibits: Vec<Exhibit>,
+}
+
+impl ExhibitIndex {
+    /// Build the index from the `documents` list of submission metadata
+    pub fn from_metadata(metadata: &MetadataDict) -> Self {
+        let documents = metadata
+            .get("documents")
+            .and_then(MetadataValue::as_list)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+
+        let exhibits = documents
+            .iter()
+            .enumerate()
+            .filter_map(|(index, doc)| {
+                let doc = doc.as_dict()?;
+                let text = |key| doc.get(key).and_then(MetadataValue::as_text).cloned();
+                let doc_type = text("type").unwrap_or_default();
+                let description = text("description");
+                let (kind, number) = classify_exhibit(&doc_type, description.as_deref());
+                Some(Exhibit {
+                    index,
+                    doc_type,
+                    kind,
+                    number,
+                    description,
+                    filename: text("filename"),
+                })
+            })
+            .collect();
+
+        Self { exhibits }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Exhibit> {
+        self.exhibits.iter()
+    }
+
+    /// Exhibits of one kind, in document order
+    pub fn of_kind(&self, kind: ExhibitKind) -> impl Iterator<Item = &Exhibit> {
+        self.exhibits
+            .iter()
+            .filter(move |exhibit| exhibit.kind == kind)
+    }
+
+    /// Exhibits numbered at or below `number`, e.g. every `10.x` for `10`
+    pub fn within<'a>(&'a self, number: &'a ExhibitNumber) -> impl Iterator<Item = &'a Exhibit> {
+        self.exhibits.iter().filter(move |exhibit| {
+            exhibit
+                .number
+                .as_ref()
+                .is_some_and(|own| own.is_within(number))
+        })
+    }
+
+    /// Exhibit with exactly this number, e.g. `99.1`
+    pub fn get(&self, number: &str) -> Option<&Exhibit> {
+        let number = ExhibitNumber::parse(number)?;
+        self.exhibits
+            .iter()
+            .find(|exhibit| exhibit.number.as_ref() == Some(&number))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_sgml_bytes;
+
+    #[test]
+    fn test_exhibit_numbers() {
+        let number = ExhibitNumber::parse("10.1.2").unwrap();
+        assert_eq!(number.major, 10);
+        assert_eq!(number.minor, vec![1, 2]);
+        assert!(number.is_within(&ExhibitNumber::parse("10.1").unwrap()));
+        assert!(!number.is_within(&ExhibitNumber::parse("10.2").unwrap()));
+
+        let ins = ExhibitNumber::parse("101.ins").unwrap();
+        assert_eq!(ins.suffix.as_deref(), Some("INS"));
+        assert_eq!(ins.to_string(), "101.INS");
+
+        let lettered = ExhibitNumber::parse("4(a)").unwrap();
+        assert_eq!(lettered.suffix.as_deref(), Some("a"));
+        assert_eq!(lettered.to_string(), "4(a)");
+        assert_eq!(ExhibitNumber::parse("A"), None);
+    }
+
+    #[test]
+    fn test_classify_exhibit() {
+        let kind = |doc_type, description| classify_exhibit(doc_type, description).0;
+        assert_eq!(kind("EX-21.1", None), ExhibitKind::Subsidiaries);
+        assert_eq!(kind("ex-31.2", None), ExhibitKind::Certification);
+        assert_eq!(
+            kind("EX-99.1", Some("Press Release dated May 2, 2024")),
+            ExhibitKind::PressRelease
+        );
+        assert_eq!(
+            kind("EX-99.2", Some("INVESTOR PRESENTATION")),
+            ExhibitKind::AdditionalExhibit
+        );
+        assert_eq!(kind("EX-101.SCH", None), ExhibitKind::XbrlComponent);
+        assert_eq!(kind("EX-FILING FEES", None), ExhibitKind::FilingFees);
+        assert_eq!(kind("GRAPHIC", None), ExhibitKind::Graphic);
+        assert_eq!(kind("EX-55", None), ExhibitKind::OtherExhibit);
+        assert_eq!(kind("10-K", None), ExhibitKind::NotExhibit);
+    }
+
+    #[test]
+    fn test_exhibit_index() {
+        let submission = b"<SUBMISSION>\n<TYPE>8-K\n\
+<DOCUMENT>\n<TYPE>8-K\n<SEQUENCE>1\n<FILENAME>form8-k.htm\n<TEXT>\nbody\n</TEXT>\n</DOCUMENT>\n\
+<DOCUMENT>\n<TYPE>EX-10.1\n<SEQUENCE>2\n<FILENAME>ex10-1.htm\n<DESCRIPTION>CREDIT AGREEMENT\n<TEXT>\nagreement\n</TEXT>\n</DOCUMENT>\n\
+<DOCUMENT>\n<TYPE>EX-99.1\n<SEQUENCE>3\n<FILENAME>ex99-1.htm\n<DESCRIPTION>PRESS RELEASE\n<TEXT>\nrelease\n</TEXT>\n</DOCUMENT>\n\
+<DOCUMENT>\n<TYPE>GRAPHIC\n<SEQUENCE>4\n<FILENAME>logo.jpg\n<TEXT>\nbegin 644 logo.jpg\n`\nend\n</TEXT>\n</DOCUMENT>\n\
+</SUBMISSION>\n";
+        let (metadata, _) = parse_sgml_bytes(submission).unwrap();
+        let index = ExhibitIndex::from_metadata(&metadata);
+
+        assert_eq!(index.exhibits.len(), 4);
+        assert_eq!(index.exhibits[0].kind, ExhibitKind::NotExhibit);
+
+        let releases: Vec<&Exhibit> = index.of_kind(ExhibitKind::PressRelease).collect();
+        assert_eq!(releases.len(), 1);
+        assert_eq!(releases[0].index, 2);
+        assert_eq!(releases[0].filename.as_deref(), Some("ex99-1.htm"));
+
+        let contract = index.get("10.1").unwrap();
+        assert_eq!(contract.kind, ExhibitKind::MaterialContract);
+        assert_eq!(contract.description.as_deref(), Some("CREDIT AGREEMENT"));
+
+        let tens = ExhibitNumber::parse("10").unwrap();
+        assert_eq!(index.within(&tens).count(), 1);
+        assert_eq!(index.of_kind(ExhibitKind::Graphic).count(), 1);
+    }
+}
@@ -0,0 +1,61 @@
+//! Entry points shared by the fuzz targets in `fuzz/` and the regression
+//! corpus in `tests/`
+//!
+//! Not part of the supported API. Each function runs one parser over
+//! arbitrary bytes and discards the result; only panics matter.
+
+use crate::types::{MetadataDict, ParseLimits, SubmissionType};
+use crate::{byte_parser, sgml, uu_decoder, xml};
+
+const TAB_TYPES: [SubmissionType; 2] = [SubmissionType::TabDefault, SubmissionType::TabPrivacy];
+
+pub fn parse_sgml_into_memory(data: &[u8]) {
+    let content = String::from_utf8_lossy(data).into_owned();
+    let _ = sgml::parse_sgml_into_memory(Some(content), None);
+}
+
+pub fn parse_sgml_bytes(data: &[u8]) {
+    let _ = byte_parser::parse_sgml_bytes(data);
+}
+
+/// Tab header parsing in both engines, with and without a privacy message
+pub fn parse_tab_header(data: &[u8]) {
+    let lines = lines(data);
+    let limits = ParseLimits::default();
+    for submission_type in &TAB_TYPES {
+        let _ = byte_parser::parse_tab_header::<MetadataDict>(
+            data,
+            data.len(),
+            submission_type,
+            &limits,
+        );
+        let _ = sgml::parse_tab_header(&lines, submission_type, &limits);
+    }
+}
+
+/// Tagged header parsing in both engines
+pub fn parse_dashed_default_header(data: &[u8]) {
+    let limits = ParseLimits::default();
+    let _ = byte_parser::parse_dashed_default_header::<MetadataDict>(data, data.len(), &limits);
+    let _ = sgml::parse_dashed_default_header(&lines(data), &limits);
+}
+
+/// XML parsing and the recursive walks over the resulting tree
+pub fn parse_xml(data: &[u8]) {
+    if let Ok(root) = xml::parse_xml(data) {
+        let _ = root.text();
+        let _ = root.find("context");
+        let _ = root.descendants();
+    }
+}
+
+pub fn uu_decode(data: &[u8]) {
+    uu_decoder::decode(data);
+}
+
+fn lines(data: &[u8]) -> Vec<String> {
+    String::from_utf8_lossy(data)
+        .lines()
+        .map(String::from)
+        .collect()
+}
@@ -0,0 +1,283 @@
+use memchr::{memchr, memmem};
+
+use crate::utils::decode_html_text;
+
+/// Token of an HTML document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Token<'a> {
+    StartTag(Tag<'a>),
+    /// End tag name as written
+    EndTag(&'a [u8]),
+    /// Raw text, entities not yet decoded
+    Text(&'a [u8]),
+    /// Comment, doctype or processing instruction
+    Comment(&'a [u8]),
+}
+
+/// Start tag with its raw attribute text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tag<'a> {
+    pub name: &'a [u8],
+    pub attributes: &'a [u8],
+    pub self_closing: bool,
+}
+
+impl Tag<'_> {
+    /// Compare the tag name, ignoring ASCII case
+    pub fn is(&self, name: &str) -> bool {
+        self.name.eq_ignore_ascii_case(name.as_bytes())
+    }
+
+    /// Lowercase tag name
+    pub fn lowercase_name(&self) -> String {
+        String::from_utf8_lossy(self.name).to_ascii_lowercase()
+    }
+
+    /// Attribute value with entities decoded, by case-insensitive name
+    pub fn attr(&self, name: &str) -> Option<String> {
+        attributes(self.attributes)
+            .find(|(key, _)| key.eq_ignore_ascii_case(name.as_bytes()))
+            .map(|(_, value)| decode_html_text(value).into_owned())
+    }
+}
+
+/// Lenient single-pass HTML tokenizer
+///
+/// Never fails: a `<` that does not start a tag is text, an unterminated
+/// tag or comment runs to the end of input, and the content of `<script>`
+/// and `<style>` is returned as one text token without looking for tags.
+pub struct Tokenizer<'a> {
+    data: &'a [u8],
+    pos: usize,
+    raw_text_end: Option<&'static [u8]>,
+}
+
+impl<'a> Tokenizer<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            pos: 0,
+            raw_text_end: None,
+        }
+    }
+
+    /// Byte offset of the next token
+    pub fn offset(&self) -> usize {
+        self.pos
+    }
+
+    fn raw_text(&mut self, end_tag: &[u8]) -> &'a [u8] {
+        let rest = &self.data[self.pos..];
+        let end = (0..rest.len())
+            .find(|&i| {
+                rest[i] == b'<'
+                    && rest[i..]
+                        .get(..end_tag.len())
+                        .is_some_and(|candidate| candidate.eq_ignore_ascii_case(end_tag))
+            })
+            .unwrap_or(rest.len());
+        self.pos += end;
+        &rest[..end]
+    }
+
+    fn markup(&mut self) -> Option<Token<'a>> {
+        let rest = &self.data[self.pos..];
+        let next = *rest.get(1)?;
+
+        if rest.starts_with(b"<!--") {
+            let (body_end, end) = memmem::find(&rest[4..], b"-->")
+                .map_or((rest.len(), rest.len()), |i| (i + 4, i + 7));
+            self.pos += end;
+            return Some(Token::Comment(&rest[4..body_end]));
+        }
+        if next == b'!' || next == b'?' {
+            let (body_end, end) =
+                memchr(b'>', rest).map_or((rest.len(), rest.len()), |i| (i, i + 1));
+            self.pos += end;
+            return Some(Token::Comment(&rest[2..body_end]));
+        }
+
+        let is_end = next == b'/';
+        let name_start = if is_end { 2 } else { 1 };
+        if !rest.get(name_start)?.is_ascii_alphabetic() {
+            return None;
+        }
+
+        let (body_end, end) = tag_end(rest).map_or((rest.len(), rest.len()), |i| (i, i + 1));
+        self.pos += end;
+        let inner = &rest[name_start..body_end];
+        let name_len = inner
+            .iter()
+            .position(|&b| b.is_ascii_whitespace() || b == b'/' || b == b'>')
+            .unwrap_or(inner.len());
+        let name = &inner[..name_len];
+
+        if is_end {
+            return Some(Token::EndTag(name));
+        }
+
+        let attributes = inner[name_len..].trim_ascii();
+        let self_closing = attributes.ends_with(b"/");
+        let attributes = attributes.strip_suffix(b"/").unwrap_or(attributes);
+
+        if name.eq_ignore_ascii_case(b"script") {
+            self.raw_text_end = Some(b"</script");
+        } else if name.eq_ignore_ascii_case(b"style") {
+            self.raw_text_end = Some(b"</style");
+        }
+        if self_closing {
+            self.raw_text_end = None;
+        }
+
+        Some(Token::StartTag(Tag {
+            name,
+            attributes,
+            self_closing,
+        }))
+    }
+}
+
+impl<'a> Iterator for Tokenizer<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        if self.pos >= self.data.len() {
+            return None;
+        }
+
+        if let Some(end_tag) = self.raw_text_end.take() {
+            let text = self.raw_text(end_tag);
+            if !text.is_empty() {
+                return Some(Token::Text(text));
+            }
+        }
+
+        if self.data[self.pos] == b'<' {
+            if let Some(token) = self.markup() {
+                return Some(token);
+            }
+        }
+
+        // Text runs to the next `<`, including a leading `<` that is not markup
+        let start = self.pos;
+        let end = memchr(b'<', &self.data[start + 1..]).map_or(self.data.len(), |i| start + 1 + i);
+        self.pos = end;
+        Some(Token::Text(&self.data[start..end]))
+    }
+}
+
+/// Position of the `>` closing a tag, skipping quoted values
+fn tag_end(rest: &[u8]) -> Option<usize> {
+    let mut quote = None;
+    for (i, &b) in rest.iter().enumerate().skip(1) {
+        match (quote, b) {
+            (None, b'"' | b'\'') => quote = Some(b),
+            (Some(q), _) if b == q => quote = None,
+            (None, b'>') => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Iterate over `name=value` pairs of raw attribute text
+fn attributes(mut text: &[u8]) -> impl Iterator<Item = (&[u8], &[u8])> {
+    std::iter::from_fn(move || {
+        text = text.trim_ascii_start();
+        if text.is_empty() {
+            return None;
+        }
+
+        let name_len = text
+            .iter()
+            .position(|&b| b.is_ascii_whitespace() || b == b'=')
+            .unwrap_or(text.len());
+        let name = &text[..name_len];
+        text = text[name_len..].trim_ascii_start();
+
+        let Some(after_eq) = text.strip_prefix(b"=") else {
+            return Some((name, &b""[..]));
+        };
+        let after_eq = after_eq.trim_ascii_start();
+
+        let value = match after_eq.first() {
+            Some(&quote @ (b'"' | b'\'')) => {
+                let end = memchr(quote, &after_eq[1..]).map_or(after_eq.len(), |i| i + 1);
+                text = after_eq.get(end + 1..).unwrap_or_default();
+                &after_eq[1..end]
+            }
+            _ => {
+                let end = after_eq
+                    .iter()
+                    .position(u8::is_ascii_whitespace)
+                    .unwrap_or(after_eq.len());
+                text = &after_eq[end..];
+                &after_eq[..end]
+            }
+        };
+        Some((name, value))
+    })
+}
+
+/// Check whether document content looks like HTML rather than plain text
+///
+/// `<TABLE>` alone is not enough, as plain text filings use it too.
+pub fn is_html(content: &[u8]) -> bool {
+    let head = &content[..content.len().min(4096)];
+    [
+        &b"<html"[..],
+        b"<!doctype html",
+        b"<body",
+        b"<div",
+        b"<p>",
+        b"<font",
+    ]
+    .iter()
+    .any(|marker| {
+        head.windows(marker.len())
+            .any(|window| window.eq_ignore_ascii_case(marker))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokens_and_attributes() {
+        let html = b"<!DOCTYPE html><TD colspan=2 ALIGN=\"right\" title='a &amp; b'>x < y</td><br/><!-- note -->";
+        let tokens: Vec<Token> = Tokenizer::new(html).collect();
+
+        assert_eq!(tokens[0], Token::Comment(b"DOCTYPE html"));
+        let Token::StartTag(td) = &tokens[1] else {
+            panic!("expected start tag, got {:?}", tokens[1]);
+        };
+        assert!(td.is("td"));
+        assert_eq!(td.attr("colspan").as_deref(), Some("2"));
+        assert_eq!(td.attr("align").as_deref(), Some("right"));
+        assert_eq!(td.attr("title").as_deref(), Some("a & b"));
+        assert_eq!(tokens[2], Token::Text(b"x "));
+        assert_eq!(tokens[3], Token::Text(b"< y"));
+        assert_eq!(tokens[4], Token::EndTag(b"td"));
+        assert!(matches!(&tokens[5], Token::StartTag(tag) if tag.self_closing));
+        assert_eq!(tokens[6], Token::Comment(b" note "));
+    }
+
+    #[test]
+    fn test_script_is_raw_text() {
+        let html = b"<script>if (a<b) { x = '</p>'; }</SCRIPT><p>after";
+        let tokens: Vec<Token> = Tokenizer::new(html).collect();
+
+        assert_eq!(tokens[1], Token::Text(b"if (a<b) { x = '</p>'; }"));
+        assert_eq!(tokens[2], Token::EndTag(b"SCRIPT"));
+        assert_eq!(tokens[4], Token::Text(b"after"));
+    }
+
+    #[test]
+    fn test_unterminated_markup() {
+        let tokens: Vec<Token> = Tokenizer::new(b"text <div class=\"a").collect();
+        assert_eq!(tokens.len(), 2);
+        assert!(is_html(b"\n<HTML><BODY>"));
+        assert!(!is_html(b"PLAIN TEXT FILING\n<PAGE>\n<TABLE>"));
+    }
+}
@@ -0,0 +1,376 @@
+use memchr::memmem;
+use serde::{Deserialize, Serialize};
+
+use crate::types::{MetadataDict, MetadataValue, ParseError};
+use crate::xml::{parse_xml, Element};
+
+/// Investment discretion keywords of the legacy text layout
+const DISCRETION: &[&str] = &[
+    "SOLE",
+    "SHARED",
+    "DEFINED",
+    "DFND",
+    "OTR",
+    "OTHER",
+    "SHARED-DEFINED",
+    "SHARED-OTHER",
+    "SH-DEF",
+    "SH-OTHER",
+];
+
+/// Row of a 13F-HR information table
+///
+/// `value` is as reported: thousands of dollars before 2023, whole dollars
+/// after.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Holding {
+    pub name_of_issuer: String,
+    pub title_of_class: String,
+    pub cusip: String,
+    pub figi: Option<String>,
+    pub value: Option<u64>,
+    pub shares_or_principal: Option<u64>,
+    /// `SH` (shares) or `PRN` (principal amount)
+    pub shares_or_principal_type: Option<String>,
+    /// `PUT` or `CALL`
+    pub put_call: Option<String>,
+    pub investment_discretion: Option<String>,
+    pub other_managers: Option<String>,
+    pub voting_sole: Option<u64>,
+    pub voting_shared: Option<u64>,
+    pub voting_none: Option<u64>,
+}
+
+/// Find and parse the information table of a parsed 13F-HR submission
+///
+/// Looks for the `INFORMATION TABLE` document first; old filings without
+/// one carry the table in the `13F-HR` text itself. Returns `None` when
+/// neither is present.
+pub fn find_information_table(
+    metadata: &MetadataDict,
+    documents: &[Vec<u8>],
+) -> Result<Option<Vec<Holding>>, ParseError> {
+    let types: Vec<Option<String>> = metadata
+        .get("documents")
+        .and_then(MetadataValue::as_list)
+        .into_iter()
+        .flatten()
+        .map(|doc| {
+            doc.as_dict()
+                .and_then(|doc| doc.get("type"))
+                .and_then(MetadataValue::as_text)
+                .map(|doc_type| doc_type.trim().to_uppercase())
+        })
+        .collect();
+
+    let find = |wanted: &dyn Fn(&str) -> bool| {
+        types
+            .iter()
+            .zip(documents)
+            .find(|(doc_type, _)| doc_type.as_deref().is_some_and(wanted))
+            .map(|(_, content)| content)
+    };
+
+    let content =
+        find(&|t| t == "INFORMATION TABLE").or_else(|| find(&|t| t.starts_with("13F-HR")));
+
+    content
+        .map(|content| parse_information_table(content))
+        .transpose()
+}
+
+/// Parse an information table in either the XML or the legacy text layout
+pub fn parse_information_table(content: &[u8]) -> Result<Vec<Holding>, ParseError> {
+    if memmem::find(content, b"infoTable").is_some() {
+        parse_xml_table(content)
+    } else {
+        Ok(parse_text_table(&String::from_utf8_lossy(content)))
+    }
+}
+
+fn parse_xml_table(content: &[u8]) -> Result<Vec<Holding>, ParseError> {
+    let document = parse_xml(content)?;
+    let root = document
+        .find("informationTable")
+        .ok_or_else(|| ParseError::InvalidContent("No informationTable element".to_string()))?;
+
+    Ok(root.children_named("infoTable").map(xml_holding).collect())
+}
+
+fn xml_holding(row: &Element) -> Holding {
+    let text = |parent: Option<&Element>, name| {
+        parent
+            .and_then(|parent| parent.child_text(name))
+            .filter(|text| !text.is_empty())
+    };
+    let number = |parent: Option<&Element>, name| text(parent, name).and_then(|t| parse_number(&t));
+    let amount = row.child("shrsOrPrnAmt");
+    let voting = row.child("votingAuthority");
+
+    Holding {
+        name_of_issuer: text(Some(row), "nameOfIssuer").unwrap_or_default(),
+        title_of_class: text(Some(row), "titleOfClass").unwrap_or_default(),
+        cusip: text(Some(row), "cusip").unwrap_or_default(),
+        figi: text(Some(row), "figi"),
+        value: number(Some(row), "value"),
+        shares_or_principal: number(amount, "sshPrnamt"),
+        shares_or_principal_type: text(amount, "sshPrnamtType"),
+        put_call: text(Some(row), "putCall").map(|t| t.to_uppercase()),
+        investment_discretion: text(Some(row), "investmentDiscretion"),
+        other_managers: text(Some(row), "otherManager"),
+        voting_sole: number(voting, "Sole"),
+        voting_shared: number(voting, "Shared"),
+        voting_none: number(voting, "None"),
+    }
+}
+
+/// Parse the fixed-width text layout used before XML information tables
+///
+/// Column positions vary between filers, so each row is anchored on its
+/// CUSIP: the text before it is the issuer name and class (split at the
+/// last run of two or more spaces), and the tokens after it are value,
+/// amount, the optional `SH`/`PRN` and `PUT`/`CALL` flags, discretion,
+/// other managers and the three voting columns. Lines without a CUSIP,
+/// such as headings and totals, are skipped.
+fn parse_text_table(content: &str) -> Vec<Holding> {
+    content.lines().filter_map(text_holding).collect()
+}
+
+fn text_holding(line: &str) -> Option<Holding> {
+    let tokens: Vec<(usize, &str)> = line
+        .split_whitespace()
+        .map(|token| (token.as_ptr() as usize - line.as_ptr() as usize, token))
+        .collect();
+
+    // The issuer name must come first, so the CUSIP is never the first token
+    let (cusip_index, cusip, consumed) = (1..tokens.len()).find_map(|i| {
+        if is_cusip(tokens[i].1) {
+            return Some((i, tokens[i].1.to_string(), 1));
+        }
+        // Some filers space the CUSIP as issuer, issue and check digit
+        let split = tokens.get(i..i + 3)?;
+        let joined: String = split.iter().map(|(_, t)| *t).collect();
+        (split[0].1.len() == 6
+            && split[1].1.len() == 2
+            && split[2].1.len() == 1
+            && is_cusip(&joined))
+        .then_some((i, joined, 3))
+    })?;
+
+    let (name_of_issuer, title_of_class) =
+        split_name_and_class(line[..tokens[cusip_index].0].trim_end());
+
+    let mut rest = tokens[cusip_index + consumed..]
+        .iter()
+        .map(|(_, t)| *t)
+        .peekable();
+    let mut holding = Holding {
+        name_of_issuer,
+        title_of_class,
+        cusip,
+        value: rest.next().and_then(parse_number),
+        shares_or_principal: rest.next().and_then(parse_number),
+        ..Holding::default()
+    };
+
+    if let Some(kind) = rest.next_if(|t| matches!(t.to_uppercase().as_str(), "SH" | "PRN")) {
+        holding.shares_or_principal_type = Some(kind.to_uppercase());
+    }
+    if let Some(option) = rest.next_if(|t| matches!(t.to_uppercase().as_str(), "PUT" | "CALL")) {
+        holding.put_call = Some(option.to_uppercase());
+    }
+    if let Some(discretion) = rest.next_if(|t| DISCRETION.contains(&t.to_uppercase().as_str())) {
+        holding.investment_discretion = Some(discretion.to_uppercase());
+    }
+
+    // Manager lists such as `1,2` would parse as numbers, so the voting
+    // columns are the well-formed amounts after them, at most the last three
+    let remaining: Vec<&str> = rest.collect();
+    let voting_start = remaining
+        .iter()
+        .rposition(|t| !is_amount(t))
+        .map_or(0, |i| i + 1)
+        .max(remaining.len().saturating_sub(3));
+    let (managers, voting) = remaining.split_at(voting_start);
+
+    if !managers.is_empty() {
+        holding.other_managers = Some(managers.join(" "));
+    }
+    let mut voting = voting.iter().map(|t| parse_number(t));
+    holding.voting_sole = voting.next().flatten();
+    holding.voting_shared = voting.next().flatten();
+    holding.voting_none = voting.next().flatten();
+
+    Some(holding)
+}
+
+fn split_name_and_class(text: &str) -> (String, String) {
+    match text.rfind("  ") {
+        Some(pos) => (
+            text[..pos].trim().to_string(),
+            text[pos..].trim().to_string(),
+        ),
+        None => match text.rsplit_once(' ') {
+            Some((name, class)) => (name.trim().to_string(), class.to_string()),
+            None => (text.to_string(), String::new()),
+        },
+    }
+}
+
+/// Nine alphanumeric characters ending in a check digit, mostly digits
+fn is_cusip(token: &str) -> bool {
+    token.len() == 9
+        && token
+            .bytes()
+            .all(|b| b.is_ascii_digit() || b.is_ascii_uppercase())
+        && token.as_bytes()[8].is_ascii_digit()
+        && token.bytes().filter(u8::is_ascii_digit).count() >= 5
+}
+
+/// Whether a token is a plain or comma-grouped amount like `20,000`
+fn is_amount(text: &str) -> bool {
+    let mut groups = text.split(',');
+    let first = groups.next().unwrap_or_default();
+    !first.is_empty()
+        && first.bytes().all(|b| b.is_ascii_digit())
+        && groups.all(|group| group.len() == 3 && group.bytes().all(|b| b.is_ascii_digit()))
+}
+
+fn parse_number(text: &str) -> Option<u64> {
+    let digits: String = text.chars().filter(|c| *c != ',' && *c != '$').collect();
+    digits.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_sgml_bytes;
+
+    const XML_SUBMISSION: &str = r#"<SUBMISSION>
+<TYPE>13F-HR
+<DOCUMENT>
+<TYPE>13F-HR
+<TEXT>
+<XML>
+<edgarSubmission><headerData/></edgarSubmission>
+</XML>
+</TEXT>
+</DOCUMENT>
+<DOCUMENT>
+<TYPE>INFORMATION TABLE
+<TEXT>
+<XML>
+<?xml version="1.0" encoding="UTF-8"?>
+<ns1:informationTable xmlns:ns1="http://www.sec.gov/edgar/document/thirteenf/informationtable">
+  <ns1:infoTable>
+    <ns1:nameOfIssuer>APPLE INC</ns1:nameOfIssuer>
+    <ns1:titleOfClass>COM</ns1:titleOfClass>
+    <ns1:cusip>037833100</ns1:cusip>
+    <ns1:value>174300000</ns1:value>
+    <ns1:shrsOrPrnAmt><ns1:sshPrnamt>915560382</ns1:sshPrnamt><ns1:sshPrnamtType>SH</ns1:sshPrnamtType></ns1:shrsOrPrnAmt>
+    <ns1:investmentDiscretion>DFND</ns1:investmentDiscretion>
+    <ns1:otherManager>4,8,11</ns1:otherManager>
+    <ns1:votingAuthority><ns1:Sole>915560382</ns1:Sole><ns1:Shared>0</ns1:Shared><ns1:None>0</ns1:None></ns1:votingAuthority>
+  </ns1:infoTable>
+  <ns1:infoTable>
+    <ns1:nameOfIssuer>SPDR S&amp;P 500 ETF TR</ns1:nameOfIssuer>
+    <ns1:titleOfClass>TR UNIT</ns1:titleOfClass>
+    <ns1:cusip>78462F103</ns1:cusip>
+    <ns1:value>2500</ns1:value>
+    <ns1:shrsOrPrnAmt><ns1:sshPrnamt>5000</ns1:sshPrnamt><ns1:sshPrnamtType>SH</ns1:sshPrnamtType></ns1:shrsOrPrnAmt>
+    <ns1:putCall>Put</ns1:putCall>
+    <ns1:investmentDiscretion>SOLE</ns1:investmentDiscretion>
+    <ns1:votingAuthority><ns1:Sole>0</ns1:Sole><ns1:Shared>0</ns1:Shared><ns1:None>5000</ns1:None></ns1:votingAuthority>
+  </ns1:infoTable>
+</ns1:informationTable>
+</XML>
+</TEXT>
+</DOCUMENT>
+</SUBMISSION>
+"#;
+
+    const TEXT_TABLE: &str = "
+                                                          VALUE   SHARES/ SH/ PUT/ INVSTMT  OTHER            VOTING AUTHORITY
+NAME OF ISSUER                 TITLE OF CLASS  CUSIP     (x$1000) PRN AMT PRN CALL DSCRETN MANAGERS      SOLE   SHARED   NONE
+------------------------------ --------------- --------- -------- ------- --- ---- ------- --------- -------- -------- --------
+ABBOTT LABS                    COM             002824100     1,234   25000 SH       SOLE                  25000        0       0
+GENERAL ELEC CO                COM             369604 10 3     880   30000 SH  CALL DEFINED  1,2           20000    10000       0
+3M CO                          COM             88579Y101       512    6000 SH       SOLE
+TOTAL                                                        2,626
+";
+
+    #[test]
+    fn test_xml_information_table() {
+        let (metadata, documents) = parse_sgml_bytes(XML_SUBMISSION.as_bytes()).unwrap();
+        let holdings = find_information_table(&metadata, &documents)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(holdings.len(), 2);
+        let apple = &holdings[0];
+        assert_eq!(apple.cusip, "037833100");
+        assert_eq!(apple.value, Some(174_300_000));
+        assert_eq!(apple.shares_or_principal, Some(915_560_382));
+        assert_eq!(apple.shares_or_principal_type.as_deref(), Some("SH"));
+        assert_eq!(apple.other_managers.as_deref(), Some("4,8,11"));
+        assert_eq!(apple.voting_sole, Some(915_560_382));
+        assert_eq!(apple.put_call, None);
+
+        assert_eq!(holdings[1].name_of_issuer, "SPDR S&P 500 ETF TR");
+        assert_eq!(holdings[1].put_call.as_deref(), Some("PUT"));
+        assert_eq!(holdings[1].voting_none, Some(5000));
+    }
+
+    #[test]
+    fn test_text_information_table() {
+        let holdings = parse_information_table(TEXT_TABLE.as_bytes()).unwrap();
+        assert_eq!(holdings.len(), 3);
+
+        let abbott = &holdings[0];
+        assert_eq!(abbott.name_of_issuer, "ABBOTT LABS");
+        assert_eq!(abbott.title_of_class, "COM");
+        assert_eq!(abbott.value, Some(1234));
+        assert_eq!(abbott.shares_or_principal, Some(25000));
+        assert_eq!(abbott.investment_discretion.as_deref(), Some("SOLE"));
+        assert_eq!(abbott.other_managers, None);
+        assert_eq!(
+            (abbott.voting_sole, abbott.voting_shared, abbott.voting_none),
+            (Some(25000), Some(0), Some(0))
+        );
+
+        let ge = &holdings[1];
+        assert_eq!(ge.cusip, "369604103");
+        assert_eq!(ge.put_call.as_deref(), Some("CALL"));
+        assert_eq!(ge.other_managers.as_deref(), Some("1,2"));
+        assert_eq!(ge.voting_shared, Some(10000));
+
+        assert_eq!(holdings[2].name_of_issuer, "3M CO");
+        assert_eq!(holdings[2].voting_sole, None);
+    }
+
+    #[test]
+    fn test_managers_column_before_blank_voting_column() {
+        let line = "GENERAL ELEC CO    COM    369604103    880    30000 SH    DEFINED  1,2    20,000    10000";
+        let holding = text_holding(line).unwrap();
+        assert_eq!(holding.other_managers.as_deref(), Some("1,2"));
+        assert_eq!(
+            (
+                holding.voting_sole,
+                holding.voting_shared,
+                holding.voting_none
+            ),
+            (Some(20000), Some(10000), None)
+        );
+
+        let line = "GENERAL ELEC CO    COM    369604103    880    30000 SH    DEFINED  1,2    0";
+        let holding = text_holding(line).unwrap();
+        assert_eq!(holding.other_managers.as_deref(), Some("1,2"));
+        assert_eq!(holding.voting_sole, Some(0));
+    }
+
+    #[test]
+    fn test_no_information_table() {
+        let (metadata, documents) = parse_sgml_bytes(b"<SUBMISSION>\n<TYPE>10-K\n").unwrap();
+        assert_eq!(find_information_table(&metadata, &documents).unwrap(), None);
+    }
+}
@@ -2,6 +2,8 @@ use std::fs::{self, File};
 use std::io::Write;
 use std::path::{Path, PathBuf};
 
+use crate::html::is_html;
+use crate::text::{html_to_text, TextOptions};
 use crate::types::{DocumentInfo, MetadataDict, MetadataValue, ParseError};
 use crate::utils::{default_filename, detect_uu, safe_filename};
 
@@ -72,28 +74,47 @@ pub fn prepare_documents(documents: Vec<Vec<u8>>, metadata: &MetadataDict) -> Ve
         .enumerate()
     {
         let doc_metadata = match metadata_value {
-            MetadataValue::Dict(dict) => dict.clone(),
+            MetadataValue::Dict(dict) => dict,
             _ => continue, // Skip if not a dictionary
         };
 
         // Detect if content is binary (UU encoded)
         let is_binary = !content.is_empty()
             && std::str::from_utf8(&content[..std::cmp::min(10, content.len())])
-                .map(|s| detect_uu(s))
+                .map(detect_uu)
                 .unwrap_or(false);
 
-        let filename = extract_filename(&doc_metadata, i, is_binary);
+        let filename = extract_filename(doc_metadata, i, is_binary);
 
         result.push(DocumentInfo {
             filename: PathBuf::from(filename),
             content,
-            metadata: doc_metadata,
         });
     }
 
     result
 }
 
+/// Replace HTML documents with their plain text rendering
+///
+/// Rendered documents get a `.txt` extension; other documents are left as
+/// they are.
+pub fn render_documents_as_text(
+    documents: Vec<DocumentInfo>,
+    options: &TextOptions,
+) -> Vec<DocumentInfo> {
+    documents
+        .into_iter()
+        .map(|mut doc| {
+            if is_html(&doc.content) {
+                doc.content = html_to_text(&doc.content, options).into_bytes();
+                doc.filename.set_extension("txt");
+            }
+            doc
+        })
+        .collect()
+}
+
 /// Write documents to output directory
 pub fn write_documents(documents: Vec<DocumentInfo>, output_dir: &Path) -> Result<(), ParseError> {
     for doc in documents {
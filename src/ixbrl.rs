@@ -0,0 +1,608 @@
+use std::ops::RangeInclusive;
+
+use memchr::memmem;
+
+use crate::types::{FastMap, ParseError};
+use crate::xbrl::{parse_context, parse_unit, RawFact, XbrlFiling};
+use crate::xml::{local_name, parse_xml, Element, Node};
+
+/// Longest `continuedAt` chain followed before giving up on a cycle
+const MAX_CONTINUATIONS: usize = 10_000;
+
+/// `scale` values applied to numeric facts; others leave the fact as shown
+/// instead of padding it with an unbounded run of zeros
+const SCALES: RangeInclusive<i32> = -30..=30;
+
+/// Check whether a document is an Inline XBRL (iXBRL) document
+pub fn is_inline_xbrl(content: &[u8]) -> bool {
+    memmem::find(content, b"http://www.xbrl.org/2013/inlineXBRL").is_some()
+        || memmem::find(content, b"<ix:header").is_some()
+}
+
+/// Parse the facts, contexts and units of an Inline XBRL document
+pub fn parse_inline_xbrl(content: &[u8]) -> Result<XbrlFiling, ParseError> {
+    let mut filing = XbrlFiling::default();
+    filing.add_inline(content)?;
+    Ok(filing)
+}
+
+impl XbrlFiling {
+    /// Add the facts, contexts and units of an Inline XBRL document
+    ///
+    /// Facts come from `ix:nonFraction` and `ix:nonNumeric` anywhere in the
+    /// document, including `ix:hidden`. Numeric values have their format
+    /// transform, `scale` and `sign` applied, so they read like the values
+    /// of a standard instance. Text values follow `continuedAt` chains,
+    /// leave out `ix:exclude` content and have whitespace collapsed, except
+    /// `escape="true"` values, which keep their markup. A fact whose format
+    /// is unknown or does not parse, or whose `scale` is outside -30 to 30,
+    /// keeps its displayed text and is marked
+    /// [`untransformed`](RawFact::untransformed).
+    pub fn add_inline(&mut self, content: &[u8]) -> Result<(), ParseError> {
+        let document = parse_xml(content)?;
+        let elements = document.descendants();
+
+        let continuations: FastMap<&str, &Element> = elements
+            .iter()
+            .filter(|element| element.local_name() == "continuation")
+            .filter_map(|element| Some((element.attr("id")?, *element)))
+            .collect();
+
+        for element in &elements {
+            match element.local_name() {
+                "resources" => {
+                    for resource in element.elements() {
+                        match resource.local_name() {
+                            "context" => {
+                                if let Some(context) = parse_context(resource) {
+                                    self.contexts.insert(context.id.clone(), context);
+                                }
+                            }
+                            "unit" => {
+                                if let Some(unit) = parse_unit(resource) {
+                                    self.units.insert(unit.id.clone(), unit);
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                "nonFraction" => {
+                    if let Some(fact) = non_fraction(element) {
+                        self.raw_facts.push(fact);
+                    }
+                }
+                "nonNumeric" => {
+                    if let Some(fact) = non_numeric(element, &continuations) {
+                        self.raw_facts.push(fact);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn non_fraction(element: &Element) -> Option<RawFact> {
+    let mut untransformed = false;
+    let value = if element.attr("nil") == Some("true") {
+        String::new()
+    } else {
+        let text = fact_text(element);
+        let number = match element.attr("format") {
+            Some(format) => transform(format, &text),
+            None => normalize_number(&text, '.'),
+        };
+        let scale = match element.attr("scale") {
+            Some(scale) => scale
+                .trim()
+                .parse()
+                .ok()
+                .filter(|scale| SCALES.contains(scale)),
+            None => Some(0),
+        };
+        match number.zip(scale) {
+            Some((number, scale)) => {
+                let scaled = apply_scale(&number, scale);
+                if element.attr("sign") == Some("-") && !is_zero(&scaled) {
+                    format!("-{}", scaled)
+                } else {
+                    scaled
+                }
+            }
+            // Keep what the document shows rather than losing the fact
+            None => {
+                untransformed = true;
+                collapse_whitespace(&text)
+            }
+        }
+    };
+
+    Some(RawFact {
+        concept: element.attr("name")?.to_string(),
+        context_ref: element.attr("contextRef")?.to_string(),
+        unit_ref: element.attr("unitRef").map(str::to_string),
+        decimals: element.attr("decimals").map(str::to_string),
+        value,
+        untransformed,
+    })
+}
+
+fn non_numeric(element: &Element, continuations: &FastMap<&str, &Element>) -> Option<RawFact> {
+    // Escaped facts hold XHTML, which is kept as markup
+    let escaped = element.attr("escape") == Some("true");
+    let content = if escaped { fact_markup } else { fact_text };
+    let mut text = content(element);
+
+    let mut next = element.attr("continuedAt");
+    let mut followed = 0;
+    while let Some(continuation) = next.and_then(|id| continuations.get(id)) {
+        followed += 1;
+        if followed > MAX_CONTINUATIONS {
+            break;
+        }
+        text.push(' ');
+        text.push_str(&content(continuation));
+        next = continuation.attr("continuedAt");
+    }
+
+    let text = if escaped {
+        text.trim().to_string()
+    } else {
+        collapse_whitespace(&text)
+    };
+    let (value, untransformed) = match element.attr("format") {
+        Some(format) => match transform(format, &text) {
+            Some(value) => (value, false),
+            None => (text, true),
+        },
+        None => (text, false),
+    };
+
+    Some(RawFact {
+        concept: element.attr("name")?.to_string(),
+        context_ref: element.attr("contextRef")?.to_string(),
+        unit_ref: None,
+        decimals: None,
+        value,
+        untransformed,
+    })
+}
+
+/// Text of a fact element without its `ix:exclude` content
+fn fact_text(element: &Element) -> String {
+    fn collect(element: &Element, text: &mut String) {
+        for node in &element.children {
+            match node {
+                Node::Text(value) => text.push_str(value),
+                Node::Element(child) if child.local_name() == "exclude" => {}
+                Node::Element(child) => {
+                    // Keep words in adjacent block elements apart
+                    if matches!(child.local_name(), "p" | "div" | "br" | "td" | "li") {
+                        text.push(' ');
+                    }
+                    collect(child, text);
+                }
+            }
+        }
+    }
+
+    let mut text = String::new();
+    collect(element, &mut text);
+    text
+}
+
+/// Markup of a fact element without its `ix:exclude` content
+fn fact_markup(element: &Element) -> String {
+    fn escape(value: &str, markup: &mut String) {
+        for c in value.chars() {
+            match c {
+                '&' => markup.push_str("&amp;"),
+                '<' => markup.push_str("&lt;"),
+                '>' => markup.push_str("&gt;"),
+                '"' => markup.push_str("&quot;"),
+                _ => markup.push(c),
+            }
+        }
+    }
+
+    fn collect(element: &Element, markup: &mut String) {
+        for node in &element.children {
+            match node {
+                Node::Text(value) => escape(value, markup),
+                Node::Element(child) if child.local_name() == "exclude" => {}
+                Node::Element(child) => {
+                    markup.push('<');
+                    markup.push_str(&child.name);
+                    for (key, value) in &child.attributes {
+                        markup.push(' ');
+                        markup.push_str(key);
+                        markup.push_str("=\"");
+                        escape(value, markup);
+                        markup.push('"');
+                    }
+                    if child.children.is_empty() {
+                        markup.push_str("/>");
+                    } else {
+                        markup.push('>');
+                        collect(child, markup);
+                        markup.push_str("</");
+                        markup.push_str(&child.name);
+                        markup.push('>');
+                    }
+                }
+            }
+        }
+    }
+
+    let mut markup = String::new();
+    collect(element, &mut markup);
+    markup
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Apply an `ixt:` / `ixt-sec:` format transform to displayed text
+///
+/// Transform names are compared without prefix, dashes or case, so the
+/// TR1 (`numcommadot`), TR2 (`numdotdecimal`) and TR3/TR4
+/// (`num-dot-decimal`) spellings are all accepted. Returns `None` for an
+/// unknown transform or when the transform finds no value.
+fn transform(format: &str, text: &str) -> Option<String> {
+    let name: String = local_name(format)
+        .chars()
+        .filter(|c| *c != '-')
+        .flat_map(char::to_lowercase)
+        .collect();
+    let text = text.trim();
+
+    match name.as_str() {
+        "numdotdecimal" | "numcommadot" | "numspacedot" | "numdotdecimalin" => {
+            normalize_number(text, '.')
+        }
+        "numcommadecimal" | "numdotcomma" | "numspacecomma" | "numcomma" => {
+            normalize_number(text, ',')
+        }
+        "zerodash" | "fixedzero" | "numdash" => Some("0".to_string()),
+        "numwordsen" | "numberwordsen" => number_words(text),
+        "fixedempty" => Some(String::new()),
+        "fixedtrue" | "booleantrue" => Some("true".to_string()),
+        "fixedfalse" | "booleanfalse" => Some("false".to_string()),
+        "datemonthdayyear" | "dateusmonthdayyear" => numeric_date(text, [1, 0, 2]),
+        "datedaymonthyear" | "dateeudaymonthyear" => numeric_date(text, [0, 1, 2]),
+        "dateyearmonthday" => numeric_date(text, [2, 1, 0]),
+        "datemonthnamedayyearen" | "datemonthdayyearen" => named_date(text, false),
+        "datedaymonthnameyearen" | "datedaymonthyearen" => named_date(text, true),
+        _ => None,
+    }
+}
+
+/// Keep the digits of a displayed number, using `decimal` as the separator
+fn normalize_number(text: &str, decimal: char) -> Option<String> {
+    let mut number = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+        } else if c == decimal && !number.contains('.') {
+            number.push('.');
+        }
+    }
+
+    if !number.bytes().any(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    if number.starts_with('.') {
+        number.insert(0, '0');
+    }
+    Some(trim_number(number))
+}
+
+/// Shift the decimal point of a plain decimal number by `scale` places
+fn apply_scale(number: &str, scale: i32) -> String {
+    let (int_part, frac_part) = number.split_once('.').unwrap_or((number, ""));
+    let digits = format!("{}{}", int_part, frac_part);
+    let point = int_part.len() as i64 + i64::from(scale);
+
+    let shifted = if point <= 0 {
+        format!("0.{}{}", "0".repeat(point.unsigned_abs() as usize), digits)
+    } else if point as usize >= digits.len() {
+        format!("{}{}", digits, "0".repeat(point as usize - digits.len()))
+    } else {
+        format!(
+            "{}.{}",
+            &digits[..point as usize],
+            &digits[point as usize..]
+        )
+    };
+
+    trim_number(shifted)
+}
+
+/// Strip leading integer zeros and trailing fraction zeros
+fn trim_number(number: String) -> String {
+    let (int_part, frac_part) = number.split_once('.').unwrap_or((&number, ""));
+    let int_part = int_part.trim_start_matches('0');
+    let frac_part = frac_part.trim_end_matches('0');
+
+    let int_part = if int_part.is_empty() { "0" } else { int_part };
+    if frac_part.is_empty() {
+        int_part.to_string()
+    } else {
+        format!("{}.{}", int_part, frac_part)
+    }
+}
+
+fn is_zero(number: &str) -> bool {
+    number.bytes().all(|b| b == b'0' || b == b'.')
+}
+
+/// Small English number words (`ixt-sec:numwordsen`), e.g. "no", "three"
+fn number_words(text: &str) -> Option<String> {
+    const WORDS: [&str; 21] = [
+        "zero",
+        "one",
+        "two",
+        "three",
+        "four",
+        "five",
+        "six",
+        "seven",
+        "eight",
+        "nine",
+        "ten",
+        "eleven",
+        "twelve",
+        "thirteen",
+        "fourteen",
+        "fifteen",
+        "sixteen",
+        "seventeen",
+        "eighteen",
+        "nineteen",
+        "twenty",
+    ];
+
+    let word = text.trim().to_lowercase();
+    if matches!(word.as_str(), "no" | "none") {
+        return Some("0".to_string());
+    }
+    WORDS
+        .iter()
+        .position(|w| *w == word)
+        .map(|n| n.to_string())
+        .or_else(|| normalize_number(&word, '.'))
+}
+
+/// Parse a numeric date; `order` gives the positions of day, month and year
+fn numeric_date(text: &str, order: [usize; 3]) -> Option<String> {
+    let parts: Vec<&str> = text
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|part| !part.is_empty())
+        .collect();
+    if parts.len() != 3 {
+        return None;
+    }
+    iso_date(
+        parts[order[2]],
+        parts[order[1]].parse().ok()?,
+        parts[order[0]].parse().ok()?,
+    )
+}
+
+/// Parse an English date with a month name, e.g. "September 30, 2023"
+fn named_date(text: &str, day_first: bool) -> Option<String> {
+    const MONTHS: [&str; 12] = [
+        "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+    ];
+
+    let words: Vec<String> = text
+        .split(|c: char| c.is_whitespace() || c == ',' || c == '.' || c == '-')
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    if words.len() != 3 {
+        return None;
+    }
+
+    let (day, month) = if day_first {
+        (&words[0], &words[1])
+    } else {
+        (&words[1], &words[0])
+    };
+    let month = MONTHS.iter().position(|name| month.starts_with(name))? + 1;
+    let day = day.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+
+    iso_date(&words[2], month as u32, day.parse().ok()?)
+}
+
+fn iso_date(year: &str, month: u32, day: u32) -> Option<String> {
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    let year = match year.len() {
+        2 => format!("20{}", year),
+        4 => year.to_string(),
+        _ => return None,
+    };
+    Some(format!("{}-{:02}-{:02}", year, month, day))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_sgml_into_memory, Fact, Period};
+
+    const INLINE: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:ix="http://www.xbrl.org/2013/inlineXBRL" xmlns:ixt="http://www.xbrl.org/inlineXBRL/transformation/2020-02-12" xmlns:ixt-sec="http://www.sec.gov/inlineXBRL/transformation/2015-08-31" xmlns:xbrli="http://www.xbrl.org/2003/instance" xmlns:us-gaap="http://fasb.org/us-gaap/2023" xmlns:dei="http://xbrl.sec.gov/dei/2023">
+<head><title>10-K</title></head>
+<body>
+<div style="display:none"><ix:header>
+<ix:hidden>
+<ix:nonNumeric name="dei:AmendmentFlag" contextRef="c-1" format="ixt:fixed-false">false</ix:nonNumeric>
+</ix:hidden>
+<ix:resources>
+<xbrli:context id="c-1"><xbrli:entity><xbrli:identifier scheme="http://www.sec.gov/CIK">0000320193</xbrli:identifier></xbrli:entity><xbrli:period><xbrli:startDate>2022-09-25</xbrli:startDate><xbrli:endDate>2023-09-30</xbrli:endDate></xbrli:period></xbrli:context>
+<xbrli:context id="c-2"><xbrli:entity><xbrli:identifier scheme="http://www.sec.gov/CIK">0000320193</xbrli:identifier></xbrli:entity><xbrli:period><xbrli:instant>2023-09-30</xbrli:instant></xbrli:period></xbrli:context>
+<xbrli:unit id="usd"><xbrli:measure>iso4217:USD</xbrli:measure></xbrli:unit>
+</ix:resources>
+</ix:header></div>
+<p>Fiscal year ended <ix:nonNumeric name="dei:DocumentPeriodEndDate" contextRef="c-1" format="ixt:date-monthname-day-year-en">September&#160;30, 2023</ix:nonNumeric></p>
+<table><tr>
+<td>Net sales</td>
+<td>$&nbsp;<ix:nonFraction name="us-gaap:Revenues" contextRef="c-1" unitRef="usd" decimals="-6" scale="6" format="ixt:num-dot-decimal">383,285</ix:nonFraction></td>
+<td>(<ix:nonFraction name="us-gaap:NetIncomeLoss" contextRef="c-1" unitRef="usd" decimals="-5" scale="6" sign="-" format="ixt:num-dot-decimal">1.5</ix:nonFraction>)</td>
+<td><ix:nonFraction name="us-gaap:Goodwill" contextRef="c-2" unitRef="usd" decimals="INF" format="ixt:fixed-zero">&#8212;</ix:nonFraction></td>
+</tr></table>
+<ix:nonNumeric name="us-gaap:RevenueRecognitionPolicyTextBlock" contextRef="c-1" continuedAt="cont-1" escape="true"><p>We recognize revenue</p><ix:exclude><p>Page 42</p></ix:exclude></ix:nonNumeric>
+<p>Page break</p>
+<ix:continuation id="cont-1"><p>when control transfers.</p></ix:continuation>
+</body>
+</html>
+"#;
+
+    fn facts() -> Vec<Fact> {
+        parse_inline_xbrl(INLINE.as_bytes())
+            .unwrap()
+            .facts()
+            .collect()
+    }
+
+    fn value<'a>(facts: &'a [Fact], concept: &str) -> &'a str {
+        &facts.iter().find(|f| f.concept == concept).unwrap().value
+    }
+
+    #[test]
+    fn test_numeric_facts() {
+        let facts = facts();
+
+        let revenue = facts
+            .iter()
+            .find(|f| f.concept == "us-gaap:Revenues")
+            .unwrap();
+        assert_eq!(revenue.value, "383285000000");
+        assert_eq!(revenue.unit.as_deref(), Some("iso4217:USD"));
+        assert_eq!(revenue.decimals.as_deref(), Some("-6"));
+        assert_eq!(revenue.entity, "0000320193");
+
+        assert_eq!(value(&facts, "us-gaap:NetIncomeLoss"), "-1500000");
+        assert_eq!(value(&facts, "us-gaap:Goodwill"), "0");
+    }
+
+    #[test]
+    fn test_text_facts() {
+        let facts = facts();
+
+        assert_eq!(value(&facts, "dei:AmendmentFlag"), "false");
+        assert_eq!(value(&facts, "dei:DocumentPeriodEndDate"), "2023-09-30");
+        assert_eq!(
+            value(&facts, "us-gaap:RevenueRecognitionPolicyTextBlock"),
+            "<p>We recognize revenue</p> <p>when control transfers.</p>"
+        );
+        let hidden = facts
+            .iter()
+            .find(|f| f.concept == "dei:AmendmentFlag")
+            .unwrap();
+        assert!(matches!(hidden.period, Period::Duration { .. }));
+    }
+
+    #[test]
+    fn test_untransformed_facts_are_kept() {
+        let content = r#"<html xmlns:ix="http://www.xbrl.org/2013/inlineXBRL"><body>
+<ix:nonFraction name="us-gaap:Assets" contextRef="c-1" unitRef="usd" scale="3" format="ixt:num-unknown">1,234</ix:nonFraction>
+<ix:nonFraction name="us-gaap:Liabilities" contextRef="c-1" unitRef="usd" format="ixt:num-dot-decimal">n/a</ix:nonFraction>
+<ix:nonNumeric name="dei:DocumentType" contextRef="c-1" format="ixt:date-monthname-day-year-en">10-K</ix:nonNumeric>
+<ix:nonFraction name="us-gaap:Equity" contextRef="c-1" unitRef="usd" format="ixt:num-dot-decimal">5</ix:nonFraction>
+</body></html>"#;
+        let filing = parse_inline_xbrl(content.as_bytes()).unwrap();
+        let facts: Vec<_> = filing
+            .raw_facts
+            .iter()
+            .map(|fact| (fact.value.as_str(), fact.untransformed))
+            .collect();
+        assert_eq!(
+            facts,
+            vec![("1,234", true), ("n/a", true), ("10-K", true), ("5", false)]
+        );
+    }
+
+    #[test]
+    fn test_out_of_range_scale_is_not_applied() {
+        let content = r#"<html xmlns:ix="http://www.xbrl.org/2013/inlineXBRL"><body>
+<ix:nonFraction name="us-gaap:Assets" contextRef="c-1" unitRef="usd" scale="1500000000">1</ix:nonFraction>
+<ix:nonFraction name="us-gaap:Liabilities" contextRef="c-1" unitRef="usd" scale="-31">2</ix:nonFraction>
+<ix:nonFraction name="us-gaap:Equity" contextRef="c-1" unitRef="usd" scale="30">3</ix:nonFraction>
+</body></html>"#;
+        let filing = parse_inline_xbrl(content.as_bytes()).unwrap();
+        let facts: Vec<_> = filing
+            .raw_facts
+            .iter()
+            .map(|fact| (fact.value.as_str(), fact.untransformed))
+            .collect();
+        assert_eq!(
+            facts,
+            vec![
+                ("1", true),
+                ("2", true),
+                ("3000000000000000000000000000000", false)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_deep_nesting() {
+        let nested = |depth: usize| {
+            format!(
+                r#"<html xmlns:ix="http://www.xbrl.org/2013/inlineXBRL"><body><ix:nonNumeric name="us-gaap:PolicyTextBlock" contextRef="c-1" escape="true">{}x</ix:nonNumeric></body></html>"#,
+                "<div>".repeat(depth)
+            )
+        };
+
+        let filing = parse_inline_xbrl(nested(500).as_bytes()).unwrap();
+        assert_eq!(
+            filing.raw_facts[0].value,
+            format!("{}x{}", "<div>".repeat(500), "</div>".repeat(500))
+        );
+        assert!(matches!(
+            parse_inline_xbrl(nested(200_000).as_bytes()),
+            Err(ParseError::InvalidContent(_))
+        ));
+    }
+
+    #[test]
+    fn test_scale_and_transforms() {
+        assert_eq!(apply_scale("12.5", -2), "0.125");
+        assert_eq!(apply_scale("0.25", 3), "250");
+        assert_eq!(apply_scale("7", 0), "7");
+        assert_eq!(
+            transform("ixt:numcommadecimal", "1.234,50").as_deref(),
+            Some("1234.5")
+        );
+        assert_eq!(
+            transform("ixt:datemonthdayyear", "9/30/23").as_deref(),
+            Some("2023-09-30")
+        );
+        assert_eq!(
+            transform("ixt-sec:numwordsen", "None").as_deref(),
+            Some("0")
+        );
+    }
+
+    #[test]
+    fn test_inline_primary_document_in_submission() {
+        let submission = format!(
+            "<SUBMISSION>\n<TYPE>10-K\n<DOCUMENT>\n<TYPE>10-K\n<FILENAME>aapl-20230930.htm\n<TEXT>\n<XBRL>\n{}</XBRL>\n</TEXT>\n</DOCUMENT>\n</SUBMISSION>\n",
+            INLINE
+        );
+        let (metadata, documents) = parse_sgml_into_memory(Some(submission), None).unwrap();
+        assert!(is_inline_xbrl(&documents[0]));
+
+        let filing = XbrlFiling::from_submission(&metadata, &documents)
+            .unwrap()
+            .unwrap();
+        assert_eq!(filing.facts().count(), 6);
+    }
+}
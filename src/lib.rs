@@ -1,7 +1,76 @@
+#[cfg(feature = "async")]
+mod async_stream;
+mod attachments;
+mod borrowed;
+mod byte_parser;
+mod detect;
+mod exhibits;
+#[cfg(feature = "fuzzing")]
+#[doc(hidden)]
+pub mod fuzzing;
+mod html;
+#[cfg(feature = "form13f")]
+mod information_table;
 mod io;
+#[cfg(feature = "xbrl")]
+mod ixbrl;
+#[cfg(feature = "ownership")]
+mod ownership;
+mod schema;
+mod sections;
+mod series;
 mod sgml;
+mod stream;
+mod tables;
+mod tag_tree;
+mod text;
 mod types;
 mod utils;
+mod uu_decoder;
+#[cfg(feature = "xbrl")]
+mod xbrl;
+#[cfg(feature = "xml")]
+mod xml;
 
-pub use sgml::{parse_sgml_into_memory, parse_sgml_submission};
-pub use types::{MetadataDict, MetadataValue, ParseError};
+#[cfg(feature = "async")]
+pub use async_stream::{parse_events_async, parse_events_async_with_options};
+pub use attachments::{verify_attachment, AttachmentCheck, AttachmentFormat, AttachmentStatus};
+pub use borrowed::{DictRef, DocumentRef, ParsedSubmission, SubmissionIndex, ValueRef};
+pub use byte_parser::{parse_sgml_bytes, parse_sgml_bytes_with_options};
+pub use detect::{detect_submission_format, Detection};
+pub use exhibits::{classify_exhibit, Exhibit, ExhibitIndex, ExhibitKind, ExhibitNumber};
+pub use html::{is_html, Tag, Token, Tokenizer};
+#[cfg(feature = "form13f")]
+pub use information_table::{find_information_table, parse_information_table, Holding};
+#[cfg(feature = "xbrl")]
+pub use ixbrl::{is_inline_xbrl, parse_inline_xbrl};
+#[cfg(feature = "ownership")]
+pub use ownership::{
+    ownership_documents, parse_ownership_document, Field, Footnote, Issuer, OwnershipDocument,
+    OwnershipRow, ReportingOwner, Signature,
+};
+pub use schema::{is_repeatable_tag, REPEATABLE_TAGS};
+pub use sections::{segment_document, segment_submission, FormKind, Section};
+pub use series::{
+    parse_series_and_classes, ClassContract, MergerParty, NewClasses, Series, SeriesAndClasses,
+    SeriesMerger,
+};
+pub use sgml::{
+    parse_sgml_into_memory, parse_sgml_into_memory_with_options, parse_sgml_submission,
+    parse_sgml_submission_as_text,
+};
+pub use stream::{
+    parse_events, parse_events_with_options, parse_header_only, parse_header_only_with_options,
+    Event, Events, StreamParser,
+};
+pub use tables::{
+    extract_html_tables, extract_tables, extract_text_tables, parse_cell_number, Cell, Table,
+};
+pub use text::{html_to_text, render_text, TextOptions};
+pub use types::{
+    ContinuationJoin, MetadataDict, MetadataValue, ParseError, ParseLimits, ParseOptions,
+    SubmissionType,
+};
+pub use uu_decoder::decode as uu_decode;
+#[cfg(feature = "xbrl")]
+pub use xbrl::{ConceptDefinition, Context, Fact, Period, RawFact, Relationship, Unit, XbrlFiling};
@@ -1,23 +1,40 @@
-use secsgml::{parse_sgml_into_memory, parse_sgml_submission, MetadataValue};
+use secsgml::{parse_sgml_submission, parse_sgml_submission_as_text, TextOptions};
 use std::env;
 use std::path::Path;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args: Vec<String> = env::args().collect();
 
-    if args.len() < 3 {
-        eprintln!("Usage: {} <path_to_sgml_file> <output_directory>", args[0]);
+    // `--text` writes HTML documents as plain text
+    let text_mode = args.iter().skip(1).any(|arg| arg == "--text");
+    let paths: Vec<&String> = args
+        .iter()
+        .skip(1)
+        .filter(|arg| !arg.starts_with("--"))
+        .collect();
+
+    if paths.len() < 2 {
+        eprintln!(
+            "Usage: {} [--text] <path_to_sgml_file> <output_directory>",
+            args[0]
+        );
         std::process::exit(1);
     }
 
-    let filepath = Path::new(&args[1]);
-    let output_dir = Path::new(&args[2]);
+    let filepath = Path::new(paths[0]);
+    let output_dir = Path::new(paths[1]);
 
     println!("Parsing SGML file: {}", filepath.display());
     println!("Output directory: {}", output_dir.display());
 
     // Parse and write to disk
-    match parse_sgml_submission(None, Some(filepath), output_dir) {
+    let result = if text_mode {
+        parse_sgml_submission_as_text(None, Some(filepath), output_dir, &TextOptions::default())
+    } else {
+        parse_sgml_submission(None, Some(filepath), output_dir)
+    };
+
+    match result {
         Ok(()) => {
             println!(
                 "Successfully wrote SGML submission to {}",
@@ -0,0 +1,493 @@
+use memchr::memmem;
+use serde::{Deserialize, Serialize};
+
+use crate::types::{MetadataDict, MetadataValue, ParseError};
+use crate::xml::{parse_xml, Element};
+
+/// Document types that carry an `<ownershipDocument>`
+const OWNERSHIP_TYPES: &[&str] = &["3", "3/A", "4", "4/A", "5", "5/A"];
+
+/// Value of an ownership form field with the footnotes attached to it
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Field {
+    pub value: Option<String>,
+    pub footnote_ids: Vec<String>,
+}
+
+impl Field {
+    /// Parse the value as a number, ignoring thousands separators
+    pub fn as_f64(&self) -> Option<f64> {
+        self.value.as_deref()?.replace(',', "").trim().parse().ok()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Issuer {
+    pub cik: String,
+    pub name: String,
+    pub trading_symbol: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReportingOwner {
+    pub cik: String,
+    pub name: String,
+    pub street1: Option<String>,
+    pub street2: Option<String>,
+    pub city: Option<String>,
+    pub state: Option<String>,
+    pub zip_code: Option<String>,
+    pub is_director: bool,
+    pub is_officer: bool,
+    pub is_ten_percent_owner: bool,
+    pub is_other: bool,
+    pub officer_title: Option<String>,
+    pub other_text: Option<String>,
+}
+
+/// Row of the non-derivative or derivative table
+///
+/// Transactions and holdings share this shape; holdings have no
+/// transaction date, coding or amounts. The derivative-only fields are
+/// default for non-derivative rows.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OwnershipRow {
+    pub security_title: Field,
+    pub transaction_date: Field,
+    pub deemed_execution_date: Field,
+    pub form_type: Option<String>,
+    pub transaction_code: Option<String>,
+    pub equity_swap_involved: Option<bool>,
+    pub coding_footnote_ids: Vec<String>,
+    pub timeliness: Field,
+    pub shares: Field,
+    /// Total value, reported instead of shares for some derivatives
+    pub total_value: Field,
+    pub price_per_share: Field,
+    /// `A` (acquired) or `D` (disposed)
+    pub acquired_disposed: Field,
+    pub shares_owned_following: Field,
+    pub value_owned_following: Field,
+    /// `D` (direct) or `I` (indirect)
+    pub direct_or_indirect: Field,
+    pub nature_of_ownership: Field,
+    pub conversion_or_exercise_price: Field,
+    pub exercise_date: Field,
+    pub expiration_date: Field,
+    pub underlying_security_title: Field,
+    pub underlying_security_shares: Field,
+    pub underlying_security_value: Field,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Footnote {
+    pub id: String,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Signature {
+    pub name: String,
+    pub date: Option<String>,
+}
+
+/// Typed Form 3, 4 or 5 `<ownershipDocument>`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OwnershipDocument {
+    pub schema_version: Option<String>,
+    pub document_type: String,
+    pub period_of_report: Option<String>,
+    pub date_of_original_submission: Option<String>,
+    pub not_subject_to_section16: Option<bool>,
+    pub issuer: Issuer,
+    pub reporting_owners: Vec<ReportingOwner>,
+    pub non_derivative_transactions: Vec<OwnershipRow>,
+    pub non_derivative_holdings: Vec<OwnershipRow>,
+    pub derivative_transactions: Vec<OwnershipRow>,
+    pub derivative_holdings: Vec<OwnershipRow>,
+    pub footnotes: Vec<Footnote>,
+    pub remarks: Option<String>,
+    pub signatures: Vec<Signature>,
+}
+
+/// Parse the `<ownershipDocument>` of a Form 3, 4 or 5 document
+///
+/// `content` is the document text as returned by either parser; the
+/// `<XML>` wrapper and XML declaration may be left in place.
+pub fn parse_ownership_document(content: &[u8]) -> Result<OwnershipDocument, ParseError> {
+    let document = parse_xml(content)?;
+    let root = document
+        .find("ownershipDocument")
+        .ok_or_else(|| ParseError::InvalidContent("No ownershipDocument element".to_string()))?;
+
+    let mut result = OwnershipDocument {
+        schema_version: root.child_text("schemaVersion"),
+        document_type: root.child_text("documentType").unwrap_or_default(),
+        period_of_report: root.child_text("periodOfReport"),
+        date_of_original_submission: root.child_text("dateOfOriginalSubmission"),
+        not_subject_to_section16: root.child_text("notSubjectToSection16").map(|v| flag(&v)),
+        remarks: root.child_text("remarks").filter(|r| !r.is_empty()),
+        ..OwnershipDocument::default()
+    };
+
+    if let Some(issuer) = root.child("issuer") {
+        result.issuer = Issuer {
+            cik: issuer.child_text("issuerCik").unwrap_or_default(),
+            name: issuer.child_text("issuerName").unwrap_or_default(),
+            trading_symbol: issuer
+                .child_text("issuerTradingSymbol")
+                .filter(|s| !s.is_empty()),
+        };
+    }
+
+    result.reporting_owners = root
+        .children_named("reportingOwner")
+        .map(reporting_owner)
+        .collect();
+
+    if let Some(table) = root.child("nonDerivativeTable") {
+        result.non_derivative_transactions = rows(table, "nonDerivativeTransaction");
+        result.non_derivative_holdings = rows(table, "nonDerivativeHolding");
+    }
+    if let Some(table) = root.child("derivativeTable") {
+        result.derivative_transactions = rows(table, "derivativeTransaction");
+        result.derivative_holdings = rows(table, "derivativeHolding");
+    }
+
+    if let Some(footnotes) = root.child("footnotes") {
+        result.footnotes = footnotes
+            .children_named("footnote")
+            .map(|footnote| Footnote {
+                id: footnote.attr("id").unwrap_or_default().to_string(),
+                text: footnote.text().trim().to_string(),
+            })
+            .collect();
+    }
+
+    result.signatures = root
+        .children_named("ownerSignature")
+        .map(|signature| Signature {
+            name: signature.child_text("signatureName").unwrap_or_default(),
+            date: signature.child_text("signatureDate"),
+        })
+        .collect();
+
+    Ok(result)
+}
+
+/// Iterate over the ownership documents of a parsed submission
+///
+/// Documents are picked by `<TYPE>` (3, 4, 5 and their amendments, or any
+/// document without a type) and must contain an `<ownershipDocument>`, so
+/// pre-XML text forms are skipped. Each item pairs the document's position
+/// in `documents` with its parse result.
+pub fn ownership_documents<'a>(
+    metadata: &'a MetadataDict,
+    documents: &'a [Vec<u8>],
+) -> impl Iterator<Item = (usize, Result<OwnershipDocument, ParseError>)> + 'a {
+    let types = metadata
+        .get("documents")
+        .and_then(MetadataValue::as_list)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+
+    documents
+        .iter()
+        .enumerate()
+        .filter(move |(index, content)| {
+            let doc_type = types
+                .get(*index)
+                .and_then(MetadataValue::as_dict)
+                .and_then(|doc| doc.get("type"))
+                .and_then(MetadataValue::as_text)
+                .map(|doc_type| doc_type.trim().to_uppercase());
+            let is_ownership_type = doc_type.is_none_or(|t| OWNERSHIP_TYPES.contains(&t.as_str()));
+            is_ownership_type && memmem::find(content, b"<ownershipDocument").is_some()
+        })
+        .map(|(index, content)| (index, parse_ownership_document(content)))
+}
+
+fn reporting_owner(owner: &Element) -> ReportingOwner {
+    let id = owner.child("reportingOwnerId");
+    let address = owner.child("reportingOwnerAddress");
+    let relationship = owner.child("reportingOwnerRelationship");
+
+    let id_text = |name| id.and_then(|id| id.child_text(name));
+    let address_text = |name| {
+        address
+            .and_then(|address| address.child_text(name))
+            .filter(|text| !text.is_empty())
+    };
+    let relationship_text = |name| relationship.and_then(|rel| rel.child_text(name));
+    let relationship_flag = |name| relationship_text(name).is_some_and(|value| flag(&value));
+
+    ReportingOwner {
+        cik: id_text("rptOwnerCik").unwrap_or_default(),
+        name: id_text("rptOwnerName").unwrap_or_default(),
+        street1: address_text("rptOwnerStreet1"),
+        street2: address_text("rptOwnerStreet2"),
+        city: address_text("rptOwnerCity"),
+        state: address_text("rptOwnerState"),
+        zip_code: address_text("rptOwnerZipCode"),
+        is_director: relationship_flag("isDirector"),
+        is_officer: relationship_flag("isOfficer"),
+        is_ten_percent_owner: relationship_flag("isTenPercentOwner"),
+        is_other: relationship_flag("isOther"),
+        officer_title: relationship_text("officerTitle").filter(|t| !t.is_empty()),
+        other_text: relationship_text("otherText").filter(|t| !t.is_empty()),
+    }
+}
+
+fn rows(table: &Element, name: &str) -> Vec<OwnershipRow> {
+    table.children_named(name).map(row).collect()
+}
+
+fn row(row: &Element) -> OwnershipRow {
+    let coding = row.child("transactionCoding");
+    let amounts = row.child("transactionAmounts");
+    let post = row.child("postTransactionAmounts");
+    let nature = row.child("ownershipNature");
+    let underlying = row.child("underlyingSecurity");
+
+    let nested = |parent: Option<&Element>, name| {
+        parent
+            .and_then(|parent| parent.child(name))
+            .map(field)
+            .unwrap_or_default()
+    };
+
+    OwnershipRow {
+        security_title: nested(Some(row), "securityTitle"),
+        transaction_date: nested(Some(row), "transactionDate"),
+        deemed_execution_date: nested(Some(row), "deemedExecutionDate"),
+        form_type: coding.and_then(|c| c.child_text("transactionFormType")),
+        transaction_code: coding.and_then(|c| c.child_text("transactionCode")),
+        equity_swap_involved: coding
+            .and_then(|c| c.child_text("equitySwapInvolved"))
+            .map(|v| flag(&v)),
+        coding_footnote_ids: coding.map(footnote_ids).unwrap_or_default(),
+        timeliness: nested(Some(row), "transactionTimeliness"),
+        shares: nested(amounts, "transactionShares"),
+        total_value: nested(amounts, "transactionTotalValue"),
+        price_per_share: nested(amounts, "transactionPricePerShare"),
+        acquired_disposed: nested(amounts, "transactionAcquiredDisposedCode"),
+        shares_owned_following: nested(post, "sharesOwnedFollowingTransaction"),
+        value_owned_following: nested(post, "valueOwnedFollowingTransaction"),
+        direct_or_indirect: nested(nature, "directOrIndirectOwnership"),
+        nature_of_ownership: nested(nature, "natureOfOwnership"),
+        conversion_or_exercise_price: nested(Some(row), "conversionOrExercisePrice"),
+        exercise_date: nested(Some(row), "exerciseDate"),
+        expiration_date: nested(Some(row), "expirationDate"),
+        underlying_security_title: nested(underlying, "underlyingSecurityTitle"),
+        underlying_security_shares: nested(underlying, "underlyingSecurityShares"),
+        underlying_security_value: nested(underlying, "underlyingSecurityValue"),
+    }
+}
+
+/// Read a `<value>` field; older filings put the text directly in the element
+fn field(element: &Element) -> Field {
+    let value = match element.child("value") {
+        Some(value) => value.text(),
+        None if element.child("footnoteId").is_none() => element.text(),
+        None => String::new(),
+    };
+    let value = value.trim();
+
+    Field {
+        value: (!value.is_empty()).then(|| value.to_string()),
+        footnote_ids: footnote_ids(element),
+    }
+}
+
+fn footnote_ids(element: &Element) -> Vec<String> {
+    element
+        .children_named("footnoteId")
+        .filter_map(|footnote| footnote.attr("id"))
+        .map(str::to_string)
+        .collect()
+}
+
+fn flag(value: &str) -> bool {
+    matches!(value.trim(), "1" | "true" | "TRUE" | "True" | "Y")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_sgml_bytes;
+
+    const FORM4: &str = r#"<SEC-DOCUMENT>0001234567-24-000001.txt : 20240315
+<SEC-HEADER>0001234567-24-000001.hdr.sgml : 20240315
+ACCESSION NUMBER:		0001234567-24-000001
+CONFORMED SUBMISSION TYPE:	4
+PUBLIC DOCUMENT COUNT:		1
+</SEC-HEADER>
+<DOCUMENT>
+<TYPE>4
+<SEQUENCE>1
+<FILENAME>form4.xml
+<TEXT>
+<XML>
+<?xml version="1.0"?>
+<ownershipDocument>
+    <schemaVersion>X0508</schemaVersion>
+    <documentType>4</documentType>
+    <periodOfReport>2024-03-13</periodOfReport>
+    <notSubjectToSection16>0</notSubjectToSection16>
+    <issuer>
+        <issuerCik>0000320193</issuerCik>
+        <issuerName>Apple Inc.</issuerName>
+        <issuerTradingSymbol>AAPL</issuerTradingSymbol>
+    </issuer>
+    <reportingOwner>
+        <reportingOwnerId>
+            <rptOwnerCik>0001214128</rptOwnerCik>
+            <rptOwnerName>DOE JANE</rptOwnerName>
+        </reportingOwnerId>
+        <reportingOwnerAddress>
+            <rptOwnerStreet1>ONE APPLE PARK WAY</rptOwnerStreet1>
+            <rptOwnerStreet2></rptOwnerStreet2>
+            <rptOwnerCity>CUPERTINO</rptOwnerCity>
+            <rptOwnerState>CA</rptOwnerState>
+            <rptOwnerZipCode>95014</rptOwnerZipCode>
+        </reportingOwnerAddress>
+        <reportingOwnerRelationship>
+            <isOfficer>1</isOfficer>
+            <officerTitle>SVP, General Counsel</officerTitle>
+        </reportingOwnerRelationship>
+    </reportingOwner>
+    <nonDerivativeTable>
+        <nonDerivativeTransaction>
+            <securityTitle><value>Common Stock</value></securityTitle>
+            <transactionDate><value>2024-03-13</value></transactionDate>
+            <transactionCoding>
+                <transactionFormType>4</transactionFormType>
+                <transactionCode>S</transactionCode>
+                <equitySwapInvolved>0</equitySwapInvolved>
+                <footnoteId id="F1"/>
+            </transactionCoding>
+            <transactionAmounts>
+                <transactionShares><value>8,000</value></transactionShares>
+                <transactionPricePerShare><value>171.45</value><footnoteId id="F2"/></transactionPricePerShare>
+                <transactionAcquiredDisposedCode><value>D</value></transactionAcquiredDisposedCode>
+            </transactionAmounts>
+            <postTransactionAmounts>
+                <sharesOwnedFollowingTransaction><value>120000</value></sharesOwnedFollowingTransaction>
+            </postTransactionAmounts>
+            <ownershipNature>
+                <directOrIndirectOwnership><value>D</value></directOrIndirectOwnership>
+            </ownershipNature>
+        </nonDerivativeTransaction>
+        <nonDerivativeHolding>
+            <securityTitle><value>Common Stock</value></securityTitle>
+            <postTransactionAmounts>
+                <sharesOwnedFollowingTransaction><value>5000</value></sharesOwnedFollowingTransaction>
+            </postTransactionAmounts>
+            <ownershipNature>
+                <directOrIndirectOwnership><value>I</value></directOrIndirectOwnership>
+                <natureOfOwnership><value>By Trust</value></natureOfOwnership>
+            </ownershipNature>
+        </nonDerivativeHolding>
+    </nonDerivativeTable>
+    <derivativeTable>
+        <derivativeTransaction>
+            <securityTitle><value>Restricted Stock Unit</value></securityTitle>
+            <conversionOrExercisePrice><footnoteId id="F3"/></conversionOrExercisePrice>
+            <transactionDate><value>2024-03-13</value></transactionDate>
+            <transactionCoding>
+                <transactionFormType>4</transactionFormType>
+                <transactionCode>M</transactionCode>
+                <equitySwapInvolved>0</equitySwapInvolved>
+            </transactionCoding>
+            <transactionAmounts>
+                <transactionShares><value>8000</value></transactionShares>
+                <transactionPricePerShare><value>0</value></transactionPricePerShare>
+                <transactionAcquiredDisposedCode><value>D</value></transactionAcquiredDisposedCode>
+            </transactionAmounts>
+            <exerciseDate><footnoteId id="F4"/></exerciseDate>
+            <expirationDate><footnoteId id="F4"/></expirationDate>
+            <underlyingSecurity>
+                <underlyingSecurityTitle><value>Common Stock</value></underlyingSecurityTitle>
+                <underlyingSecurityShares><value>8000</value></underlyingSecurityShares>
+            </underlyingSecurity>
+            <postTransactionAmounts>
+                <sharesOwnedFollowingTransaction><value>24000</value></sharesOwnedFollowingTransaction>
+            </postTransactionAmounts>
+            <ownershipNature>
+                <directOrIndirectOwnership><value>D</value></directOrIndirectOwnership>
+            </ownershipNature>
+        </derivativeTransaction>
+    </derivativeTable>
+    <footnotes>
+        <footnote id="F1">Sale under a Rule 10b5-1 plan.</footnote>
+        <footnote id="F2">Weighted average price.</footnote>
+    </footnotes>
+    <remarks></remarks>
+    <ownerSignature>
+        <signatureName>/s/ Attorney-in-fact for Jane Doe</signatureName>
+        <signatureDate>2024-03-15</signatureDate>
+    </ownerSignature>
+</ownershipDocument>
+</XML>
+</TEXT>
+</DOCUMENT>
+</SEC-DOCUMENT>
+"#;
+
+    #[test]
+    fn test_form4_from_submission() {
+        let (metadata, documents) = parse_sgml_bytes(FORM4.as_bytes()).unwrap();
+        let parsed: Vec<_> = ownership_documents(&metadata, &documents).collect();
+        assert_eq!(parsed.len(), 1);
+        let (index, form) = &parsed[0];
+        assert_eq!(*index, 0);
+        let form = form.as_ref().unwrap();
+
+        assert_eq!(form.document_type, "4");
+        assert_eq!(form.not_subject_to_section16, Some(false));
+        assert_eq!(form.issuer.trading_symbol.as_deref(), Some("AAPL"));
+        assert_eq!(form.remarks, None);
+
+        let owner = &form.reporting_owners[0];
+        assert_eq!(owner.cik, "0001214128");
+        assert!(owner.is_officer && !owner.is_director);
+        assert_eq!(owner.officer_title.as_deref(), Some("SVP, General Counsel"));
+        assert_eq!(owner.street2, None);
+
+        let sale = &form.non_derivative_transactions[0];
+        assert_eq!(sale.transaction_code.as_deref(), Some("S"));
+        assert_eq!(sale.equity_swap_involved, Some(false));
+        assert_eq!(sale.coding_footnote_ids, vec!["F1"]);
+        assert_eq!(sale.shares.as_f64(), Some(8000.0));
+        assert_eq!(sale.price_per_share.value.as_deref(), Some("171.45"));
+        assert_eq!(sale.price_per_share.footnote_ids, vec!["F2"]);
+        assert_eq!(sale.acquired_disposed.value.as_deref(), Some("D"));
+
+        let holding = &form.non_derivative_holdings[0];
+        assert_eq!(holding.direct_or_indirect.value.as_deref(), Some("I"));
+        assert_eq!(
+            holding.nature_of_ownership.value.as_deref(),
+            Some("By Trust")
+        );
+        assert_eq!(holding.transaction_date, Field::default());
+
+        let rsu = &form.derivative_transactions[0];
+        assert_eq!(rsu.conversion_or_exercise_price.value, None);
+        assert_eq!(rsu.conversion_or_exercise_price.footnote_ids, vec!["F3"]);
+        assert_eq!(
+            rsu.underlying_security_shares.value.as_deref(),
+            Some("8000")
+        );
+
+        assert_eq!(form.footnotes.len(), 2);
+        assert_eq!(form.signatures[0].date.as_deref(), Some("2024-03-15"));
+    }
+
+    #[test]
+    fn test_missing_root_is_an_error() {
+        assert!(matches!(
+            parse_ownership_document(b"<XML><edgarSubmission/></XML>"),
+            Err(ParseError::InvalidContent(_))
+        ));
+    }
+}
@@ -0,0 +1,143 @@
+use crate::types::{MetadataDict, MetadataValue};
+
+/// Header tags that can occur more than once under the same parent
+///
+/// With `ParseOptions::always_list` set, values under these keys are always
+/// emitted as lists, even when the tag occurs once. Names are in the dashed
+/// form; tab-formatted keys match with spaces in place of dashes.
+///
+/// | Tag | Repeats when |
+/// |-----|--------------|
+/// | `filer` | several registrants file jointly |
+/// | `reporting-owner` | a Form 3/4/5 has several reporting owners |
+/// | `subject-company` | a schedule or tender offer names several targets |
+/// | `filed-by` | several persons file a schedule together |
+/// | `former-company` | the filer has changed its name more than once |
+/// | `former-name` | a reporting owner has changed name more than once |
+/// | `items` | an 8-K reports several items |
+/// | `item-information` | an 8-K reports several items |
+/// | `group-members` | a Schedule 13D/G lists several group members |
+/// | `depositor`, `securitizer`, `issuing-entity` | ABS filings with several parties |
+/// | `series`, `new-series`, `class-contract` | fund filings with several series or classes |
+/// | `new-classes-contracts` | classes are added to several existing series |
+/// | `merger`, `acquiring-data`, `target-data` | fund mergers with several parties |
+/// | `references-429` | a registration statement refers to several earlier ones |
+pub const REPEATABLE_TAGS: &[&str] = &[
+    "filer",
+    "reporting-owner",
+    "subject-company",
+    "filed-by",
+    "former-company",
+    "former-name",
+    "items",
+    "item-information",
+    "group-members",
+    "depositor",
+    "securitizer",
+    "issuing-entity",
+    "series",
+    "new-series",
+    "class-contract",
+    "new-classes-contracts",
+    "merger",
+    "acquiring-data",
+    "target-data",
+    "references-429",
+];
+
+/// Check whether a header key is in the repeatable tag registry
+pub fn is_repeatable_tag(key: &str) -> bool {
+    REPEATABLE_TAGS
+        .iter()
+        .any(|tag| tag.len() == key.len() && tag.bytes().zip(key.bytes()).all(tag_byte_eq))
+}
+
+/// Compare tag bytes, treating a space in tab-formatted keys as a dash
+fn tag_byte_eq((tag, key): (u8, u8)) -> bool {
+    tag == key || (tag == b'-' && key == b' ')
+}
+
+/// Wrap single values of repeatable tags in lists, recursively
+pub fn apply_always_list(dict: &mut MetadataDict) {
+    for (key, value) in dict.iter_mut() {
+        match value {
+            MetadataValue::Dict(nested) => apply_always_list(nested),
+            MetadataValue::List(list) => {
+                for item in list.iter_mut() {
+                    if let MetadataValue::Dict(nested) = item {
+                        apply_always_list(nested);
+                    }
+                }
+            }
+            MetadataValue::Text(_) => {}
+        }
+
+        if is_repeatable_tag(key) && !matches!(value, MetadataValue::List(_)) {
+            let single = std::mem::replace(value, MetadataValue::List(Vec::new()));
+            *value = MetadataValue::List(vec![single]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_sgml_bytes_with_options, parse_sgml_into_memory_with_options, ParseOptions};
+
+    const FORM4_HEADER: &str = "<SUBMISSION>
+<TYPE>4
+<REPORTING-OWNER>
+<OWNER-DATA>
+<CONFORMED-NAME>DOE JOHN
+</OWNER-DATA>
+</REPORTING-OWNER>
+<ISSUER>
+<COMPANY-DATA>
+<CONFORMED-NAME>EXAMPLE CORP
+</COMPANY-DATA>
+<FORMER-COMPANY>
+<FORMER-CONFORMED-NAME>EXAMPLE INC
+</FORMER-COMPANY>
+</ISSUER>
+";
+
+    #[test]
+    fn test_registry_matches_tab_keys() {
+        assert!(is_repeatable_tag("reporting-owner"));
+        assert!(is_repeatable_tag("former company"));
+        assert!(!is_repeatable_tag("issuer"));
+        assert!(!is_repeatable_tag("filer-x"));
+    }
+
+    #[test]
+    fn test_always_list_in_both_engines() {
+        let options = ParseOptions {
+            always_list: true,
+            ..ParseOptions::default()
+        };
+        let (line_metadata, _) =
+            parse_sgml_into_memory_with_options(Some(FORM4_HEADER.to_string()), None, &options)
+                .unwrap();
+        let (byte_metadata, _) =
+            parse_sgml_bytes_with_options(FORM4_HEADER.as_bytes(), &options).unwrap();
+
+        for metadata in [line_metadata, byte_metadata] {
+            let owners = metadata["reporting-owner"].as_list().unwrap();
+            assert_eq!(owners.len(), 1);
+
+            // Non-repeatable containers keep their shape, nested repeatables are lists
+            let issuer = metadata["issuer"].as_dict().unwrap();
+            assert_eq!(issuer["former-company"].as_list().unwrap().len(), 1);
+            assert!(issuer["company-data"].as_dict().is_some());
+            assert!(metadata["type"].as_text().is_some());
+        }
+    }
+
+    #[test]
+    fn test_default_keeps_single_values() {
+        let (metadata, _) =
+            parse_sgml_bytes_with_options(FORM4_HEADER.as_bytes(), &ParseOptions::default())
+                .unwrap();
+        assert!(metadata["reporting-owner"].as_dict().is_some());
+    }
+}
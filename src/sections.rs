@@ -0,0 +1,698 @@
+use serde::{Deserialize, Serialize};
+
+use crate::html::{is_html, Tokenizer};
+use crate::text::{HtmlRenderer, TextOptions};
+use crate::types::{MetadataDict, MetadataValue};
+use crate::utils::decode_text;
+
+/// Longest line accepted as a heading, in characters
+const MAX_HEADING_LEN: usize = 200;
+
+/// Headings closer than this many characters of text are a table of
+/// contents when at least three follow each other
+const TOC_GAP: usize = 300;
+
+const TEN_K_ITEMS: &[(&str, &str)] = &[
+    ("1", "Business"),
+    ("1A", "Risk Factors"),
+    ("1B", "Unresolved Staff Comments"),
+    ("1C", "Cybersecurity"),
+    ("2", "Properties"),
+    ("3", "Legal Proceedings"),
+    ("4", "Mine Safety Disclosures"),
+    ("5", "Market for Registrant's Common Equity, Related Stockholder Matters and Issuer Purchases of Equity Securities"),
+    ("6", "Selected Financial Data"),
+    ("7", "Management's Discussion and Analysis of Financial Condition and Results of Operations"),
+    ("7A", "Quantitative and Qualitative Disclosures About Market Risk"),
+    ("8", "Financial Statements and Supplementary Data"),
+    ("9", "Changes in and Disagreements with Accountants on Accounting and Financial Disclosure"),
+    ("9A", "Controls and Procedures"),
+    ("9B", "Other Information"),
+    ("9C", "Disclosure Regarding Foreign Jurisdictions that Prevent Inspections"),
+    ("10", "Directors, Executive Officers and Corporate Governance"),
+    ("11", "Executive Compensation"),
+    ("12", "Security Ownership of Certain Beneficial Owners and Management and Related Stockholder Matters"),
+    ("13", "Certain Relationships and Related Transactions, and Director Independence"),
+    ("14", "Principal Accountant Fees and Services"),
+    ("15", "Exhibits and Financial Statement Schedules"),
+    ("16", "Form 10-K Summary"),
+];
+
+const TEN_Q_PART_I_ITEMS: &[(&str, &str)] = &[
+    ("1", "Financial Statements"),
+    (
+        "2",
+        "Management's Discussion and Analysis of Financial Condition and Results of Operations",
+    ),
+    (
+        "3",
+        "Quantitative and Qualitative Disclosures About Market Risk",
+    ),
+    ("4", "Controls and Procedures"),
+];
+
+const TEN_Q_PART_II_ITEMS: &[(&str, &str)] = &[
+    ("1", "Legal Proceedings"),
+    ("1A", "Risk Factors"),
+    (
+        "2",
+        "Unregistered Sales of Equity Securities and Use of Proceeds",
+    ),
+    ("3", "Defaults Upon Senior Securities"),
+    ("4", "Mine Safety Disclosures"),
+    ("5", "Other Information"),
+    ("6", "Exhibits"),
+];
+
+const EIGHT_K_ITEMS: &[(&str, &str)] = &[
+    ("1.01", "Entry into a Material Definitive Agreement"),
+    ("1.02", "Termination of a Material Definitive Agreement"),
+    ("1.03", "Bankruptcy or Receivership"),
+    ("1.04", "Mine Safety - Reporting of Shutdowns and Patterns of Violations"),
+    ("1.05", "Material Cybersecurity Incidents"),
+    ("2.01", "Completion of Acquisition or Disposition of Assets"),
+    ("2.02", "Results of Operations and Financial Condition"),
+    ("2.03", "Creation of a Direct Financial Obligation or an Obligation under an Off-Balance Sheet Arrangement of a Registrant"),
+    ("2.04", "Triggering Events That Accelerate or Increase a Direct Financial Obligation or an Obligation under an Off-Balance Sheet Arrangement"),
+    ("2.05", "Costs Associated with Exit or Disposal Activities"),
+    ("2.06", "Material Impairments"),
+    ("3.01", "Notice of Delisting or Failure to Satisfy a Continued Listing Rule or Standard; Transfer of Listing"),
+    ("3.02", "Unregistered Sales of Equity Securities"),
+    ("3.03", "Material Modification to Rights of Security Holders"),
+    ("4.01", "Changes in Registrant's Certifying Accountant"),
+    ("4.02", "Non-Reliance on Previously Issued Financial Statements or a Related Audit Report or Completed Interim Review"),
+    ("5.01", "Changes in Control of Registrant"),
+    ("5.02", "Departure of Directors or Certain Officers; Election of Directors; Appointment of Certain Officers; Compensatory Arrangements of Certain Officers"),
+    ("5.03", "Amendments to Articles of Incorporation or Bylaws; Change in Fiscal Year"),
+    ("5.04", "Temporary Suspension of Trading Under Registrant's Employee Benefit Plans"),
+    ("5.05", "Amendment to Registrant's Code of Ethics, or Waiver of a Provision of the Code of Ethics"),
+    ("5.06", "Change in Shell Company Status"),
+    ("5.07", "Submission of Matters to a Vote of Security Holders"),
+    ("5.08", "Shareholder Director Nominations"),
+    ("6.01", "ABS Informational and Computational Material"),
+    ("6.02", "Change of Servicer or Trustee"),
+    ("6.03", "Change in Credit Enhancement or Other External Support"),
+    ("6.04", "Failure to Make a Required Distribution"),
+    ("6.05", "Securities Act Updating Disclosure"),
+    ("7.01", "Regulation FD Disclosure"),
+    ("8.01", "Other Events"),
+    ("9.01", "Financial Statements and Exhibits"),
+];
+
+/// Form families with known Item layouts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FormKind {
+    TenK,
+    TenQ,
+    EightK,
+    /// Any other form; every `Item N` heading is accepted
+    Other,
+}
+
+impl FormKind {
+    /// Classify a form type such as `10-K405`, `10-Q/A` or `8-K`
+    pub fn from_form_type(form_type: &str) -> Self {
+        let form_type = form_type.trim().to_uppercase();
+        let base = form_type.split('/').next().unwrap_or_default();
+        if base.starts_with("10-K") {
+            Self::TenK
+        } else if base.starts_with("10-Q") {
+            Self::TenQ
+        } else if base == "8-K" || base == "8-K12B" || base == "8-K15D5" {
+            Self::EightK
+        } else {
+            Self::Other
+        }
+    }
+
+    fn items(self, part: Option<&str>) -> Option<&'static [(&'static str, &'static str)]> {
+        match (self, part) {
+            (Self::TenK, _) => Some(TEN_K_ITEMS),
+            (Self::TenQ, Some("II")) => Some(TEN_Q_PART_II_ITEMS),
+            (Self::TenQ, _) => Some(TEN_Q_PART_I_ITEMS),
+            (Self::EightK, _) => Some(EIGHT_K_ITEMS),
+            (Self::Other, _) => None,
+        }
+    }
+}
+
+/// Item section of a document
+///
+/// `start..end` is a byte range of the document content, from the heading
+/// to the next accepted heading or the end of the document.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Section {
+    /// Item number as written in the form, e.g. `1A` or `2.02`
+    pub item: String,
+    /// `I`, `II`, ... when the heading falls under a `PART` heading
+    pub part: Option<String>,
+    /// Standard item title, or the heading text for unknown items
+    pub title: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Section {
+    /// Slice the section out of the document content
+    pub fn content<'a>(&self, document: &'a [u8]) -> &'a [u8] {
+        &document[self.start.min(document.len())..self.end.min(document.len())]
+    }
+}
+
+/// Segment the primary document of a parsed submission
+///
+/// The form type comes from the header, and for 8-Ks the `ITEMS` /
+/// `ITEM INFORMATION` header values are passed on as hints. The primary
+/// document is the first whose `<TYPE>` is the form type, or the first
+/// document when none is.
+pub fn segment_submission(metadata: &MetadataDict, documents: &[Vec<u8>]) -> Vec<Section> {
+    let form_type = [
+        "type",
+        "conformed submission type",
+        "conformed-submission-type",
+    ]
+    .iter()
+    .find_map(|key| metadata.get(*key).and_then(MetadataValue::as_text))
+    .cloned()
+    .unwrap_or_default();
+
+    let mut hints = Vec::new();
+    for key in ["items", "item information", "item-information"] {
+        match metadata.get(key) {
+            Some(MetadataValue::Text(text)) => hints.push(text.clone()),
+            Some(MetadataValue::List(list)) => {
+                hints.extend(list.iter().filter_map(MetadataValue::as_text).cloned())
+            }
+            _ => {}
+        }
+    }
+
+    let primary = metadata
+        .get("documents")
+        .and_then(MetadataValue::as_list)
+        .and_then(|list| {
+            list.iter().position(|document| {
+                document
+                    .as_dict()
+                    .and_then(|document| document.get("type"))
+                    .and_then(MetadataValue::as_text)
+                    .is_some_and(|doc_type| doc_type.trim().eq_ignore_ascii_case(form_type.trim()))
+            })
+        })
+        .and_then(|index| documents.get(index))
+        .or(documents.first());
+    match primary {
+        Some(primary) => segment_document(primary, &form_type, &hints),
+        None => Vec::new(),
+    }
+}
+
+/// Find the Item sections of a 10-K, 10-Q, 8-K or other document
+///
+/// Headings are lines starting with `Item` and an item number; `PART`
+/// headings set the part, which tells 10-Q Part I and Part II items apart.
+/// A run of three or more headings with little text between them whose
+/// items appear again later is a table of contents and is skipped. Of the
+/// rest, headings are kept only while items keep increasing, which drops
+/// repeated page headers and stray references.
+///
+/// `hints` are 8-K item numbers (`2.02`) or titles (`Results of Operations
+/// and Financial Condition`). When given, only those items are looked for,
+/// and a hinted item without an `Item` heading is found by its title.
+pub fn segment_document(content: &[u8], form_type: &str, hints: &[String]) -> Vec<Section> {
+    let kind = FormKind::from_form_type(form_type);
+    let lines = if is_html(content) {
+        html_lines(content)
+    } else {
+        text_lines(content)
+    };
+
+    let hinted: Vec<&'static str> = if kind == FormKind::EightK {
+        hints.iter().filter_map(|hint| hinted_item(hint)).collect()
+    } else {
+        Vec::new()
+    };
+
+    let candidates = candidates(&lines, kind, &hinted);
+    let candidates = drop_table_of_contents(candidates);
+
+    let mut accepted: Vec<&Candidate> = Vec::new();
+    for candidate in &candidates {
+        if accepted.last().is_none_or(|last| candidate.key > last.key) {
+            accepted.push(candidate);
+        }
+    }
+
+    accepted
+        .iter()
+        .enumerate()
+        .map(|(i, candidate)| Section {
+            item: candidate.item.clone(),
+            part: candidate.part.clone(),
+            title: candidate.title.clone(),
+            start: candidate.offset,
+            end: accepted
+                .get(i + 1)
+                .map_or(content.len(), |next| next.offset),
+        })
+        .collect()
+}
+
+/// Rendered line with the byte offset it starts at
+#[derive(Debug)]
+struct Line {
+    text: String,
+    offset: usize,
+    /// Characters of text before this line
+    text_pos: usize,
+}
+
+#[derive(Debug)]
+struct Candidate {
+    item: String,
+    part: Option<String>,
+    title: String,
+    offset: usize,
+    text_pos: usize,
+    key: (u8, u32, u32, u8),
+}
+
+fn text_lines(content: &[u8]) -> Vec<Line> {
+    let mut lines = Vec::new();
+    let mut offset = 0;
+    let mut text_pos = 0;
+    for raw in content.split(|&b| b == b'\n') {
+        let text = decode_text(raw).trim().to_string();
+        let len = text.chars().count();
+        if !text.is_empty() {
+            lines.push(Line {
+                text,
+                offset,
+                text_pos,
+            });
+        }
+        text_pos += len;
+        offset += raw.len() + 1;
+    }
+    lines
+}
+
+/// Render HTML into lines with the text renderer, treating table cells as
+/// part of one line
+fn html_lines(content: &[u8]) -> Vec<Line> {
+    let options = TextOptions {
+        strip_ixbrl_header: true,
+        cell_separator: " ".to_string(),
+    };
+    let mut renderer = HtmlRenderer::new(&options);
+    let mut tokenizer = Tokenizer::new(content);
+    loop {
+        let offset = tokenizer.offset();
+        let Some(token) = tokenizer.next() else {
+            break;
+        };
+        renderer.token_at(token, offset);
+    }
+
+    let mut text_pos = 0;
+    renderer
+        .finish_lines()
+        .into_iter()
+        .map(|(text, offset)| {
+            let line = Line {
+                offset,
+                text_pos,
+                text,
+            };
+            text_pos += line.text.chars().count();
+            line
+        })
+        .collect()
+}
+
+fn candidates(lines: &[Line], kind: FormKind, hinted: &[&'static str]) -> Vec<Candidate> {
+    let mut candidates = Vec::new();
+    let mut part: Option<String> = None;
+
+    for line in lines {
+        if let Some(numeral) = part_heading(&line.text) {
+            part = Some(numeral);
+            continue;
+        }
+        let Some((item, rest)) = item_heading(&line.text) else {
+            continue;
+        };
+        if !hinted.is_empty() && !hinted.contains(&item.as_str()) {
+            continue;
+        }
+
+        let known = kind.items(part.as_deref());
+        let title = match known {
+            Some(items) => match items.iter().find(|(id, _)| *id == item) {
+                Some((_, title)) => title.to_string(),
+                None => continue,
+            },
+            None => rest,
+        };
+
+        candidates.push(Candidate {
+            key: item_key(kind, part.as_deref(), &item),
+            item,
+            part: part.clone(),
+            title,
+            offset: line.offset,
+            text_pos: line.text_pos,
+        });
+    }
+
+    // Hinted 8-K items without an `Item` heading, found by their title
+    for &item in hinted {
+        if candidates.iter().any(|candidate| candidate.item == item) {
+            continue;
+        }
+        let Some((_, title)) = EIGHT_K_ITEMS.iter().find(|(id, _)| *id == item) else {
+            continue;
+        };
+        let wanted = normalize_title(title);
+        if let Some(line) = lines.iter().find(|line| {
+            line.text.chars().count() <= MAX_HEADING_LEN
+                && normalize_title(&line.text).starts_with(&wanted)
+        }) {
+            candidates.push(Candidate {
+                item: item.to_string(),
+                part: None,
+                title: title.to_string(),
+                offset: line.offset,
+                text_pos: line.text_pos,
+                key: item_key(kind, None, item),
+            });
+        }
+    }
+
+    // Lines of one `<pre>` block share an offset, so order by text as well
+    candidates.sort_by_key(|candidate| (candidate.offset, candidate.text_pos));
+    candidates
+}
+
+/// Drop table of contents entries
+///
+/// A table of contents is a run of increasing items with little text in
+/// between; the run ends where the items start over, which is usually the
+/// first real heading right after it.
+fn drop_table_of_contents(candidates: Vec<Candidate>) -> Vec<Candidate> {
+    let mut in_toc = vec![false; candidates.len()];
+    let mut start = 0;
+
+    while start < candidates.len() {
+        let mut end = start;
+        while end + 1 < candidates.len()
+            && candidates[end + 1]
+                .text_pos
+                .abs_diff(candidates[end].text_pos)
+                < TOC_GAP
+            && candidates[end + 1].key > candidates[end].key
+        {
+            end += 1;
+        }
+
+        if end - start + 1 >= 3 {
+            for i in start..=end {
+                let repeated = candidates[end + 1..]
+                    .iter()
+                    .any(|later| later.key == candidates[i].key);
+                in_toc[i] = repeated;
+            }
+        }
+        start = end + 1;
+    }
+
+    candidates
+        .into_iter()
+        .zip(in_toc)
+        .filter(|(_, toc)| !toc)
+        .map(|(candidate, _)| candidate)
+        .collect()
+}
+
+/// Parse `Item 1A. Risk Factors` into the item number and the rest
+fn item_heading(line: &str) -> Option<(String, String)> {
+    if line.chars().count() > MAX_HEADING_LEN {
+        return None;
+    }
+    let rest = line
+        .get(..4)
+        .filter(|word| word.eq_ignore_ascii_case("item"))
+        .map(|_| &line[4..])?
+        .trim_start_matches(|c: char| c.is_whitespace() || c == '\u{a0}');
+
+    let digits = rest.chars().take_while(char::is_ascii_digit).count();
+    if digits == 0 || digits > 2 {
+        return None;
+    }
+    let mut len = digits;
+
+    // 8-K style `2.02`
+    let bytes = rest.as_bytes();
+    if bytes.get(len) == Some(&b'.')
+        && bytes.get(len + 1).is_some_and(u8::is_ascii_digit)
+        && bytes.get(len + 2).is_some_and(u8::is_ascii_digit)
+    {
+        len += 3;
+    } else if bytes
+        .get(len)
+        .is_some_and(|b| matches!(b.to_ascii_uppercase(), b'A'..=b'D'))
+        && !bytes.get(len + 1).is_some_and(u8::is_ascii_alphabetic)
+    {
+        len += 1;
+    }
+
+    let after = &rest[len..];
+    if after.chars().next().is_some_and(|c| c.is_alphanumeric()) {
+        return None;
+    }
+
+    let title = after
+        .trim_start_matches(|c: char| {
+            c.is_whitespace() || matches!(c, '.' | ':' | '-' | '\u{2013}' | '\u{2014}' | ')')
+        })
+        .trim()
+        .to_string();
+    Some((rest[..len].to_ascii_uppercase(), title))
+}
+
+/// Parse `PART II` or `Part II - Other Information` into its numeral
+fn part_heading(line: &str) -> Option<String> {
+    let rest = line
+        .get(..4)
+        .filter(|word| word.eq_ignore_ascii_case("part"))
+        .map(|_| line[4..].trim_start())?;
+    let numeral: String = rest
+        .chars()
+        .take_while(|c| matches!(c.to_ascii_uppercase(), 'I' | 'V' | 'X'))
+        .collect();
+    let after = &rest[numeral.len()..];
+    if numeral.is_empty() || after.chars().next().is_some_and(char::is_alphanumeric) {
+        return None;
+    }
+    Some(numeral.to_ascii_uppercase())
+}
+
+/// Ordering key: part, then item number, minor number and letter
+fn item_key(kind: FormKind, part: Option<&str>, item: &str) -> (u8, u32, u32, u8) {
+    let part = match (kind, part) {
+        (FormKind::TenQ, Some("II")) => 2,
+        (FormKind::TenQ, _) => 1,
+        _ => 0,
+    };
+    let digits: String = item.chars().take_while(char::is_ascii_digit).collect();
+    let major = digits.parse().unwrap_or(0);
+    let rest = &item[digits.len()..];
+    let (minor, letter) = match rest.strip_prefix('.') {
+        Some(minor) => (minor.parse().unwrap_or(0), 0),
+        None => (0, rest.bytes().next().unwrap_or(0)),
+    };
+    (part, major, minor, letter)
+}
+
+/// Map an 8-K `ITEMS` or `ITEM INFORMATION` value to an item number
+fn hinted_item(hint: &str) -> Option<&'static str> {
+    let hint = hint.trim();
+    if let Some((id, _)) = EIGHT_K_ITEMS.iter().find(|(id, _)| *id == hint) {
+        return Some(id);
+    }
+    let wanted = normalize_title(hint);
+    EIGHT_K_ITEMS
+        .iter()
+        .find(|(_, title)| {
+            let title = normalize_title(title);
+            !wanted.is_empty() && (title.starts_with(&wanted) || wanted.starts_with(&title))
+        })
+        .map(|(id, _)| *id)
+}
+
+/// Lowercase letters and digits only, for comparing titles
+fn normalize_title(title: &str) -> String {
+    title
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_sgml_bytes;
+
+    fn filler(words: usize) -> String {
+        "Lorem ipsum dolor sit amet. ".repeat(words / 5 + 1)
+    }
+
+    #[test]
+    fn test_ten_k_html_with_table_of_contents() {
+        let mut html = String::from("<html><body><p>TABLE OF CONTENTS</p><table>");
+        for (item, title) in [
+            ("1", "Business"),
+            ("1A", "Risk Factors"),
+            ("7", "Management's Discussion"),
+            ("8", "Financial Statements"),
+        ] {
+            html.push_str(&format!(
+                "<tr><td>Item {}.</td><td>{}</td><td>3</td></tr>",
+                item, title
+            ));
+        }
+        html.push_str("</table><p>PART I</p>");
+        for (item, title) in [
+            ("1", "Business"),
+            ("1A", "Risk&nbsp;Factors"),
+            ("7", "Management&#8217;s Discussion"),
+            ("8", "Financial Statements"),
+        ] {
+            html.push_str(&format!(
+                "<p style=\"font-weight:bold\">ITEM {}. {}</p><p>{}</p>",
+                item,
+                title,
+                filler(100)
+            ));
+            if item == "1A" {
+                // A page header repeating an earlier item is not a new section
+                html.push_str("<p>Item 1. Business (continued)</p>");
+                html.push_str(&format!("<p>{}</p>", filler(50)));
+            }
+        }
+        html.push_str("</body></html>");
+        let content = html.as_bytes();
+
+        let sections = segment_document(content, "10-K", &[]);
+        let items: Vec<&str> = sections.iter().map(|s| s.item.as_str()).collect();
+        assert_eq!(items, vec!["1", "1A", "7", "8"]);
+
+        let risk = &sections[1];
+        assert_eq!(risk.title, "Risk Factors");
+        assert_eq!(risk.part.as_deref(), Some("I"));
+        let text = String::from_utf8_lossy(risk.content(content));
+        assert!(text.starts_with("ITEM 1A. Risk&nbsp;Factors</p>"));
+        assert!(text.contains("(continued)"));
+        assert!(!text.contains("ITEM 7"));
+        assert_eq!(sections[3].end, content.len());
+    }
+
+    #[test]
+    fn test_ten_q_parts() {
+        let text = format!(
+            "PART I - FINANCIAL INFORMATION\nItem 1. Financial Statements\n{0}\nItem 2. Management's Discussion\n{0}\nPART II - OTHER INFORMATION\nItem 1. Legal Proceedings\n{0}\nItem 1A. Risk Factors\n{0}\nItem 6. Exhibits\n{0}\n",
+            filler(80)
+        );
+        let sections = segment_document(text.as_bytes(), "10-Q", &[]);
+
+        let keys: Vec<(Option<&str>, &str)> = sections
+            .iter()
+            .map(|s| (s.part.as_deref(), s.item.as_str()))
+            .collect();
+        assert_eq!(
+            keys,
+            vec![
+                (Some("I"), "1"),
+                (Some("I"), "2"),
+                (Some("II"), "1"),
+                (Some("II"), "1A"),
+                (Some("II"), "6"),
+            ]
+        );
+        assert_eq!(sections[2].title, "Legal Proceedings");
+        assert!(text[sections[2].start..].starts_with("Item 1. Legal Proceedings"));
+    }
+
+    #[test]
+    fn test_eight_k_uses_header_hints() {
+        let submission = format!(
+            "<SUBMISSION>\n<TYPE>8-K\n<ITEMS>2.02\n<ITEMS>9.01\n<DOCUMENT>\n<TYPE>8-K\n<TEXT>\n\
+             FORM 8-K\n\
+             Results of Operations and Financial Condition.\n{0}\n\
+             Item 5.02 is not reported here, see Item 2.02 above.\n\
+             Item 9.01 Financial Statements and Exhibits.\n{0}\n\
+             </TEXT>\n</DOCUMENT>\n</SUBMISSION>\n",
+            filler(40)
+        );
+        let (metadata, documents) = parse_sgml_bytes(submission.as_bytes()).unwrap();
+        let sections = segment_submission(&metadata, &documents);
+
+        let items: Vec<&str> = sections.iter().map(|s| s.item.as_str()).collect();
+        assert_eq!(items, vec!["2.02", "9.01"]);
+        assert_eq!(
+            sections[0].title,
+            "Results of Operations and Financial Condition"
+        );
+        let body = String::from_utf8_lossy(sections[0].content(&documents[0]));
+        assert!(body.starts_with("Results of Operations"));
+        assert!(body.contains("Item 5.02 is not reported"));
+    }
+
+    #[test]
+    fn test_hinted_titles_in_one_pre_block() {
+        let html = b"<html><body><pre>Results of Operations and Financial Condition\nsome text\n\
+Item 9.01 Financial Statements and Exhibits\nmore</pre></body></html>";
+        let sections = segment_document(html, "8-K", &["2.02".to_string(), "9.01".to_string()]);
+
+        let items: Vec<&str> = sections.iter().map(|s| s.item.as_str()).collect();
+        assert_eq!(items, vec!["2.02", "9.01"]);
+    }
+
+    #[test]
+    fn test_primary_document_found_by_type() {
+        let submission = format!(
+            "<SUBMISSION>\n<TYPE>10-K\n\
+             <DOCUMENT>\n<TYPE>CORRESP\n<TEXT>\nItem 7. See our letter.\n</TEXT>\n</DOCUMENT>\n\
+             <DOCUMENT>\n<TYPE>10-K\n<TEXT>\nItem 1. Business\n{0}\nItem 2. Properties\n{0}\n</TEXT>\n</DOCUMENT>\n\
+             </SUBMISSION>\n",
+            filler(40)
+        );
+        let (metadata, documents) = parse_sgml_bytes(submission.as_bytes()).unwrap();
+        let sections = segment_submission(&metadata, &documents);
+
+        let items: Vec<&str> = sections.iter().map(|s| s.item.as_str()).collect();
+        assert_eq!(items, vec!["1", "2"]);
+        assert!(sections[0]
+            .content(&documents[1])
+            .starts_with(b"Item 1. Business"));
+    }
+
+    #[test]
+    fn test_item_heading() {
+        assert_eq!(
+            item_heading("ITEM 7A. QUANTITATIVE"),
+            Some(("7A".to_string(), "QUANTITATIVE".to_string()))
+        );
+        assert_eq!(
+            item_heading("Item\u{a0}2.02 Results"),
+            Some(("2.02".to_string(), "Results".to_string()))
+        );
+        assert_eq!(item_heading("Items 1 and 2"), None);
+        assert_eq!(item_heading("Item 1Business"), None);
+        assert_eq!(
+            hinted_item("Results of Operations and Financial Condition"),
+            Some("2.02")
+        );
+    }
+}
@@ -0,0 +1,283 @@
+use serde::{Deserialize, Serialize};
+
+use crate::types::{MetadataDict, MetadataValue};
+
+/// Share class or contract of a fund series
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClassContract {
+    pub id: String,
+    pub name: String,
+    pub ticker: Option<String>,
+}
+
+/// Fund series with its classes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Series {
+    pub owner_cik: Option<String>,
+    pub series_id: String,
+    pub name: String,
+    pub classes: Vec<ClassContract>,
+}
+
+/// Classes added to a series that already exists
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NewClasses {
+    pub series_id: String,
+    pub classes: Vec<ClassContract>,
+}
+
+/// One side of a series merger
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MergerParty {
+    pub cik: Option<String>,
+    pub series: Vec<Series>,
+}
+
+/// Merger of target series into an acquiring series
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeriesMerger {
+    pub acquiring: Vec<MergerParty>,
+    pub targets: Vec<MergerParty>,
+}
+
+/// Typed `<SERIES-AND-CLASSES-CONTRACTS-DATA>` block
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeriesAndClasses {
+    pub existing: Vec<Series>,
+    pub new: Vec<Series>,
+    pub new_classes: Vec<NewClasses>,
+    pub mergers: Vec<SeriesMerger>,
+    pub deleted: Vec<Series>,
+}
+
+/// Extract typed fund series and classes from parsed header metadata
+///
+/// The generic header parsers emit a dict for a tag seen once and a list
+/// for a repeated tag; both shapes are accepted here, as are tab-style keys
+/// with spaces instead of dashes. Returns `None` when the header has no
+/// series block.
+pub fn parse_series_and_classes(header: &MetadataDict) -> Option<SeriesAndClasses> {
+    let data = dicts(header, "series-and-classes-contracts-data");
+    if data.is_empty() {
+        return None;
+    }
+
+    let mut result = SeriesAndClasses::default();
+
+    for data in data {
+        for existing in dicts(data, "existing-series-and-classes-contracts") {
+            result
+                .existing
+                .extend(series_list(existing, "series", None));
+        }
+
+        for new in dicts(data, "new-series-and-classes-contracts") {
+            let owner_cik = text(new, "owner-cik");
+            result
+                .new
+                .extend(series_list(new, "new-series", owner_cik.as_deref()));
+
+            for new_classes in dicts(new, "new-classes-contracts") {
+                result.new_classes.push(NewClasses {
+                    series_id: text(new_classes, "series-id").unwrap_or_default(),
+                    classes: classes(new_classes),
+                });
+            }
+        }
+
+        for merger_data in dicts(data, "merger-series-and-classes-contracts") {
+            for merger in dicts(merger_data, "merger") {
+                result.mergers.push(SeriesMerger {
+                    acquiring: merger_parties(merger, "acquiring-data"),
+                    targets: merger_parties(merger, "target-data"),
+                });
+            }
+        }
+
+        for deleted in dicts(data, "deleted-series-and-classes-contracts") {
+            result.deleted.extend(series_list(deleted, "series", None));
+            result
+                .deleted
+                .extend(series_list(deleted, "deleted-series", None));
+        }
+    }
+
+    Some(result)
+}
+
+fn series_list(parent: &MetadataDict, key: &str, owner_cik: Option<&str>) -> Vec<Series> {
+    dicts(parent, key)
+        .into_iter()
+        .map(|series| Series {
+            owner_cik: text(series, "owner-cik").or_else(|| owner_cik.map(str::to_string)),
+            series_id: text(series, "series-id").unwrap_or_default(),
+            name: text(series, "series-name").unwrap_or_default(),
+            classes: classes(series),
+        })
+        .collect()
+}
+
+fn classes(parent: &MetadataDict) -> Vec<ClassContract> {
+    dicts(parent, "class-contract")
+        .into_iter()
+        .map(|class| ClassContract {
+            id: text(class, "class-contract-id").unwrap_or_default(),
+            name: text(class, "class-contract-name").unwrap_or_default(),
+            ticker: text(class, "class-contract-ticker-symbol"),
+        })
+        .collect()
+}
+
+fn merger_parties(merger: &MetadataDict, key: &str) -> Vec<MergerParty> {
+    dicts(merger, key)
+        .into_iter()
+        .map(|party| MergerParty {
+            cik: text(party, "cik"),
+            series: series_list(party, "series", None),
+        })
+        .collect()
+}
+
+/// Look up a key in either dashed (`series-id`) or tab (`series id`) spelling
+fn get<'a>(dict: &'a MetadataDict, key: &str) -> Option<&'a MetadataValue> {
+    dict.get(key)
+        .or_else(|| dict.get(key.replace('-', " ").as_str()))
+}
+
+/// All dicts under a key, whether it holds one dict or a list of them
+fn dicts<'a>(dict: &'a MetadataDict, key: &str) -> Vec<&'a MetadataDict> {
+    match get(dict, key) {
+        Some(MetadataValue::Dict(dict)) => vec![dict],
+        Some(MetadataValue::List(list)) => list.iter().filter_map(MetadataValue::as_dict).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// First text value under a key
+fn text(dict: &MetadataDict, key: &str) -> Option<String> {
+    match get(dict, key)? {
+        MetadataValue::Text(text) => Some(text.clone()),
+        MetadataValue::List(list) => list.iter().find_map(MetadataValue::as_text).cloned(),
+        MetadataValue::Dict(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_sgml_bytes;
+
+    const FUND_HEADER: &[u8] = b"<SUBMISSION>
+<TYPE>485BPOS
+<SERIES-AND-CLASSES-CONTRACTS-DATA>
+<EXISTING-SERIES-AND-CLASSES-CONTRACTS>
+<SERIES>
+<OWNER-CIK>0000002110
+<SERIES-ID>S000001001
+<SERIES-NAME>Growth Fund
+<CLASS-CONTRACT>
+<CLASS-CONTRACT-ID>C000002001
+<CLASS-CONTRACT-NAME>Class A
+<CLASS-CONTRACT-TICKER-SYMBOL>GRWAX
+</CLASS-CONTRACT>
+<CLASS-CONTRACT>
+<CLASS-CONTRACT-ID>C000002002
+<CLASS-CONTRACT-NAME>Class I
+</CLASS-CONTRACT>
+</SERIES>
+<SERIES>
+<OWNER-CIK>0000002110
+<SERIES-ID>S000001002
+<SERIES-NAME>Income Fund
+<CLASS-CONTRACT>
+<CLASS-CONTRACT-ID>C000002003
+<CLASS-CONTRACT-NAME>Class A
+<CLASS-CONTRACT-TICKER-SYMBOL>INCAX
+</CLASS-CONTRACT>
+</SERIES>
+</EXISTING-SERIES-AND-CLASSES-CONTRACTS>
+<NEW-SERIES-AND-CLASSES-CONTRACTS>
+<OWNER-CIK>0000002110
+<NEW-SERIES>
+<SERIES-ID>S000001003
+<SERIES-NAME>Value Fund
+<CLASS-CONTRACT>
+<CLASS-CONTRACT-ID>C000002004
+<CLASS-CONTRACT-NAME>Class A
+</CLASS-CONTRACT>
+</NEW-SERIES>
+</NEW-SERIES-AND-CLASSES-CONTRACTS>
+<MERGER-SERIES-AND-CLASSES-CONTRACTS>
+<MERGER>
+<ACQUIRING-DATA>
+<CIK>0000002110
+<SERIES>
+<SERIES-ID>S000001001
+<CLASS-CONTRACT>
+<CLASS-CONTRACT-ID>C000002001
+</CLASS-CONTRACT>
+</SERIES>
+</ACQUIRING-DATA>
+<TARGET-DATA>
+<CIK>0000009999
+<SERIES>
+<SERIES-ID>S000009001
+</SERIES>
+</TARGET-DATA>
+</MERGER>
+</MERGER-SERIES-AND-CLASSES-CONTRACTS>
+</SERIES-AND-CLASSES-CONTRACTS-DATA>
+<DOCUMENT>
+<TYPE>485BPOS
+<TEXT>
+Prospectus
+</TEXT>
+</DOCUMENT>
+</SUBMISSION>
+";
+
+    #[test]
+    fn test_parse_series_and_classes() {
+        let (metadata, _) = parse_sgml_bytes(FUND_HEADER).unwrap();
+        let data = parse_series_and_classes(&metadata).unwrap();
+
+        assert_eq!(data.existing.len(), 2);
+        let growth = &data.existing[0];
+        assert_eq!(growth.series_id, "S000001001");
+        assert_eq!(growth.name, "Growth Fund");
+        assert_eq!(growth.owner_cik.as_deref(), Some("0000002110"));
+        assert_eq!(
+            growth.classes,
+            vec![
+                ClassContract {
+                    id: "C000002001".to_string(),
+                    name: "Class A".to_string(),
+                    ticker: Some("GRWAX".to_string()),
+                },
+                ClassContract {
+                    id: "C000002002".to_string(),
+                    name: "Class I".to_string(),
+                    ticker: None,
+                },
+            ]
+        );
+        // A single class comes out as a one-element list, not a bare dict
+        assert_eq!(data.existing[1].classes.len(), 1);
+
+        assert_eq!(data.new.len(), 1);
+        assert_eq!(data.new[0].series_id, "S000001003");
+        assert_eq!(data.new[0].owner_cik.as_deref(), Some("0000002110"));
+
+        assert_eq!(data.mergers.len(), 1);
+        let merger = &data.mergers[0];
+        assert_eq!(merger.acquiring[0].series[0].series_id, "S000001001");
+        assert_eq!(merger.targets[0].cik.as_deref(), Some("0000009999"));
+        assert!(data.deleted.is_empty());
+    }
+
+    #[test]
+    fn test_no_series_block() {
+        let (metadata, _) = parse_sgml_bytes(b"<SUBMISSION>\n<TYPE>10-K\n").unwrap();
+        assert_eq!(parse_series_and_classes(&metadata), None);
+    }
+}
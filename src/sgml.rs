@@ -1,22 +1,23 @@
-use std::collections::HashMap;
+use std::borrow::Cow;
 use std::fs;
 use std::path::Path;
 
-use crate::io::{ensure_output_dir, prepare_documents, write_documents, write_metadata};
-use crate::types::{DocumentIndex, MetadataDict, MetadataValue, ParseError, SubmissionType};
-use crate::utils::process_text_content;
-
-pub fn detect_submission_type(first_line: &str) -> Result<SubmissionType, ParseError> {
-    if first_line.starts_with("<SUBMISSION>") {
-        Ok(SubmissionType::DashedDefault)
-    } else if first_line.starts_with("-----BEGIN PRIVACY-ENHANCED MESSAGE-----") {
-        Ok(SubmissionType::TabPrivacy)
-    } else if first_line.starts_with("<SEC-DOCUMENT>") {
-        Ok(SubmissionType::TabDefault)
-    } else {
-        Err(ParseError::UnknownSubmissionType(first_line.to_string()))
-    }
-}
+use crate::attachments::add_attachment_check;
+use crate::detect::{content_start, detect_submission_format};
+use crate::io::{
+    ensure_output_dir, prepare_documents, render_documents_as_text, write_documents, write_metadata,
+};
+use crate::schema::apply_always_list;
+use crate::tag_tree::parse_tagged_header;
+use crate::text::TextOptions;
+use crate::types::{
+    ContinuationJoin, DocumentIndex, MetadataDict, MetadataValue, ParseError, ParseLimits,
+    ParseOptions, Span, SubmissionType,
+};
+use crate::utils::{
+    extract_index_headers, insert_metadata_value, map_in_order, process_text_content,
+    DocumentMetadataBuilder,
+};
 
 pub fn build_document_index(lines: &[String]) -> DocumentIndex {
     let mut index = DocumentIndex::new();
@@ -24,17 +25,18 @@ pub fn build_document_index(lines: &[String]) -> DocumentIndex {
     let mut text_start: isize = -1;
 
     for (i, line) in lines.iter().enumerate() {
-        if line == "<DOCUMENT>" {
+        let trimmed = line.trim();
+        if trimmed == "<DOCUMENT>" {
             if doc_start < 0 && index.header_end == 0 {
                 index.header_end = i;
             }
             doc_start = i as isize;
-        } else if line == "</DOCUMENT>" {
+        } else if trimmed == "</DOCUMENT>" {
             if doc_start >= 0 {
                 index.document_positions.push((doc_start as usize, i));
                 doc_start = -1;
             }
-        } else if line == "<TEXT>" {
+        } else if trimmed == "<TEXT>" {
             text_start = i as isize;
         } else if line.contains("</TEXT>") {
             let next_line = lines[i + 1..]
@@ -43,10 +45,9 @@ pub fn build_document_index(lines: &[String]) -> DocumentIndex {
                 .map(|line| line.trim());
 
             if next_line == Some("</DOCUMENT>") && text_start >= 0 {
-                if line != "</TEXT>" {
-                    let parts: Vec<&str> = line.split("</TEXT>").collect();
-                    if !parts.is_empty() {
-                        index.text_leftovers.insert(i, parts[0].to_string());
+                if trimmed != "</TEXT>" {
+                    if let Some(pos) = line.find("</TEXT>") {
+                        index.text_leftovers.insert(i, Span::new(0, pos));
                     }
                 }
                 index.text_positions.push((text_start as usize, i));
@@ -55,143 +56,62 @@ pub fn build_document_index(lines: &[String]) -> DocumentIndex {
         }
     }
 
+    // Header-only inputs have no documents, so the header runs to the end
+    if index.document_positions.is_empty() && doc_start < 0 {
+        index.header_end = lines.len();
+    }
+
     index
 }
 
-fn parse_document_metadata(lines: &[String]) -> MetadataDict {
-    let mut metadata = HashMap::new();
-    let mut current_key = None;
+fn parse_document_metadata(lines: &[String], join: ContinuationJoin) -> MetadataDict {
+    let mut builder = DocumentMetadataBuilder::new(join);
 
     for line in lines {
-        if line.starts_with('<') && line.contains('>') {
-            let parts: Vec<&str> = line.split('>').collect();
-            if parts.len() < 2 {
-                continue;
-            }
-
-            let key = parts[0][1..].to_lowercase();
-            let value = parts[1].trim().to_string();
-
-            current_key = Some(key.clone());
-            metadata.insert(key, MetadataValue::Text(value));
-        } else if let Some(key) = &current_key {
-            if let Some(MetadataValue::Text(text)) = metadata.get_mut(key) {
-                *text = format!("{} {}", text, line.trim());
-            }
+        match line.strip_prefix('<').and_then(|rest| rest.split_once('>')) {
+            Some((tag, value)) => builder.tag(tag.as_bytes(), Cow::Borrowed(value)),
+            None => builder.continuation(Cow::Borrowed(line)),
         }
     }
 
-    metadata
+    builder.finish()
 }
 
-fn parse_header_metadata(lines: &[String], submission_type: &SubmissionType) -> MetadataDict {
+fn parse_header_metadata(
+    lines: &[String],
+    submission_type: &SubmissionType,
+    limits: &ParseLimits,
+) -> Result<MetadataDict, ParseError> {
     match submission_type {
-        SubmissionType::DashedDefault => parse_dashed_default_header(lines),
+        SubmissionType::DashedDefault => parse_dashed_default_header(lines, limits),
         SubmissionType::TabPrivacy | SubmissionType::TabDefault => {
-            parse_tab_header(lines, submission_type)
+            parse_tab_header(lines, submission_type, limits)
         }
+        SubmissionType::SecHeader => parse_wrapped_header(lines, "SEC-HEADER", limits),
+        SubmissionType::ImsHeader => parse_wrapped_header(lines, "IMS-HEADER", limits),
+        // Index pages are unescaped into a `<SEC-HEADER>` input before parsing
+        SubmissionType::IndexHeaders => Ok(MetadataDict::default()),
     }
 }
 
-fn parse_dashed_default_header(lines: &[String]) -> MetadataDict {
-    let mut header_metadata = HashMap::new();
-    let mut tag_stack: Vec<String> = Vec::new();
-    let mut dict_stack: Vec<MetadataDict> = vec![header_metadata.clone()];
-
-    for (i, line) in lines.iter().enumerate() {
-        if !line.contains('>') {
-            continue;
-        }
-
-        let parts: Vec<&str> = line.split('>').collect();
-        if parts.is_empty() || !parts[0].starts_with('<') {
-            continue;
-        }
-
-        let tag = parts[0][1..].to_lowercase();
-        let text = parts.get(1).map_or("", |s| s.trim()).to_string();
-
-        // Handle closing tags
-        if tag.starts_with('/') {
-            let tag_name = tag[1..].to_string();
-
-            if tag_stack.last().map_or(false, |t| t == &tag_name) {
-                tag_stack.pop();
-                dict_stack.pop();
-            }
-            continue;
-        }
-
-        // Look ahead to check if this tag has a closing tag
-        let has_closing_tag = lines[i + 1..]
-            .iter()
-            .any(|l| l.trim().to_lowercase().starts_with(&format!("</{}>", tag)));
-
-        let current_dict = dict_stack.last_mut().unwrap();
-
-        if has_closing_tag {
-            // Create new nested dict
-            let nested_dict = HashMap::new();
-
-            // Insert into current dict
-            match current_dict.get(&tag) {
-                Some(MetadataValue::Dict(existing)) => {
-                    let mut list = vec![MetadataValue::Dict(existing.clone())];
-                    list.push(MetadataValue::Dict(nested_dict.clone()));
-                    current_dict.insert(tag.clone(), MetadataValue::List(list));
-                }
-                Some(MetadataValue::List(list)) => {
-                    let mut new_list = list.clone();
-                    new_list.push(MetadataValue::Dict(nested_dict.clone()));
-                    current_dict.insert(tag.clone(), MetadataValue::List(new_list));
-                }
-                Some(MetadataValue::Text(_)) => {
-                    let existing = current_dict.remove(&tag).unwrap();
-                    let list = vec![existing, MetadataValue::Dict(nested_dict.clone())];
-                    current_dict.insert(tag.clone(), MetadataValue::List(list));
-                }
-                None => {
-                    current_dict.insert(tag.clone(), MetadataValue::Dict(nested_dict.clone()));
-                }
-            }
-
-            // Push new context to stacks
-            tag_stack.push(tag);
-            dict_stack.push(nested_dict);
-        } else if !text.is_empty() {
-            // Add text value
-            match current_dict.get(&tag) {
-                Some(MetadataValue::Text(existing)) => {
-                    let list = vec![
-                        MetadataValue::Text(existing.clone()),
-                        MetadataValue::Text(text),
-                    ];
-                    current_dict.insert(tag.clone(), MetadataValue::List(list));
-                }
-                Some(MetadataValue::List(list)) => {
-                    let mut new_list = list.clone();
-                    new_list.push(MetadataValue::Text(text));
-                    current_dict.insert(tag.clone(), MetadataValue::List(new_list));
-                }
-                Some(MetadataValue::Dict(_)) => {
-                    let existing = current_dict.remove(&tag).unwrap();
-                    let list = vec![existing, MetadataValue::Text(text)];
-                    current_dict.insert(tag.clone(), MetadataValue::List(list));
-                }
-                None => {
-                    current_dict.insert(tag.clone(), MetadataValue::Text(text));
-                }
-            }
-        }
-    }
-
-    dict_stack[0].clone()
+pub(crate) fn parse_dashed_default_header(
+    lines: &[String],
+    limits: &ParseLimits,
+) -> Result<MetadataDict, ParseError> {
+    parse_tagged_header(lines.iter().map(|line| line.as_bytes()), limits)
 }
 
-fn parse_tab_header(lines: &[String], submission_type: &SubmissionType) -> MetadataDict {
-    let mut header_metadata = HashMap::new();
-
-    // Handle privacy-enhanced message if needed
+pub(crate) fn parse_tab_header(
+    lines: &[String],
+    submission_type: &SubmissionType,
+    limits: &ParseLimits,
+) -> Result<MetadataDict, ParseError> {
+    let mut header_metadata = MetadataDict::default();
+    let mut lines = lines;
+
+    // Handle privacy-enhanced message if needed. Its preamble lines belong
+    // to the PEM envelope, so keys are only read from the first tag line
+    // after it
     if *submission_type == SubmissionType::TabPrivacy {
         let mut privacy_msg = Vec::new();
         let mut i = 0;
@@ -214,6 +134,13 @@ fn parse_tab_header(lines: &[String], submission_type: &SubmissionType) -> Metad
                     "privacy-enhanced-message".to_string(),
                     MetadataValue::Text(privacy_msg.join("\n")),
                 );
+
+                while i < lines.len()
+                    && !(lines[i].contains('<') && lines[i].chars().any(|c| c.is_uppercase()))
+                {
+                    i += 1;
+                }
+                lines = &lines[i..];
                 break;
             }
             i += 1;
@@ -221,8 +148,9 @@ fn parse_tab_header(lines: &[String], submission_type: &SubmissionType) -> Metad
     }
 
     // Use indentation to track nesting
-    let mut indent_stack: Vec<usize> = vec![0];
-    let mut dict_stack: Vec<MetadataDict> = vec![header_metadata.clone()];
+    let mut indent_stack: Vec<usize> = Vec::new();
+    let mut tag_stack: Vec<String> = Vec::new();
+    let mut dict_stack: Vec<MetadataDict> = vec![header_metadata];
 
     for line in lines {
         if line.trim().is_empty() {
@@ -256,75 +184,98 @@ fn parse_tab_header(lines: &[String], submission_type: &SubmissionType) -> Metad
         };
 
         // Find appropriate parent based on indentation
-        while indent_stack.len() > 1 && indent_stack.last().unwrap() >= &indent {
+        while indent_stack.last().is_some_and(|&last| last >= indent) {
             indent_stack.pop();
-            dict_stack.pop();
+            close_nested_dict(&mut tag_stack, &mut dict_stack);
         }
 
-        let current_dict = dict_stack.last_mut().unwrap();
-
         if !text.is_empty() {
-            // Add text value to current dict
-            match current_dict.get(&tag) {
-                Some(MetadataValue::Text(existing)) => {
-                    let list = vec![
-                        MetadataValue::Text(existing.clone()),
-                        MetadataValue::Text(text),
-                    ];
-                    current_dict.insert(tag.clone(), MetadataValue::List(list));
-                }
-                Some(MetadataValue::List(list)) => {
-                    let mut new_list = list.clone();
-                    new_list.push(MetadataValue::Text(text));
-                    current_dict.insert(tag.clone(), MetadataValue::List(new_list));
-                }
-                Some(MetadataValue::Dict(_)) => {
-                    let existing = current_dict.remove(&tag).unwrap();
-                    let list = vec![existing, MetadataValue::Text(text)];
-                    current_dict.insert(tag.clone(), MetadataValue::List(list));
-                }
-                None => {
-                    current_dict.insert(tag.clone(), MetadataValue::Text(text));
-                }
-            }
+            let current_dict = dict_stack.last_mut().unwrap();
+            insert_metadata_value(current_dict, tag, MetadataValue::Text(text));
         } else {
-            // Create nested dict with ownership transfer
-            let nested_dict = HashMap::new();
-
-            // Insert into current dict
-            match current_dict.get(&tag) {
-                Some(MetadataValue::Dict(existing)) => {
-                    let mut list = vec![MetadataValue::Dict(existing.clone())];
-                    list.push(MetadataValue::Dict(nested_dict.clone()));
-                    current_dict.insert(tag.clone(), MetadataValue::List(list));
-                }
-                Some(MetadataValue::List(list)) => {
-                    let mut new_list = list.clone();
-                    new_list.push(MetadataValue::Dict(nested_dict.clone()));
-                    current_dict.insert(tag.clone(), MetadataValue::List(new_list));
-                }
-                Some(MetadataValue::Text(_)) => {
-                    let existing = current_dict.remove(&tag).unwrap();
-                    let list = vec![existing, MetadataValue::Dict(nested_dict.clone())];
-                    current_dict.insert(tag.clone(), MetadataValue::List(list));
-                }
-                None => {
-                    current_dict.insert(tag.clone(), MetadataValue::Dict(nested_dict.clone()));
-                }
-            }
-
-            // Push new context to stacks
+            // Nested dict is attached to its parent once indentation drops back
+            limits.check_header_depth(dict_stack.len() + 1)?;
             indent_stack.push(indent);
-            dict_stack.push(nested_dict);
+            tag_stack.push(tag);
+            dict_stack.push(MetadataDict::default());
+        }
+    }
+
+    while !tag_stack.is_empty() {
+        close_nested_dict(&mut tag_stack, &mut dict_stack);
+    }
+
+    Ok(dict_stack.remove(0))
+}
+
+/// Pop the innermost nested dict and attach it to its parent under its tag
+fn close_nested_dict(tag_stack: &mut Vec<String>, dict_stack: &mut Vec<MetadataDict>) {
+    if let (Some(tag), Some(nested_dict)) = (tag_stack.pop(), dict_stack.pop()) {
+        let parent = dict_stack.last_mut().unwrap();
+        insert_metadata_value(parent, tag, MetadataValue::Dict(nested_dict));
+    }
+}
+
+/// Parse a header wrapped in `<SEC-HEADER>` or `<IMS-HEADER>`
+///
+/// The wrapper line usually carries the header filename and date, e.g.
+/// `<SEC-HEADER>0000320193-20-000096.hdr.sgml : 20201030`, which is kept
+/// under the wrapper's own key. The wrapped lines may be either tagged
+/// (`.hdr.sgml` files) or tab-formatted (older `.txt` submissions).
+fn parse_wrapped_header(
+    lines: &[String],
+    wrapper: &str,
+    limits: &ParseLimits,
+) -> Result<MetadataDict, ParseError> {
+    let close_tag = format!("</{}>", wrapper);
+    let inner_end = lines
+        .iter()
+        .position(|line| line.trim() == close_tag)
+        .unwrap_or(lines.len());
+    let inner = lines.get(1..inner_end).unwrap_or(&[]);
+
+    let mut metadata = if is_tab_formatted(inner) {
+        parse_tab_header(inner, &SubmissionType::TabDefault, limits)?
+    } else {
+        parse_dashed_default_header(inner, limits)?
+    };
+
+    let open_tag = format!("<{}>", wrapper);
+    if let Some(value) = lines
+        .first()
+        .and_then(|line| line.trim().strip_prefix(&open_tag))
+    {
+        let value = value.trim();
+        if !value.is_empty() {
+            metadata.insert(
+                wrapper.to_lowercase(),
+                MetadataValue::Text(value.to_string()),
+            );
         }
     }
 
-    dict_stack[0].clone()
+    Ok(metadata)
+}
+
+/// Check whether header lines use the `KEY:<tab>value` layout
+fn is_tab_formatted(lines: &[String]) -> bool {
+    lines.iter().any(|line| {
+        let line = line.trim();
+        !line.starts_with('<') && line.contains(':')
+    })
 }
 
 pub fn parse_sgml_into_memory(
     content: Option<String>,
     filepath: Option<&Path>,
+) -> Result<(MetadataDict, Vec<Vec<u8>>), ParseError> {
+    parse_sgml_into_memory_with_options(content, filepath, &ParseOptions::default())
+}
+
+pub fn parse_sgml_into_memory_with_options(
+    content: Option<String>,
+    filepath: Option<&Path>,
+    options: &ParseOptions,
 ) -> Result<(MetadataDict, Vec<Vec<u8>>), ParseError> {
     if filepath.is_none() && content.is_none() {
         return Err(ParseError::NoInput);
@@ -333,58 +284,92 @@ pub fn parse_sgml_into_memory(
     // Read content if not provided
     let content = match content {
         Some(c) => c,
-        None => fs::read_to_string(filepath.unwrap()).map_err(|e| ParseError::Io(e))?,
+        None => fs::read_to_string(filepath.unwrap()).map_err(ParseError::Io)?,
     };
 
-    let lines: Vec<String> = content.lines().map(String::from).collect();
-
-    if lines.is_empty() {
+    if content.trim().is_empty() {
         return Err(ParseError::InvalidContent("Empty content".to_string()));
     }
+    options.limits.check_input_size(content.len())?;
+    options.limits.check_line_lengths(content.as_bytes())?;
+
+    // Detect submission type, skipping any BOM or preamble
+    let (submission_type, offset) = match &options.submission_type {
+        Some(forced) => (forced.clone(), content_start(content.as_bytes())),
+        None => {
+            let detection = detect_submission_format(content.as_bytes())?;
+            (detection.submission_type, detection.offset)
+        }
+    };
 
-    // Detect submission type
-    let submission_type = detect_submission_type(&lines[0])?;
+    // `-index-headers.html` pages carry an HTML-escaped `<SEC-HEADER>` block
+    if submission_type == SubmissionType::IndexHeaders {
+        let header = extract_index_headers(content.as_bytes()).ok_or_else(|| {
+            ParseError::InvalidContent("No SEC header found in HTML page".to_string())
+        })?;
+        let mut header_options = options.clone();
+        header_options.submission_type = None;
+        return parse_sgml_into_memory_with_options(
+            Some(String::from_utf8_lossy(&header).into_owned()),
+            None,
+            &header_options,
+        );
+    }
+
+    let lines: Vec<String> = content[offset..].lines().map(String::from).collect();
 
     // Get document structure index
     let doc_index = build_document_index(&lines);
+    options
+        .limits
+        .check_documents(doc_index.document_positions.len())?;
 
     // Parse header metadata
     let header_lines = &lines[..doc_index.header_end];
-    let mut metadata = parse_header_metadata(header_lines, &submission_type);
-
-    // Process documents using indexed positions
-    let mut documents = Vec::new();
-    let mut doc_metadata_list = Vec::new();
+    let mut metadata = parse_header_metadata(header_lines, &submission_type, &options.limits)?;
+    if options.always_list {
+        apply_always_list(&mut metadata);
+    }
 
-    for &(doc_start, doc_end) in &doc_index.document_positions {
+    // Process documents using indexed positions, in parallel with the
+    // `parallel` feature
+    let decoded = map_in_order(&doc_index.document_positions, |&(doc_start, doc_end)| {
         // Find corresponding text section for this document
-        let mut text_range = None;
-
-        for &(start, end) in &doc_index.text_positions {
-            if start > doc_start && end < doc_end {
-                text_range = Some((start, end));
-                break;
-            }
-        }
+        let Some((text_start, text_end)) = doc_index
+            .text_positions
+            .iter()
+            .copied()
+            .find(|&(start, end)| start > doc_start && end < doc_end)
+        else {
+            return Ok(None);
+        };
 
-        if let Some((text_start, text_end)) = text_range {
-            // Extract document metadata
-            let doc_metadata = parse_document_metadata(&lines[doc_start + 1..text_start]);
-            doc_metadata_list.push(MetadataValue::Dict(doc_metadata));
+        // Extract document metadata
+        let mut doc_metadata =
+            parse_document_metadata(&lines[doc_start + 1..text_start], options.continuation_join);
 
-            // Process text contents
-            let mut text_lines = lines[text_start + 1..text_end].to_vec();
+        // Process text contents
+        let mut text_lines = lines[text_start + 1..text_end].to_vec();
 
-            // If there's leftover content at the end
-            if let Some(leftover) = doc_index.text_leftovers.get(&text_end) {
-                text_lines.push(leftover.clone());
-            }
+        // If there's leftover content at the end
+        if let Some(leftover) = doc_index.text_leftovers.get(&text_end) {
+            text_lines.push(lines[text_end][leftover.start..leftover.end].to_string());
+        }
 
-            // Process content and add to documents list
-            let content_bytes = process_text_content(&text_lines);
-            documents.push(content_bytes);
+        // Process content and add to documents list
+        let (content_bytes, decoded) = process_text_content(&text_lines);
+        options.limits.check_decoded_size(content_bytes.len())?;
+        if options.verify_attachments && decoded {
+            add_attachment_check(&mut doc_metadata, &content_bytes);
         }
-    }
+        Ok(Some((MetadataValue::Dict(doc_metadata), content_bytes)))
+    });
+    let (doc_metadata_list, documents): (Vec<_>, Vec<_>) = decoded
+        .into_iter()
+        .collect::<Result<Vec<_>, ParseError>>()?
+        .into_iter()
+        .flatten()
+        .unzip();
 
     // Add document metadata to the metadata dictionary
     metadata.insert(
@@ -415,3 +400,246 @@ pub fn parse_sgml_submission(
 
     Ok(())
 }
+
+/// Like [`parse_sgml_submission`], but writes HTML documents as plain text
+pub fn parse_sgml_submission_as_text(
+    content: Option<String>,
+    filepath: Option<&Path>,
+    output_dir: &Path,
+    text_options: &TextOptions,
+) -> Result<(), ParseError> {
+    let (metadata, documents) = parse_sgml_into_memory(content, filepath)?;
+
+    ensure_output_dir(output_dir)?;
+    write_metadata(&metadata, output_dir)?;
+
+    let doc_infos = prepare_documents(documents, &metadata);
+    write_documents(
+        render_documents_as_text(doc_infos, text_options),
+        output_dir,
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HDR_SGML: &str = "<SEC-HEADER>0000320193-20-000096.hdr.sgml : 20201030
+<ACCEPTANCE-DATETIME>20201029180625
+<ACCESSION-NUMBER>0000320193-20-000096
+<TYPE>10-K
+<FILER>
+<COMPANY-DATA>
+<CONFORMED-NAME>Apple Inc.
+<CIK>0000320193
+</COMPANY-DATA>
+</FILER>
+</SEC-HEADER>
+";
+
+    const TAB_SEC_HEADER: &str = "<IMS-HEADER>0000950123-94-000001.txt : 19940103
+ACCESSION NUMBER:\t\t0000950123-94-000001
+CONFORMED SUBMISSION TYPE:\t10-Q
+FILER:
+\tCOMPANY DATA:
+\t\tCOMPANY CONFORMED NAME:\t\tEXAMPLE CORP
+</IMS-HEADER>
+";
+
+    fn text<'a>(dict: &'a MetadataDict, path: &[&str]) -> Option<&'a str> {
+        let (last, parents) = path.split_last()?;
+        let mut current = dict;
+        for key in parents {
+            current = current.get(*key)?.as_dict()?;
+        }
+        current.get(*last)?.as_text().map(String::as_str)
+    }
+
+    #[test]
+    fn test_hdr_sgml_header_only() {
+        let (metadata, documents) =
+            parse_sgml_into_memory(Some(HDR_SGML.to_string()), None).unwrap();
+
+        assert!(documents.is_empty());
+        assert_eq!(metadata["documents"].as_list().map(Vec::len), Some(0));
+        assert_eq!(
+            text(&metadata, &["sec-header"]),
+            Some("0000320193-20-000096.hdr.sgml : 20201030")
+        );
+        assert_eq!(text(&metadata, &["type"]), Some("10-K"));
+        assert_eq!(
+            text(&metadata, &["filer", "company-data", "conformed-name"]),
+            Some("Apple Inc.")
+        );
+    }
+
+    #[test]
+    fn test_tab_formatted_ims_header() {
+        let (metadata, _) = parse_sgml_into_memory(Some(TAB_SEC_HEADER.to_string()), None).unwrap();
+
+        assert_eq!(
+            text(&metadata, &["ims-header"]),
+            Some("0000950123-94-000001.txt : 19940103")
+        );
+        assert_eq!(
+            text(&metadata, &["conformed submission type"]),
+            Some("10-Q")
+        );
+        assert_eq!(
+            text(
+                &metadata,
+                &["filer", "company data", "company conformed name"]
+            ),
+            Some("EXAMPLE CORP")
+        );
+    }
+
+    #[test]
+    fn test_index_headers_html() {
+        let escaped = HDR_SGML
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;");
+        let page = format!("<html>\n<body><pre>\n{}</pre></body>\n</html>\n", escaped);
+
+        let (metadata, documents) = parse_sgml_into_memory(Some(page), None).unwrap();
+
+        assert!(documents.is_empty());
+        assert_eq!(
+            text(&metadata, &["accession-number"]),
+            Some("0000320193-20-000096")
+        );
+        assert_eq!(
+            text(&metadata, &["filer", "company-data", "cik"]),
+            Some("0000320193")
+        );
+    }
+
+    #[test]
+    fn test_detection_skips_preamble() {
+        let content = format!(
+            "\u{feff}HTTP/1.1 200 OK\r\nServer: edgar\r\n\r\n{}",
+            HDR_SGML
+        );
+        let (metadata, _) = parse_sgml_into_memory(Some(content), None).unwrap();
+
+        assert_eq!(text(&metadata, &["type"]), Some("10-K"));
+    }
+
+    /// Document header lines and the values both engines must produce;
+    /// `None` means the key must be absent
+    struct ContinuationFixture {
+        name: &'static str,
+        header: &'static str,
+        join: ContinuationJoin,
+        expected: &'static [(&'static str, Option<&'static str>)],
+    }
+
+    const CONTINUATION_FIXTURES: &[ContinuationFixture] = &[
+        ContinuationFixture {
+            name: "single line values",
+            header: "<TYPE>EX-99.1\n<SEQUENCE>2\n<DESCRIPTION>PRESS RELEASE\n",
+            join: ContinuationJoin::Space,
+            expected: &[
+                ("type", Some("EX-99.1")),
+                ("sequence", Some("2")),
+                ("description", Some("PRESS RELEASE")),
+            ],
+        },
+        ContinuationFixture {
+            name: "wrapped description joined with spaces",
+            header: "<TYPE>EX-10.1\n<DESCRIPTION>AMENDED AND RESTATED\n  CREDIT AGREEMENT\n",
+            join: ContinuationJoin::Space,
+            expected: &[("description", Some("AMENDED AND RESTATED CREDIT AGREEMENT"))],
+        },
+        ContinuationFixture {
+            name: "wrapped description joined with newlines",
+            header: "<TYPE>EX-10.1\n<DESCRIPTION>AMENDED AND RESTATED\n  CREDIT AGREEMENT\n",
+            join: ContinuationJoin::Newline,
+            expected: &[(
+                "description",
+                Some("AMENDED AND RESTATED\nCREDIT AGREEMENT"),
+            )],
+        },
+        ContinuationFixture {
+            name: "value starting on the next line",
+            header: "<TYPE>EX-21\n<DESCRIPTION>\nSUBSIDIARIES OF\nTHE REGISTRANT\n",
+            join: ContinuationJoin::Space,
+            expected: &[("description", Some("SUBSIDIARIES OF THE REGISTRANT"))],
+        },
+        ContinuationFixture {
+            name: "blank lines inside a value",
+            header: "<TYPE>EX-21\n<DESCRIPTION>LIST OF\n\n   \nSUBSIDIARIES\n",
+            join: ContinuationJoin::Newline,
+            expected: &[("description", Some("LIST OF\nSUBSIDIARIES"))],
+        },
+        ContinuationFixture {
+            name: "value containing an angle bracket",
+            header: "<TYPE>EX-99\n<DESCRIPTION>REVENUE > $1B\n",
+            join: ContinuationJoin::Space,
+            expected: &[("description", Some("REVENUE > $1B"))],
+        },
+        ContinuationFixture {
+            name: "empty tag without continuation is omitted",
+            header: "<TYPE>GRAPHIC\n<DESCRIPTION>\n<FILENAME>logo.jpg\n",
+            join: ContinuationJoin::Space,
+            expected: &[
+                ("type", Some("GRAPHIC")),
+                ("description", None),
+                ("filename", Some("logo.jpg")),
+            ],
+        },
+        ContinuationFixture {
+            name: "repeated empty tag keeps the earlier value",
+            header: "<TYPE>EX-99\n<DESCRIPTION>PRESS RELEASE\n<DESCRIPTION>\n",
+            join: ContinuationJoin::Space,
+            expected: &[("description", Some("PRESS RELEASE"))],
+        },
+    ];
+
+    #[test]
+    fn test_continuation_fixtures_match_across_engines() {
+        for fixture in CONTINUATION_FIXTURES {
+            let submission = format!(
+                "<SUBMISSION>\n<TYPE>8-K\n<DOCUMENT>\n{}<TEXT>\nBody\n</TEXT>\n</DOCUMENT>\n</SUBMISSION>\n",
+                fixture.header
+            );
+            let options = ParseOptions {
+                continuation_join: fixture.join,
+                ..ParseOptions::default()
+            };
+
+            let (line_metadata, _) =
+                parse_sgml_into_memory_with_options(Some(submission.clone()), None, &options)
+                    .unwrap();
+            let (byte_metadata, _) =
+                crate::byte_parser::parse_sgml_bytes_with_options(submission.as_bytes(), &options)
+                    .unwrap();
+
+            for metadata in [&line_metadata, &byte_metadata] {
+                let document = metadata["documents"].as_list().unwrap()[0]
+                    .as_dict()
+                    .unwrap();
+                for &(key, value) in fixture.expected {
+                    assert_eq!(text(document, &[key]), value, "{}: {}", fixture.name, key);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_uu_content_uses_line_length() {
+        // The length character `)` gives 9 bytes; decoding whole lines
+        // would pad the output to 45
+        let submission = "<SUBMISSION>\n<TYPE>8-K\n<DOCUMENT>\n<TYPE>GRAPHIC\n<TEXT>\nbegin 644 a.bin\n)86)C86)C86)C\n`\nend\n</TEXT>\n</DOCUMENT>\n</SUBMISSION>\n";
+
+        let (_, documents) = parse_sgml_into_memory(Some(submission.to_string()), None).unwrap();
+        assert_eq!(documents, [b"abcabcabc".to_vec()]);
+
+        let (_, byte_documents) =
+            crate::byte_parser::parse_sgml_bytes(submission.as_bytes()).unwrap();
+        assert_eq!(byte_documents, documents);
+    }
+}
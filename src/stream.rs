@@ -0,0 +1,731 @@
+use std::collections::VecDeque;
+use std::io::{ErrorKind, Read};
+
+use memchr::memmem;
+
+use crate::attachments::add_attachment_check;
+use crate::byte_parser::{
+    parse_document_metadata, parse_sgml_bytes_with_options, unwrapped_range, DOCUMENT_CLOSE,
+    DOCUMENT_OPEN, TEXT_CLOSE, TEXT_OPEN,
+};
+use crate::types::{MetadataDict, ParseError, ParseOptions};
+use crate::uu_decoder::decode_line_into;
+
+/// An event produced while parsing a submission incrementally
+#[derive(Debug, Clone)]
+pub enum Event {
+    /// The submission header, emitted before the first document
+    Header(MetadataDict),
+    /// A complete document with its metadata and processed content
+    Document {
+        metadata: MetadataDict,
+        content: Vec<u8>,
+    },
+}
+
+/// Where the parser is within the submission
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Header,
+    Metadata,
+    Text,
+    AfterText,
+    /// Text was followed by something other than `</DOCUMENT>`
+    SkipDocument,
+    BetweenDocuments,
+}
+
+/// How the text of the current document is being handled
+#[derive(Debug)]
+enum Body {
+    /// Not yet known; holds the text seen so far
+    Pending(Vec<u8>),
+    Plain,
+    Uu,
+    UuDone,
+}
+
+/// Push-based parser state machine shared by the streaming front ends
+///
+/// Input is fed in chunks of any size and events are taken out with
+/// [`StreamParser::next_event`] as their closing tags complete. A tag or
+/// UU line split across chunks is carried over until its line is whole.
+/// Only the current document is held in memory; UU-encoded text is
+/// decoded line by line. Headers and document content come out the same
+/// as with
+/// [`parse_sgml_bytes_with_options`](crate::parse_sgml_bytes_with_options).
+#[derive(Debug)]
+pub struct StreamParser {
+    options: ParseOptions,
+    state: State,
+    /// Incomplete last line of the input fed so far
+    partial: Vec<u8>,
+    /// Bytes fed so far
+    input_size: usize,
+    /// Documents started so far
+    document_count: usize,
+    header: Vec<u8>,
+    metadata: Vec<u8>,
+    body: Body,
+    content: Vec<u8>,
+    events: VecDeque<Event>,
+}
+
+impl StreamParser {
+    pub fn new() -> Self {
+        Self::with_options(ParseOptions::default())
+    }
+
+    pub fn with_options(options: ParseOptions) -> Self {
+        Self {
+            options,
+            state: State::Header,
+            partial: Vec::new(),
+            input_size: 0,
+            document_count: 0,
+            header: Vec::new(),
+            metadata: Vec::new(),
+            body: Body::Pending(Vec::new()),
+            content: Vec::new(),
+            events: VecDeque::new(),
+        }
+    }
+
+    /// Feed the next chunk of input
+    pub fn feed(&mut self, chunk: &[u8]) -> Result<(), ParseError> {
+        let limits = self.options.limits;
+        self.input_size += chunk.len();
+        limits.check_input_size(self.input_size)?;
+
+        let mut start = 0;
+        for end in memchr::memchr_iter(b'\n', chunk) {
+            let line = &chunk[start..=end];
+            start = end + 1;
+            limits.check_line_length(self.partial.len() + line.len() - 1)?;
+            if self.partial.is_empty() {
+                self.push_line(line)?;
+            } else {
+                let mut joined = std::mem::take(&mut self.partial);
+                joined.extend_from_slice(line);
+                self.push_line(&joined)?;
+            }
+        }
+        self.partial.extend_from_slice(&chunk[start..]);
+        // Fail before buffering an unbounded line
+        limits.check_line_length(self.partial.len())
+    }
+
+    /// Handle one line of input, including its newline if it has one
+    ///
+    /// Boundaries follow the byte parser: tags are found anywhere in a
+    /// line, and a line may hold several of them, so each state handles
+    /// the line up to its next tag and passes the rest on.
+    fn push_line(&mut self, mut line: &[u8]) -> Result<(), ParseError> {
+        while !line.is_empty() {
+            line = match self.state {
+                State::Header => match memmem::find(line, DOCUMENT_OPEN) {
+                    Some(pos) => {
+                        self.header.extend_from_slice(&line[..pos]);
+                        self.emit_header()?;
+                        self.start_document()?;
+                        &line[pos + DOCUMENT_OPEN.len()..]
+                    }
+                    None => {
+                        self.header.extend_from_slice(line);
+                        &[]
+                    }
+                },
+                State::BetweenDocuments => match memmem::find(line, DOCUMENT_OPEN) {
+                    Some(pos) => {
+                        self.start_document()?;
+                        &line[pos + DOCUMENT_OPEN.len()..]
+                    }
+                    None => &[],
+                },
+                State::Metadata => {
+                    let text = memmem::find(line, TEXT_OPEN);
+                    match memmem::find(line, DOCUMENT_CLOSE) {
+                        // A document without text is dropped
+                        Some(pos) if text.is_none_or(|text| pos < text) => {
+                            self.state = State::BetweenDocuments;
+                            &line[pos + DOCUMENT_CLOSE.len()..]
+                        }
+                        _ => match text {
+                            Some(pos) => {
+                                self.metadata.extend_from_slice(&line[..pos]);
+                                self.state = State::Text;
+                                &line[pos + TEXT_OPEN.len()..]
+                            }
+                            None => {
+                                self.metadata.extend_from_slice(line);
+                                &[]
+                            }
+                        },
+                    }
+                }
+                State::Text => match memmem::find(line, TEXT_CLOSE) {
+                    Some(pos) => {
+                        self.text_line(&line[..pos]);
+                        self.state = State::AfterText;
+                        &line[pos + TEXT_CLOSE.len()..]
+                    }
+                    None => {
+                        self.text_line(line);
+                        &[]
+                    }
+                },
+                State::AfterText => self.after_text(line),
+                State::SkipDocument => match memmem::find(line, DOCUMENT_CLOSE) {
+                    Some(pos) => {
+                        self.state = State::BetweenDocuments;
+                        &line[pos + DOCUMENT_CLOSE.len()..]
+                    }
+                    None => &[],
+                },
+            };
+        }
+        self.options.limits.check_decoded_size(self.content_size())
+    }
+
+    /// Signal the end of input
+    ///
+    /// A submission with no documents still produces its header. A
+    /// document cut off before `</DOCUMENT>` is dropped.
+    pub fn finish(&mut self) -> Result<(), ParseError> {
+        if !self.partial.is_empty() {
+            let last = std::mem::take(&mut self.partial);
+            self.push_line(&last)?;
+        }
+        if self.state == State::Header {
+            self.emit_header()?;
+        }
+        self.state = State::BetweenDocuments;
+        Ok(())
+    }
+
+    /// Take the next completed event, if any
+    pub fn next_event(&mut self) -> Option<Event> {
+        self.events.pop_front()
+    }
+
+    fn emit_header(&mut self) -> Result<(), ParseError> {
+        let header = std::mem::take(&mut self.header);
+        let metadata = if header.trim_ascii().is_empty() {
+            MetadataDict::default()
+        } else {
+            let (mut metadata, _) = parse_sgml_bytes_with_options(&header, &self.options)?;
+            metadata.remove("documents");
+            metadata
+        };
+        self.events.push_back(Event::Header(metadata));
+        Ok(())
+    }
+
+    fn start_document(&mut self) -> Result<(), ParseError> {
+        self.document_count += 1;
+        self.options.limits.check_documents(self.document_count)?;
+        self.state = State::Metadata;
+        self.metadata.clear();
+        self.body = Body::Pending(Vec::new());
+        self.content = Vec::new();
+        Ok(())
+    }
+
+    /// Size of the current document's content so far
+    fn content_size(&self) -> usize {
+        match &self.body {
+            Body::Pending(pending) => pending.len(),
+            _ => self.content.len(),
+        }
+    }
+
+    /// Look for the `</DOCUMENT>` ending the current document, returning
+    /// the rest of the line
+    ///
+    /// Anything but whitespace before it drops the document.
+    fn after_text<'a>(&mut self, line: &'a [u8]) -> &'a [u8] {
+        let rest = &line[line
+            .iter()
+            .take_while(|&&b| matches!(b, b' ' | b'\n' | b'\r'))
+            .count()..];
+        if let Some(rest) = rest.strip_prefix(DOCUMENT_CLOSE) {
+            self.finish_document();
+            return rest;
+        }
+        if !rest.is_empty() {
+            self.state = State::SkipDocument;
+        }
+        rest
+    }
+
+    /// Handle text, deciding between plain and UU content once the first
+    /// line past any `<PDF>`-style wrapper is complete
+    fn text_line(&mut self, line: &[u8]) {
+        match &mut self.body {
+            Body::Pending(pending) => {
+                // Skip leading blank lines, keeping the indentation of the
+                // first line
+                if pending.is_empty() && line.trim_ascii().is_empty() {
+                    return;
+                }
+                pending.extend_from_slice(line);
+                self.resolve_body(false);
+            }
+            Body::Plain => self.content.extend_from_slice(line),
+            Body::Uu => self.decode_lines(line),
+            Body::UuDone => {}
+        }
+    }
+
+    fn resolve_body(&mut self, at_end: bool) {
+        let Body::Pending(pending) = &self.body else {
+            return;
+        };
+        let trimmed = pending.trim_ascii_start();
+        let unwrapped = [&b"<PDF>"[..], b"<XBRL>", b"<XML>"]
+            .iter()
+            .find_map(|tag| trimmed.strip_prefix(*tag))
+            .map_or(trimmed, <[u8]>::trim_ascii_start);
+        let first_line_end = memchr::memchr(b'\n', unwrapped);
+        if !at_end && first_line_end.is_none() {
+            return;
+        }
+        let is_uu = unwrapped.starts_with(b"begin");
+        // Start of the UU lines after `begin`; without a complete `begin`
+        // line there is nothing to decode
+        let body_start = first_line_end.map(|end| pending.len() - unwrapped.len() + end + 1);
+
+        let Body::Pending(pending) = std::mem::replace(&mut self.body, Body::Plain) else {
+            unreachable!();
+        };
+        if is_uu {
+            self.body = Body::Uu;
+            if let Some(start) = body_start {
+                self.decode_lines(&pending[start..]);
+            }
+        } else {
+            self.content = pending;
+        }
+    }
+
+    fn decode_lines(&mut self, text: &[u8]) {
+        for line in text.split(|&b| b == b'\n') {
+            let line = line.trim_ascii();
+            if line == b"end" {
+                self.body = Body::UuDone;
+                return;
+            }
+            decode_line_into(line, &mut self.content);
+        }
+    }
+
+    fn finish_document(&mut self) {
+        self.resolve_body(true);
+        self.state = State::BetweenDocuments;
+
+        let mut metadata = parse_document_metadata(
+            &self.metadata,
+            0,
+            self.metadata.len(),
+            self.options.continuation_join,
+        );
+        let mut content = std::mem::take(&mut self.content);
+        let decoded = matches!(self.body, Body::Uu | Body::UuDone);
+        if !decoded {
+            let range = unwrapped_range(&content);
+            content.truncate(range.end);
+            content.drain(..range.start);
+        }
+        if self.options.verify_attachments && decoded {
+            add_attachment_check(&mut metadata, &content);
+        }
+        self.events.push_back(Event::Document { metadata, content });
+    }
+}
+
+impl Default for StreamParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Size of the reads made by the streaming front ends
+pub(crate) const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Size of the reads made when only the header is wanted
+const HEADER_CHUNK_SIZE: usize = 8 * 1024;
+
+/// Iterator over the events of a submission read from a [`Read`]
+pub struct Events<R> {
+    reader: R,
+    parser: StreamParser,
+    buffer: Vec<u8>,
+    done: bool,
+}
+
+impl<R: Read> Iterator for Events<R> {
+    type Item = Result<Event, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(event) = self.parser.next_event() {
+                return Some(Ok(event));
+            }
+            if self.done {
+                return None;
+            }
+
+            let result = match self.reader.read(&mut self.buffer) {
+                Ok(0) => {
+                    self.done = true;
+                    self.parser.finish()
+                }
+                Ok(n) => self.parser.feed(&self.buffer[..n]),
+                Err(err) if err.kind() == ErrorKind::Interrupted => Ok(()),
+                Err(err) => Err(ParseError::Io(err)),
+            };
+            if let Err(err) = result {
+                self.done = true;
+                return Some(Err(err));
+            }
+        }
+    }
+}
+
+/// Parse a submission from a reader, yielding the header and then each
+/// document as it completes
+pub fn parse_events<R: Read>(reader: R) -> Events<R> {
+    parse_events_with_options(reader, ParseOptions::default())
+}
+
+/// Parse a submission from a reader with explicit options
+pub fn parse_events_with_options<R: Read>(reader: R, options: ParseOptions) -> Events<R> {
+    Events {
+        reader,
+        parser: StreamParser::with_options(options),
+        buffer: vec![0; CHUNK_SIZE],
+        done: false,
+    }
+}
+
+/// Parse only the header of a submission
+///
+/// Reading stops once the first `<DOCUMENT>` line is seen, so the cost
+/// does not depend on the size of the documents. The reader can be a
+/// decompressor. The result is the header metadata of the full parse,
+/// without the `documents` entry.
+pub fn parse_header_only<R: Read>(reader: R) -> Result<MetadataDict, ParseError> {
+    parse_header_only_with_options(reader, ParseOptions::default())
+}
+
+/// Parse only the header of a submission with explicit options
+pub fn parse_header_only_with_options<R: Read>(
+    mut reader: R,
+    options: ParseOptions,
+) -> Result<MetadataDict, ParseError> {
+    // Headers are a few kilobytes, so small reads avoid overshooting much
+    let mut buffer = [0; HEADER_CHUNK_SIZE];
+    let mut parser = StreamParser::with_options(options);
+    loop {
+        match reader.read(&mut buffer) {
+            Ok(0) => parser.finish()?,
+            Ok(n) => parser.feed(&buffer[..n])?,
+            Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+            Err(err) => return Err(ParseError::Io(err)),
+        }
+        if let Some(Event::Header(header)) = parser.next_event() {
+            return Ok(header);
+        }
+    }
+}
+
+/// Assemble events back into the shape returned by the whole-buffer parsers
+#[cfg(test)]
+pub(crate) fn collect_events(
+    events: impl IntoIterator<Item = Event>,
+) -> (MetadataDict, Vec<Vec<u8>>) {
+    use crate::types::MetadataValue;
+
+    let mut metadata = MetadataDict::default();
+    let mut doc_metadata = Vec::new();
+    let mut documents = Vec::new();
+    for event in events {
+        match event {
+            Event::Header(header) => metadata = header,
+            Event::Document {
+                metadata: doc,
+                content,
+            } => {
+                doc_metadata.push(MetadataValue::Dict(doc));
+                documents.push(content);
+            }
+        }
+    }
+    metadata.insert("documents".to_string(), MetadataValue::List(doc_metadata));
+    (metadata, documents)
+}
+
+#[cfg(test)]
+pub(crate) const SAMPLE: &[u8] = b"<SUBMISSION>
+<ACCESSION-NUMBER>0000950170-24-000001
+<TYPE>10-K
+<FILER>
+<COMPANY-DATA>
+<CONFORMED-NAME>Example Corp
+</COMPANY-DATA>
+</FILER>
+<DOCUMENT>
+<TYPE>10-K
+<SEQUENCE>1
+<FILENAME>form10k.htm
+<DESCRIPTION>ANNUAL REPORT
+<TEXT>
+<html><body>Annual report</body></html>
+</TEXT>
+</DOCUMENT>
+<DOCUMENT>
+<TYPE>GRAPHIC
+<SEQUENCE>2
+<FILENAME>chart.pdf
+<TEXT>
+<PDF>
+begin 644 chart.pdf
+)<&1F(&)Y=&5S
+`
+end
+</PDF>
+</TEXT>
+</DOCUMENT>
+</SUBMISSION>
+";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::byte_parser::parse_sgml_bytes;
+    use crate::types::ParseLimits;
+
+    fn json(metadata: &MetadataDict) -> serde_json::Value {
+        serde_json::to_value(metadata).unwrap()
+    }
+
+    #[test]
+    fn test_events_match_byte_parser() {
+        let events = parse_events(SAMPLE).collect::<Result<Vec<_>, _>>().unwrap();
+        assert!(matches!(events[0], Event::Header(_)));
+        assert_eq!(events.len(), 3);
+
+        let (metadata, documents) = collect_events(events);
+        let (expected_metadata, expected_documents) = parse_sgml_bytes(SAMPLE).unwrap();
+        assert_eq!(json(&metadata), json(&expected_metadata));
+        assert_eq!(documents, expected_documents);
+        assert_eq!(documents[1], b"pdf bytes");
+    }
+
+    #[test]
+    fn test_header_only_submission() {
+        let data = b"<SEC-HEADER>0000320193-20-000096.hdr.sgml : 20201030
+<ACCESSION-NUMBER>0000320193-20-000096
+</SEC-HEADER>
+";
+        let events = parse_events(&data[..])
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(events.len(), 1);
+        let Event::Header(header) = &events[0] else {
+            panic!("expected a header");
+        };
+        let (expected, _) = parse_sgml_bytes(data).unwrap();
+        let mut expected = json(&expected);
+        expected.as_object_mut().unwrap().remove("documents");
+        assert_eq!(json(header), expected);
+    }
+
+    fn feed_in_chunks(data: &[u8], size: usize) -> (MetadataDict, Vec<Vec<u8>>) {
+        try_feed_in_chunks(data, size).unwrap()
+    }
+
+    fn try_feed_in_chunks(
+        data: &[u8],
+        size: usize,
+    ) -> Result<(MetadataDict, Vec<Vec<u8>>), ParseError> {
+        let mut parser = StreamParser::new();
+        let mut events = Vec::new();
+        for chunk in data.chunks(size) {
+            parser.feed(chunk)?;
+            events.extend(std::iter::from_fn(|| parser.next_event()));
+        }
+        parser.finish()?;
+        events.extend(std::iter::from_fn(|| parser.next_event()));
+        Ok(collect_events(events))
+    }
+
+    #[test]
+    fn test_any_chunk_size() {
+        let (expected_metadata, expected_documents) = parse_sgml_bytes(SAMPLE).unwrap();
+        for size in 1..=64 {
+            let (metadata, documents) = feed_in_chunks(SAMPLE, size);
+            assert_eq!(
+                json(&metadata),
+                json(&expected_metadata),
+                "chunk size {size}"
+            );
+            assert_eq!(documents, expected_documents, "chunk size {size}");
+        }
+    }
+
+    #[test]
+    fn test_uu_lines_split_across_chunks() {
+        let payload: Vec<u8> = (0..10_000u32).map(|i| (i * 7 + i / 13) as u8).collect();
+        let data = format!(
+            "<SUBMISSION>\n<TYPE>8-K\n<DOCUMENT>\n<TYPE>GRAPHIC\n<FILENAME>g.jpg\n<TEXT>\n{}\n</TEXT>\n</DOCUMENT>\n</SUBMISSION>",
+            uuencode::uuencode("g.jpg", &payload)
+        );
+        for size in [1, 7, 60, 61, 62, 4096] {
+            let (_, documents) = feed_in_chunks(data.as_bytes(), size);
+            assert_eq!(
+                documents,
+                std::slice::from_ref(&payload),
+                "chunk size {size}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_header_only_stops_at_first_document() {
+        struct Unreadable;
+        impl Read for Unreadable {
+            fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+                panic!("read past the header");
+            }
+        }
+
+        let header_end = memmem::find(SAMPLE, DOCUMENT_OPEN).unwrap();
+        let prefix = &SAMPLE[..header_end + DOCUMENT_OPEN.len() + 1];
+        let header = parse_header_only(prefix.chain(Unreadable)).unwrap();
+
+        let (mut expected, _) = parse_sgml_bytes(SAMPLE).unwrap();
+        expected.remove("documents");
+        assert_eq!(json(&header), json(&expected));
+    }
+
+    #[test]
+    fn test_header_only_from_gzip() {
+        use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+        use std::io::Write;
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(SAMPLE).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let header = parse_header_only(GzDecoder::new(&compressed[..])).unwrap();
+        assert_eq!(json(&header), json(&parse_header_only(SAMPLE).unwrap()));
+        assert!(header.contains_key("accession-number"));
+    }
+
+    #[test]
+    fn test_limits_while_streaming() {
+        let limited = |limits| {
+            StreamParser::with_options(ParseOptions {
+                limits,
+                ..Default::default()
+            })
+        };
+
+        // A line that never ends fails once it outgrows the limit
+        let mut parser = limited(ParseLimits {
+            max_line_length: Some(100),
+            ..Default::default()
+        });
+        parser.feed(&[b'x'; 64]).unwrap();
+        assert!(matches!(
+            parser.feed(&[b'x'; 64]),
+            Err(ParseError::LineTooLong { length: 128, .. })
+        ));
+
+        let mut parser = limited(ParseLimits {
+            max_documents: Some(1),
+            ..Default::default()
+        });
+        assert!(matches!(
+            parser.feed(SAMPLE),
+            Err(ParseError::TooManyDocuments { limit: 1 })
+        ));
+
+        let mut parser = limited(ParseLimits {
+            max_decoded_size: Some(8),
+            ..Default::default()
+        });
+        let result = SAMPLE.chunks(5).try_for_each(|chunk| parser.feed(chunk));
+        assert!(matches!(
+            result,
+            Err(ParseError::DocumentTooLarge { limit: 8, .. })
+        ));
+    }
+
+    #[test]
+    fn test_boundaries_match_byte_parser() {
+        // `<DOCUMENT>` after other text on its line still opens a document,
+        // and text followed by anything but `</DOCUMENT>` drops it
+        let data =
+            b"<SUBMISSION>\n<TYPE>8-K\n  <DOCUMENT><TYPE>8-K\n<TEXT>\nkept\n</TEXT>\n</DOCUMENT>\n\
+<DOCUMENT>\n<TYPE>EX-99\n<TEXT>\ndropped\n</TEXT>\njunk\n</DOCUMENT>\n</SUBMISSION>\n";
+        let (expected_metadata, expected_documents) = parse_sgml_bytes(data).unwrap();
+        assert_eq!(expected_documents, vec![b"kept\n".to_vec()]);
+        for size in [1, 7, data.len()] {
+            let (metadata, documents) = feed_in_chunks(data, size);
+            assert_eq!(
+                json(&metadata),
+                json(&expected_metadata),
+                "chunk size {size}"
+            );
+            assert_eq!(documents, expected_documents, "chunk size {size}");
+        }
+    }
+
+    #[test]
+    fn test_unterminated_document_is_dropped() {
+        let data = b"<SUBMISSION>\n<TYPE>8-K\n<DOCUMENT>\n<TYPE>8-K\n<TEXT>\ncut off";
+        let mut parser = StreamParser::new();
+        parser.feed(data).unwrap();
+        parser.finish().unwrap();
+        assert!(matches!(parser.next_event(), Some(Event::Header(_))));
+        assert!(parser.next_event().is_none());
+    }
+
+    /// The fuzz corpus and golden submissions come out of the stream as
+    /// they do out of the byte parser, however the input is chunked
+    #[test]
+    fn test_stream_matches_byte_parser_on_corpus() {
+        let tests = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests");
+        let mut inputs = Vec::new();
+        for target in std::fs::read_dir(tests.join("fuzz_corpus")).unwrap() {
+            for input in std::fs::read_dir(target.unwrap().path()).unwrap() {
+                inputs.push(input.unwrap().path());
+            }
+        }
+        for fixture in std::fs::read_dir(tests.join("golden")).unwrap() {
+            inputs.push(fixture.unwrap().path().join("submission.txt"));
+        }
+        inputs.sort();
+
+        for path in &inputs {
+            let data = std::fs::read(path).unwrap();
+            let expected = parse_sgml_bytes(&data);
+            for size in [1, 3, 64, data.len().max(1)] {
+                match (&expected, try_feed_in_chunks(&data, size)) {
+                    (Ok((expected_metadata, expected_documents)), Ok((metadata, documents))) => {
+                        let context = format!("{} in chunks of {size}", path.display());
+                        assert_eq!(json(&metadata), json(expected_metadata), "{context}");
+                        assert_eq!(&documents, expected_documents, "{context}");
+                    }
+                    (Err(_), Err(_)) => {}
+                    (expected, actual) => panic!(
+                        "{} in chunks of {size}: expected {expected:?}, got {actual:?}",
+                        path.display()
+                    ),
+                }
+            }
+        }
+    }
+}
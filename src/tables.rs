@@ -0,0 +1,720 @@
+use serde::{Deserialize, Serialize};
+
+use crate::html::{is_html, Token, Tokenizer};
+use crate::types::ParseError;
+use crate::utils::{decode_html_text, decode_text};
+
+/// Largest `colspan`/`rowspan` honored
+const MAX_SPAN: usize = 256;
+
+/// Columns kept per HTML table row; cells past it are dropped
+const MAX_COLUMNS: usize = 1024;
+
+/// Cells kept per table once rows are padded to the same width; rows past
+/// it are dropped. With [`MAX_COLUMNS`] this bounds the grid of hostile
+/// input, which spans and padding would otherwise grow far past its size.
+const MAX_CELLS: usize = 1 << 20;
+
+/// Cell text made up only of a currency symbol or opening parenthesis,
+/// shown in its own cell left of the number
+const PREFIXES: &[&str] = &["$", "US$", "€", "£", "¥", "(", "($", "$("];
+
+/// Cell text shown in its own cell right of the number
+const SUFFIXES: &[&str] = &[")", "%", "%)", ")%"];
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Cell {
+    pub text: String,
+    /// Numeric reading of `text`, see [`parse_cell_number`]
+    pub value: Option<f64>,
+}
+
+/// Rectangular grid of cells
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Table {
+    pub rows: Vec<Vec<Cell>>,
+}
+
+impl Table {
+    /// Build a table from rows of cell text
+    ///
+    /// Currency symbols, opening parentheses and `%` or `)` that sit in
+    /// their own cells are merged into the neighbouring number, rows are
+    /// padded to the same width, and rows and columns that are empty
+    /// throughout are dropped. Rows that would take the grid past a million
+    /// cells are dropped too.
+    pub fn from_rows(mut rows: Vec<Vec<String>>) -> Self {
+        for row in &mut rows {
+            merge_affixes(row);
+        }
+
+        rows.retain(|row| row.iter().any(|text| !text.is_empty()));
+        let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+        rows.truncate(MAX_CELLS / width.max(1));
+        let used: Vec<bool> = (0..width)
+            .map(|col| {
+                rows.iter()
+                    .any(|row| row.get(col).is_some_and(|t| !t.is_empty()))
+            })
+            .collect();
+
+        let rows = rows
+            .into_iter()
+            .map(|mut row| {
+                row.resize(width, String::new());
+                row.into_iter()
+                    .zip(&used)
+                    .filter(|(_, used)| **used)
+                    .map(|(text, _)| Cell {
+                        value: parse_cell_number(&text),
+                        text,
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Self { rows }
+    }
+
+    pub fn width(&self) -> usize {
+        self.rows.first().map_or(0, Vec::len)
+    }
+
+    /// Render the cell text as CSV
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::new();
+        for row in &self.rows {
+            let line: Vec<String> = row.iter().map(|cell| csv_field(&cell.text)).collect();
+            csv.push_str(&line.join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+
+    /// Serialize the table, including numeric values, as JSON
+    pub fn to_json(&self) -> Result<String, ParseError> {
+        serde_json::to_string(self).map_err(ParseError::Json)
+    }
+}
+
+/// Extract the tables of a document, HTML or plain text
+pub fn extract_tables(content: &[u8]) -> Vec<Table> {
+    if is_html(content) {
+        extract_html_tables(content)
+    } else {
+        extract_text_tables(&decode_text(content))
+    }
+}
+
+/// Parse a financial statement number
+///
+/// Accepts thousands separators, currency symbols, a trailing `%` (the
+/// value stays in percent) and negatives written as `(1,234)` or with a
+/// minus sign or dash. Text that is not a number, including a lone dash
+/// for nil, gives `None`.
+pub fn parse_cell_number(text: &str) -> Option<f64> {
+    let text = text.trim();
+    let text = text
+        .strip_prefix("US")
+        .filter(|rest| rest.starts_with('$'))
+        .unwrap_or(text)
+        .trim_start_matches(['$', '€', '£', '¥', ' ', '\u{a0}'])
+        .trim_end_matches(['%', ' ', '\u{a0}']);
+
+    let mut negative = false;
+    let text = match text.strip_prefix('(').and_then(|t| t.strip_suffix(')')) {
+        Some(inner) => {
+            negative = true;
+            inner
+        }
+        None => text,
+    };
+
+    let mut digits: String = text
+        .chars()
+        .filter(|c| !matches!(c, ',' | '$' | ' ' | '\u{a0}'))
+        .collect();
+    if let Some(rest) = digits.strip_prefix(['-', '\u{2212}', '\u{2013}', '\u{2014}']) {
+        negative = !negative;
+        digits = rest.to_string();
+    }
+
+    if !digits.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+        return None;
+    }
+    let value: f64 = digits.parse().ok()?;
+    Some(if negative { -value } else { value })
+}
+
+/// Extract `<table>` elements as grids
+///
+/// `colspan` and `rowspan` cells are repeated into every grid position they
+/// cover. A nested table is returned on its own and left out of the
+/// enclosing cell. Tables come back in the order they open.
+pub fn extract_html_tables(content: &[u8]) -> Vec<Table> {
+    let mut open: Vec<(usize, HtmlTableBuilder)> = Vec::new();
+    let mut tables: Vec<(usize, Table)> = Vec::new();
+    let mut opened = 0;
+    let mut in_raw_text = false;
+
+    for token in Tokenizer::new(content) {
+        match token {
+            Token::StartTag(tag) if tag.is("script") || tag.is("style") => {
+                in_raw_text = !tag.self_closing;
+            }
+            Token::EndTag(name)
+                if name.eq_ignore_ascii_case(b"script") || name.eq_ignore_ascii_case(b"style") =>
+            {
+                in_raw_text = false;
+            }
+            Token::StartTag(tag) if tag.is("table") => {
+                open.push((opened, HtmlTableBuilder::default()));
+                opened += 1;
+            }
+            Token::EndTag(name) if name.eq_ignore_ascii_case(b"table") => {
+                if let Some((order, builder)) = open.pop() {
+                    let table = builder.finish();
+                    if !table.rows.is_empty() {
+                        tables.push((order, table));
+                    }
+                }
+            }
+            _ => {
+                let Some((_, builder)) = open.last_mut() else {
+                    continue;
+                };
+                match token {
+                    Token::StartTag(tag) if tag.is("tr") => builder.start_row(),
+                    Token::StartTag(tag) if tag.is("td") || tag.is("th") => {
+                        let span = |name| {
+                            tag.attr(name)
+                                .and_then(|value| value.trim().parse::<usize>().ok())
+                                .map_or(1, |span| span.clamp(1, MAX_SPAN))
+                        };
+                        builder.start_cell(span("colspan"), span("rowspan"));
+                    }
+                    Token::StartTag(tag)
+                        if tag.is("br") || tag.is("p") || tag.is("div") || tag.is("li") =>
+                    {
+                        builder.space();
+                    }
+                    Token::EndTag(name)
+                        if name.eq_ignore_ascii_case(b"td") || name.eq_ignore_ascii_case(b"th") =>
+                    {
+                        builder.close_cell();
+                    }
+                    Token::EndTag(name) if name.eq_ignore_ascii_case(b"tr") => {
+                        builder.close_row();
+                    }
+                    Token::Text(text) if !in_raw_text => builder.text(text),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    // Tables left open at the end of input still count
+    while let Some((order, builder)) = open.pop() {
+        let table = builder.finish();
+        if !table.rows.is_empty() {
+            tables.push((order, table));
+        }
+    }
+
+    tables.sort_by_key(|(order, _)| *order);
+    tables.into_iter().map(|(_, table)| table).collect()
+}
+
+#[derive(Debug, Default)]
+struct HtmlCell {
+    text: String,
+    colspan: usize,
+    rowspan: usize,
+    pending_space: bool,
+}
+
+#[derive(Debug, Default)]
+struct HtmlTableBuilder {
+    rows: Vec<Vec<HtmlCell>>,
+    row_open: bool,
+    cell: Option<HtmlCell>,
+}
+
+impl HtmlTableBuilder {
+    fn start_row(&mut self) {
+        self.close_row();
+        self.rows.push(Vec::new());
+        self.row_open = true;
+    }
+
+    fn close_row(&mut self) {
+        self.close_cell();
+        self.row_open = false;
+    }
+
+    fn start_cell(&mut self, colspan: usize, rowspan: usize) {
+        self.close_cell();
+        if !self.row_open {
+            self.start_row();
+        }
+        self.cell = Some(HtmlCell {
+            colspan,
+            rowspan,
+            ..HtmlCell::default()
+        });
+    }
+
+    fn close_cell(&mut self) {
+        if let Some(cell) = self.cell.take() {
+            if let Some(row) = self.rows.last_mut() {
+                row.push(cell);
+            }
+        }
+    }
+
+    fn space(&mut self) {
+        if let Some(cell) = &mut self.cell {
+            cell.pending_space = true;
+        }
+    }
+
+    fn text(&mut self, text: &[u8]) {
+        let Some(cell) = &mut self.cell else {
+            return;
+        };
+        for c in decode_html_text(text).chars() {
+            if c.is_whitespace() {
+                cell.pending_space = true;
+            } else if c != '\u{ad}' {
+                if cell.pending_space && !cell.text.is_empty() {
+                    cell.text.push(' ');
+                }
+                cell.pending_space = false;
+                cell.text.push(c);
+            }
+        }
+    }
+
+    /// Lay the cells out on a grid, expanding spans
+    fn finish(mut self) -> Table {
+        self.close_row();
+
+        let mut grid: Vec<Vec<String>> = Vec::with_capacity(self.rows.len());
+        // Text and remaining rows of cells spanning down, by column
+        let mut carried: Vec<Option<(String, usize)>> = Vec::new();
+
+        for cells in self.rows {
+            let mut row: Vec<String> = Vec::new();
+            let mut col = 0;
+
+            for cell in cells {
+                fill_carried(&mut row, &mut col, &mut carried);
+                for offset in 0..cell.colspan {
+                    let target = col + offset;
+                    if target >= MAX_COLUMNS {
+                        break;
+                    }
+                    row.resize(target + 1, String::new());
+                    row[target] = cell.text.clone();
+                    if cell.rowspan > 1 {
+                        if carried.len() <= target {
+                            carried.resize(target + 1, None);
+                        }
+                        carried[target] = Some((cell.text.clone(), cell.rowspan - 1));
+                    }
+                }
+                col += cell.colspan;
+            }
+
+            // Cells spanning down past the last cell of this row
+            while col < carried.len() {
+                fill_carried(&mut row, &mut col, &mut carried);
+                col += 1;
+            }
+
+            grid.push(row);
+        }
+
+        Table::from_rows(grid)
+    }
+}
+
+/// Place cells spanning down from earlier rows, starting at `col`
+fn fill_carried(row: &mut Vec<String>, col: &mut usize, carried: &mut [Option<(String, usize)>]) {
+    while let Some(Some((text, remaining))) = carried.get_mut(*col) {
+        row.resize(*col + 1, String::new());
+        row[*col] = text.clone();
+        *remaining -= 1;
+        if *remaining == 0 {
+            carried[*col] = None;
+        }
+        *col += 1;
+    }
+}
+
+/// Extract `<TABLE>` blocks of plain text documents
+///
+/// Columns are inferred from the body rows (after the `<S>`/`<C>` marker
+/// line when there is one): a run of two or more positions that is blank
+/// in every body row separates columns. Each line is split into phrases at
+/// runs of two or more spaces and each phrase goes to the first column it
+/// overlaps, so headings line up with the numbers below them. Dot
+/// leaders are trimmed, and dashed rules, `<CAPTION>` and `<PAGE>` lines
+/// are skipped.
+pub fn extract_text_tables(content: &str) -> Vec<Table> {
+    let mut tables = Vec::new();
+    let mut lines = content.lines();
+
+    while lines.by_ref().any(|line| starts_with_tag(line, "<TABLE>")) {
+        let block: Vec<&str> = lines
+            .by_ref()
+            .take_while(|line| !starts_with_tag(line, "</TABLE>"))
+            .collect();
+        let table = text_table(&block);
+        if !table.rows.is_empty() {
+            tables.push(table);
+        }
+    }
+
+    tables
+}
+
+fn starts_with_tag(line: &str, tag: &str) -> bool {
+    line.trim_start()
+        .get(..tag.len())
+        .is_some_and(|start| start.eq_ignore_ascii_case(tag))
+}
+
+fn text_table(block: &[&str]) -> Table {
+    let is_marker = |line: &str| starts_with_tag(line, "<S>") || starts_with_tag(line, "<C>");
+    let is_skipped = |line: &str| {
+        let trimmed = line.trim();
+        trimmed.is_empty()
+            || is_marker(line)
+            || starts_with_tag(line, "<CAPTION>")
+            || starts_with_tag(line, "</CAPTION>")
+            || starts_with_tag(line, "<PAGE>")
+            || trimmed.chars().all(|c| matches!(c, '-' | '=' | '_' | ' '))
+    };
+
+    let rows: Vec<Vec<char>> = block
+        .iter()
+        .filter(|line| !is_skipped(line))
+        .map(|line| line.replace('\t', "        ").chars().collect())
+        .collect();
+
+    let body_start = block
+        .iter()
+        .position(|line| is_marker(line))
+        .map(|marker| {
+            block[..marker]
+                .iter()
+                .filter(|line| !is_skipped(line))
+                .count()
+        })
+        .unwrap_or(0);
+    let body = if body_start < rows.len() {
+        &rows[body_start..]
+    } else {
+        &rows[..]
+    };
+
+    let columns = infer_columns(body);
+    let grid = rows
+        .iter()
+        .map(|row| {
+            let mut cells = vec![String::new(); columns.len()];
+            for (start, end) in phrases(row) {
+                let text: String = row[start..end].iter().collect();
+                let text = trim_leaders(&text);
+                if text.is_empty() {
+                    continue;
+                }
+                let col = best_column(&columns, start, end);
+                if !cells[col].is_empty() {
+                    cells[col].push(' ');
+                }
+                cells[col].push_str(text);
+            }
+            cells
+        })
+        .collect();
+
+    Table::from_rows(grid)
+}
+
+/// Column spans separated by gutters at least two positions wide
+fn infer_columns(rows: &[Vec<char>]) -> Vec<(usize, usize)> {
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let occupied: Vec<bool> = (0..width)
+        .map(|i| {
+            rows.iter()
+                .any(|row| row.get(i).is_some_and(|c| !c.is_whitespace()))
+        })
+        .collect();
+
+    let mut columns: Vec<(usize, usize)> = Vec::new();
+    let mut i = 0;
+    while i < width {
+        if !occupied[i] {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        let mut end = i;
+        while i < width {
+            if occupied[i] {
+                end = i + 1;
+                i += 1;
+            } else if occupied.get(i + 1) == Some(&true) {
+                // A single blank position does not separate columns
+                i += 1;
+            } else {
+                break;
+            }
+        }
+        columns.push((start, end));
+    }
+
+    if columns.is_empty() {
+        columns.push((0, width));
+    }
+    columns
+}
+
+/// Spans of a line separated by two or more blanks
+fn phrases(row: &[char]) -> Vec<(usize, usize)> {
+    let mut spans = Vec::new();
+    let mut i = 0;
+    while i < row.len() {
+        if row[i].is_whitespace() {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        let mut end = i;
+        while i < row.len() {
+            if !row[i].is_whitespace() {
+                end = i + 1;
+                i += 1;
+            } else if row.get(i + 1).is_some_and(|c| !c.is_whitespace()) {
+                i += 1;
+            } else {
+                break;
+            }
+        }
+        spans.push((start, end));
+    }
+    spans
+}
+
+/// Column for a phrase: the leftmost one it overlaps, so a heading over
+/// several columns goes to the first, or else the nearest
+fn best_column(columns: &[(usize, usize)], start: usize, end: usize) -> usize {
+    let distance = |&(col_start, col_end): &(usize, usize)| {
+        if end <= col_start {
+            col_start - end
+        } else {
+            start.saturating_sub(col_end)
+        }
+    };
+
+    columns
+        .iter()
+        .position(|&(col_start, col_end)| start < col_end && col_start < end)
+        .or_else(|| (0..columns.len()).min_by_key(|&i| distance(&columns[i])))
+        .unwrap_or(0)
+}
+
+/// Remove dot leaders such as `Net sales......` or `Net sales . . .`
+fn trim_leaders(text: &str) -> &str {
+    text.trim_end_matches(['.', ' ', '\u{a0}']).trim()
+}
+
+/// Merge affix cells into their numbers
+///
+/// Affixes and numbers are single cells; a cell repeated from a `colspan`
+/// is a heading such as "%" over a column, so it is left alone.
+fn merge_affixes(row: &mut [String]) {
+    let spans = |row: &[String], i: usize| {
+        (i > 0 && row[i - 1] == row[i]) || row.get(i + 1).is_some_and(|next| *next == row[i])
+    };
+
+    for i in 0..row.len() {
+        let text = row[i].trim();
+        if spans(row, i) {
+            continue;
+        }
+        if PREFIXES.contains(&text) {
+            let prefix = text.to_string();
+            if let Some(next) = (i + 1..row.len()).find(|&j| !row[j].is_empty()) {
+                if starts_numeric(&row[next]) && !spans(row, next) {
+                    row[next] = format!("{}{}", prefix, row[next]);
+                    row[i].clear();
+                }
+            }
+        } else if SUFFIXES.contains(&text) {
+            let suffix = text.to_string();
+            if let Some(prev) = (0..i).rev().find(|&j| !row[j].is_empty()) {
+                if row[prev].ends_with(|c: char| c.is_ascii_digit() || c == ')')
+                    && !spans(row, prev)
+                {
+                    row[prev].push_str(&suffix);
+                    row[i].clear();
+                }
+            }
+        }
+    }
+}
+
+fn starts_numeric(text: &str) -> bool {
+    text.trim_start_matches(['(', '$', ' ', '-'])
+        .starts_with(|c: char| c.is_ascii_digit() || c == '.')
+}
+
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(table: &Table) -> Vec<Vec<&str>> {
+        table
+            .rows
+            .iter()
+            .map(|row| row.iter().map(|cell| cell.text.as_str()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_cell_number() {
+        assert_eq!(parse_cell_number("$1,234.5"), Some(1234.5));
+        assert_eq!(parse_cell_number("(1,234)"), Some(-1234.0));
+        assert_eq!(parse_cell_number("$(12)"), Some(-12.0));
+        assert_eq!(parse_cell_number("12.5%"), Some(12.5));
+        assert_eq!(parse_cell_number("\u{2212}3"), Some(-3.0));
+        assert_eq!(parse_cell_number("\u{2014}"), None);
+        assert_eq!(parse_cell_number("2023"), Some(2023.0));
+        assert_eq!(parse_cell_number("Net sales"), None);
+    }
+
+    #[test]
+    fn test_html_table_with_spans_and_affixes() {
+        let html = br#"<html><body>
+<table>
+<tr><td rowspan="2">Item</td><th colspan="3">Year ended</th><th colspan=2>Change</th></tr>
+<tr><th colspan=3>2023</th><th colspan=2>%</th></tr>
+<tr><td>Net sales</td><td>$</td><td>383,285</td><td></td><td>(2.8</td><td>)%</td></tr>
+<tr><td>Net income</td><td>$</td><td>(1,200</td><td>)</td><td>12</td><td>%</td></tr>
+<tr><td colspan=6>&nbsp;</td></tr>
+</table>
+</body></html>"#;
+
+        let tables = extract_tables(html);
+        assert_eq!(tables.len(), 1);
+        let table = &tables[0];
+        assert_eq!(
+            texts(table),
+            vec![
+                vec![
+                    "Item",
+                    "Year ended",
+                    "Year ended",
+                    "Year ended",
+                    "Change",
+                    "Change"
+                ],
+                vec!["Item", "2023", "2023", "2023", "%", "%"],
+                vec!["Net sales", "", "$383,285", "", "(2.8)%", ""],
+                vec!["Net income", "", "$(1,200)", "", "12%", ""],
+            ]
+        );
+        assert_eq!(table.rows[2][2].value, Some(383_285.0));
+        assert_eq!(table.rows[3][2].value, Some(-1200.0));
+        assert_eq!(table.rows[2][4].value, Some(-2.8));
+    }
+
+    #[test]
+    fn test_windows_1252_cell_with_entities() {
+        let html = b"<table><tr><td>Apple\x92s &#8220;net&#8221; sales</td></tr></table>";
+        let tables = extract_html_tables(html);
+        assert_eq!(
+            tables[0].rows[0][0].text,
+            "Apple\u{2019}s \u{201c}net\u{201d} sales"
+        );
+    }
+
+    #[test]
+    fn test_nested_tables_in_document_order() {
+        let html = b"<div><table><tr><td>outer<table><tr><td>inner</td></tr></table></td></tr></table><table><tr><td>last";
+        let tables = extract_html_tables(html);
+        let firsts: Vec<&str> = tables.iter().map(|t| t.rows[0][0].text.as_str()).collect();
+        assert_eq!(firsts, vec!["outer", "inner", "last"]);
+    }
+
+    #[test]
+    fn test_text_table() {
+        let text = "Some text before the table.
+<TABLE>
+<CAPTION>
+                                               Year Ended December 31,
+                                              -------------------------
+                                                 1999           1998
+                                              ----------     ----------
+<S>                                           <C>            <C>
+Net sales...................................  $ 1,234.5      $ 1,000.0
+Cost of sales...............................       (800)          (700)
+Gross margin . . . . . . . . . . . . . . . .       35.2%          30.0%
+</TABLE>
+";
+        let tables = extract_tables(text.as_bytes());
+        assert_eq!(tables.len(), 1);
+        assert_eq!(
+            texts(&tables[0]),
+            vec![
+                vec!["", "Year Ended December 31,", ""],
+                vec!["", "1999", "1998"],
+                vec!["Net sales", "$ 1,234.5", "$ 1,000.0"],
+                vec!["Cost of sales", "(800)", "(700)"],
+                vec!["Gross margin", "35.2%", "30.0%"],
+            ]
+        );
+        assert_eq!(tables[0].rows[2][1].value, Some(1234.5));
+        assert_eq!(tables[0].rows[3][2].value, Some(-700.0));
+    }
+
+    #[test]
+    fn test_csv_and_json() {
+        let table = Table::from_rows(vec![
+            vec!["Name".to_string(), "Value".to_string()],
+            vec!["Sales, net".to_string(), "(5)".to_string()],
+        ]);
+        assert_eq!(table.to_csv(), "Name,Value\n\"Sales, net\",(5)\n");
+
+        let json: serde_json::Value = serde_json::from_str(&table.to_json().unwrap()).unwrap();
+        assert_eq!(json["rows"][1][1]["value"], -5.0);
+        assert_eq!(json["rows"][0][0]["text"], "Name");
+    }
+
+    #[test]
+    fn test_wide_spans_do_not_blow_up_the_grid() {
+        let html = format!(
+            "<table><tr>{}</tr>{}</table>",
+            "<td colspan=256>x</td>".repeat(1000),
+            "<tr><td>y</td></tr>".repeat(2000)
+        );
+        let tables = extract_html_tables(html.as_bytes());
+
+        let table = &tables[0];
+        assert_eq!(table.width(), MAX_COLUMNS);
+        assert!(table.rows.len() * table.width() <= MAX_CELLS);
+        assert!(table.rows[0].iter().all(|cell| cell.text == "x"));
+        assert_eq!(table.rows[1][0].text, "y");
+    }
+}
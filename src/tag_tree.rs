@@ -0,0 +1,320 @@
+use crate::types::{fast_map_with_capacity, FastMap, ParseError, ParseLimits};
+use crate::utils::MetadataSink;
+
+/// Element of a tagged header
+///
+/// `children` is `None` for a value tag that is never closed and `Some` for
+/// a container closed by a matching `</TAG>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Node<'a> {
+    pub tag: &'a [u8],
+    pub text: &'a [u8],
+    pub children: Option<Vec<Node<'a>>>,
+    /// Levels of containers from this node down, 0 for a value
+    pub height: usize,
+}
+
+/// Single-pass builder for tagged (`<TAG>value` / `</TAG>`) headers
+///
+/// SGML headers do not mark which tags are containers, so every opening tag
+/// is pushed as a pending value. When `</TAG>` arrives, everything above the
+/// nearest pending `TAG` on the stack becomes its children and it turns into
+/// a container; closing tags with nothing to match are ignored. Each node is
+/// pushed and popped at most once, so building is linear in header size.
+///
+/// The header depth limit is checked as each container closes, before a
+/// hostile header can build a tree too deep to convert or drop.
+#[derive(Debug, Default)]
+pub struct TagTreeBuilder<'a> {
+    stack: Vec<Node<'a>>,
+    limits: ParseLimits,
+    /// Pending (unclosed) tags on the stack, by lowercase name
+    open_counts: FastMap<Vec<u8>, usize>,
+    /// Stack pushes and pops so far, to check that building stays linear
+    #[cfg(test)]
+    operations: usize,
+}
+
+impl<'a> TagTreeBuilder<'a> {
+    pub fn new(limits: &ParseLimits) -> Self {
+        Self {
+            stack: Vec::with_capacity(64),
+            limits: *limits,
+            open_counts: fast_map_with_capacity(32),
+            #[cfg(test)]
+            operations: 0,
+        }
+    }
+
+    /// Feed one header line; lines that are not tags are ignored
+    pub fn push_line(&mut self, line: &'a [u8]) -> Result<(), ParseError> {
+        let Some(rest) = line.strip_prefix(b"<") else {
+            return Ok(());
+        };
+        let Some(tag_end) = memchr::memchr(b'>', rest) else {
+            return Ok(());
+        };
+        let tag = rest[..tag_end].trim_ascii();
+        let text = rest[tag_end + 1..].trim_ascii();
+
+        match tag.strip_prefix(b"/") {
+            Some(name) => return self.close(name),
+            None if !tag.is_empty() => self.open(tag, text),
+            None => {}
+        }
+        Ok(())
+    }
+
+    fn open(&mut self, tag: &'a [u8], text: &'a [u8]) {
+        *self
+            .open_counts
+            .entry(tag.to_ascii_lowercase())
+            .or_insert(0) += 1;
+        self.count_operation();
+        self.stack.push(Node {
+            tag,
+            text,
+            children: None,
+            height: 0,
+        });
+    }
+
+    fn close(&mut self, name: &[u8]) -> Result<(), ParseError> {
+        let key = name.to_ascii_lowercase();
+        if self.open_counts.get(&key).copied().unwrap_or(0) == 0 {
+            return Ok(());
+        }
+
+        let mut children = Vec::new();
+        let mut height = 1;
+        while let Some(node) = self.stack.pop() {
+            self.count_operation();
+            if node.children.is_none() {
+                self.release(node.tag);
+                if node.tag.eq_ignore_ascii_case(name) {
+                    // The top level counts as 1, so a container here is
+                    // `height` levels below it
+                    self.limits.check_header_depth(1 + height)?;
+                    children.reverse();
+                    self.count_operation();
+                    self.stack.push(Node {
+                        children: Some(children),
+                        height,
+                        ..node
+                    });
+                    return Ok(());
+                }
+            }
+            height = height.max(node.height + 1);
+            children.push(node);
+        }
+        Ok(())
+    }
+
+    fn release(&mut self, tag: &[u8]) {
+        if let Some(count) = self.open_counts.get_mut(&tag.to_ascii_lowercase()) {
+            *count -= 1;
+        }
+    }
+
+    fn count_operation(&mut self) {
+        #[cfg(test)]
+        {
+            self.operations += 1;
+        }
+    }
+
+    /// Finish building; tags never closed remain values at the top level
+    pub fn finish(self) -> Vec<Node<'a>> {
+        self.stack
+    }
+}
+
+/// Convert a tag tree to metadata
+///
+/// Containers become dicts, values with text become text, and value tags
+/// without text are dropped.
+pub fn to_metadata<'a, S: MetadataSink<'a>>(nodes: &[Node<'a>]) -> S {
+    // Headers can nest arbitrarily deep, so containers are converted with an
+    // explicit stack of (remaining children, dict so far, tag in parent)
+    let mut stack = vec![(nodes.iter(), S::default(), &b""[..])];
+
+    loop {
+        let (children, dict, _) = stack.last_mut().expect("stack holds the top level");
+        match children.next() {
+            Some(node) => match &node.children {
+                Some(children) => stack.push((children.iter(), S::default(), node.tag)),
+                None if !node.text.is_empty() => {
+                    dict.push_text(node.tag, String::from_utf8_lossy(node.text))
+                }
+                None => {}
+            },
+            None => {
+                let (_, dict, tag) = stack.pop().expect("stack holds the top level");
+                match stack.last_mut() {
+                    Some((_, parent, _)) => parent.push_dict(tag, dict),
+                    None => return dict,
+                }
+            }
+        }
+    }
+}
+
+/// Parse tagged header lines into metadata
+pub fn parse_tagged_header<'a, S: MetadataSink<'a>>(
+    lines: impl IntoIterator<Item = &'a [u8]>,
+    limits: &ParseLimits,
+) -> Result<S, ParseError> {
+    let mut builder = TagTreeBuilder::new(limits);
+    for line in lines {
+        builder.push_line(line)?;
+    }
+    Ok(to_metadata(&builder.finish()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::MetadataDict;
+
+    fn parse(header: &str) -> MetadataDict {
+        parse_tagged_header(header.lines().map(str::as_bytes), &ParseLimits::default()).unwrap()
+    }
+
+    fn nested(depth: usize) -> String {
+        let mut header = "<A>\n".repeat(depth);
+        header.push_str("<CIK>1\n");
+        header.push_str(&"</A>\n".repeat(depth));
+        header
+    }
+
+    #[test]
+    fn test_containers_and_values() {
+        let metadata = parse(
+            "<SUBMISSION>\n<TYPE>10-K\n<FILER>\n<COMPANY-DATA>\n<CIK>0000320193\n</COMPANY-DATA>\n</FILER>\n<ITEMS>2.02\n<ITEMS>9.01\n",
+        );
+
+        assert!(!metadata.contains_key("submission"));
+        assert_eq!(metadata["type"].as_text().unwrap(), "10-K");
+        let company = metadata["filer"].as_dict().unwrap()["company-data"]
+            .as_dict()
+            .unwrap();
+        assert_eq!(company["cik"].as_text().unwrap(), "0000320193");
+        assert_eq!(metadata["items"].as_list().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_stray_and_mismatched_closing_tags() {
+        let metadata = parse("</FILER>\n<FILER>\n<CIK>1\n</COMPANY-DATA>\n</filer>\n<TYPE>4\n");
+
+        let filer = metadata["filer"].as_dict().unwrap();
+        assert_eq!(filer["cik"].as_text().unwrap(), "1");
+        assert_eq!(metadata["type"].as_text().unwrap(), "4");
+    }
+
+    #[test]
+    fn test_value_tag_shadowed_by_later_container() {
+        // The first <SERIES> is a value; only the second one is closed
+        let metadata = parse("<SERIES>S1\n<SERIES>\n<SERIES-ID>S2\n</SERIES>\n");
+
+        let series = metadata["series"].as_list().unwrap();
+        assert_eq!(series[0].as_text().unwrap(), "S1");
+        assert_eq!(
+            series[1].as_dict().unwrap()["series-id"].as_text().unwrap(),
+            "S2"
+        );
+    }
+
+    #[test]
+    fn test_container_longer_than_lookahead() {
+        let mut header = String::from("<SERIES-AND-CLASSES-CONTRACTS-DATA>\n");
+        for i in 0..500 {
+            header.push_str(&format!(
+                "<SERIES>\n<SERIES-ID>S{:09}\n<CLASS-CONTRACT>\n<CLASS-CONTRACT-ID>C{:09}\n</CLASS-CONTRACT>\n</SERIES>\n",
+                i, i
+            ));
+        }
+        header.push_str("</SERIES-AND-CLASSES-CONTRACTS-DATA>\n");
+
+        let metadata = parse(&header);
+        let data = metadata["series-and-classes-contracts-data"]
+            .as_dict()
+            .unwrap();
+        assert_eq!(data["series"].as_list().unwrap().len(), 500);
+    }
+
+    #[test]
+    fn test_large_header_is_linear() {
+        // Unclosed values followed by stray closing tags would be quadratic
+        // with a per-tag lookahead; each line costs a bounded number of
+        // stack operations here
+        let mut header = String::new();
+        for i in 0..100_000 {
+            header.push_str(&format!("<VALUE-{}>x\n</NEVER-OPENED>\n", i % 50));
+        }
+        header.push_str("<OUTER>\n");
+        for i in 0..100_000 {
+            header.push_str(&format!("<VALUE-{}>x\n</VALUE-{}>\n", i % 50, i % 50));
+        }
+        header.push_str("</OUTER>\n");
+
+        let mut builder = TagTreeBuilder::new(&ParseLimits::default());
+        for line in header.lines() {
+            builder.push_line(line.as_bytes()).unwrap();
+        }
+        let lines = header.lines().count();
+        assert!(builder.operations <= 3 * lines);
+
+        let metadata: MetadataDict = to_metadata(&builder.finish());
+        assert_eq!(metadata.len(), 51);
+        assert_eq!(metadata["outer"].as_dict().unwrap().len(), 50);
+    }
+
+    #[test]
+    fn test_deep_tree_to_metadata() {
+        let depth = 100_000;
+        let header = nested(depth);
+        let unlimited = ParseLimits {
+            max_header_depth: None,
+            ..ParseLimits::default()
+        };
+
+        let mut builder = TagTreeBuilder::new(&unlimited);
+        for line in header.lines() {
+            builder.push_line(line.as_bytes()).unwrap();
+        }
+        let nodes = builder.finish();
+        let metadata: MetadataDict = to_metadata(&nodes);
+        let mut dict = &metadata;
+        for _ in 0..depth {
+            dict = dict["a"].as_dict().unwrap();
+        }
+        assert_eq!(dict["cik"].as_text().unwrap(), "1");
+        // Both trees nest too deep for the recursive drops
+        std::mem::forget(metadata);
+        std::mem::forget(nodes);
+    }
+
+    #[test]
+    fn test_depth_limit_while_building() {
+        let limits = ParseLimits {
+            max_header_depth: Some(3),
+            ..ParseLimits::default()
+        };
+        let parse = |header: &str| {
+            parse_tagged_header::<MetadataDict>(header.lines().map(str::as_bytes), &limits)
+        };
+
+        assert!(parse(&nested(2)).is_ok());
+        assert!(matches!(
+            parse(&nested(3)),
+            Err(ParseError::HeaderTooDeep { depth: 4, limit: 3 })
+        ));
+        // Fails at the first container past the limit, not after the tree
+        // is complete
+        assert!(matches!(
+            parse(&nested(200_000)),
+            Err(ParseError::HeaderTooDeep { depth: 4, limit: 3 })
+        ));
+    }
+}
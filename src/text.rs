@@ -0,0 +1,428 @@
+use crate::html::{is_html, Token, Tokenizer};
+use crate::utils::{decode_html_text, decode_text};
+
+/// Options for rendering HTML documents as plain text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextOptions {
+    /// Drop the hidden `<ix:header>` block of inline XBRL documents
+    pub strip_ixbrl_header: bool,
+    /// Placed between the cells of a table row
+    pub cell_separator: String,
+}
+
+impl Default for TextOptions {
+    fn default() -> Self {
+        Self {
+            strip_ixbrl_header: true,
+            cell_separator: "\t".to_string(),
+        }
+    }
+}
+
+/// Elements whose content is never rendered
+const SKIPPED: &[&str] = &["script", "style", "template"];
+
+/// Elements that may appear in `<head>`; any other element ends it
+const HEAD_ELEMENTS: &[&str] = &[
+    "title", "meta", "link", "base", "style", "script", "noscript", "template",
+];
+
+/// Head elements without content
+const VOID_HEAD_ELEMENTS: &[&str] = &["meta", "link", "base"];
+
+/// Elements that end a paragraph
+const PARAGRAPHS: &[&str] = &[
+    "p",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "table",
+    "ul",
+    "ol",
+    "dl",
+    "blockquote",
+    "pre",
+    "hr",
+    "section",
+    "article",
+    "center",
+    "page",
+];
+
+/// Elements that end a line
+const LINES: &[&str] = &[
+    "br", "div", "tr", "li", "dt", "dd", "caption", "header", "footer", "form", "address",
+];
+
+/// Render document content as plain text
+///
+/// HTML is rendered with [`html_to_text`]; plain text documents are only
+/// decoded, so their line layout is kept.
+pub fn render_text(content: &[u8], options: &TextOptions) -> String {
+    if is_html(content) {
+        html_to_text(content, options)
+    } else {
+        decode_text(content).into_owned()
+    }
+}
+
+/// Render HTML as plain text
+///
+/// Tags are stripped and entities decoded. Block elements start new lines
+/// and paragraphs are separated by a blank line; table rows become lines
+/// with cells joined by `cell_separator`. Whitespace is collapsed except in
+/// `<pre>`. `<script>`, `<style>` and the document head are dropped, as is
+/// the inline XBRL `<ix:header>` when `strip_ixbrl_header` is set.
+pub fn html_to_text(content: &[u8], options: &TextOptions) -> String {
+    let mut renderer = HtmlRenderer::new(options);
+    for token in Tokenizer::new(content) {
+        renderer.token(token);
+    }
+    renderer.finish()
+}
+
+/// Feeds HTML tokens to a [`TextWriter`], dropping what is not rendered
+///
+/// A head missing its `</head>` ends at the first element that cannot
+/// appear in a head, such as `<body>`, or at the first text.
+pub(crate) struct HtmlRenderer<'a> {
+    writer: TextWriter<'a>,
+    strip_ixbrl_header: bool,
+    /// Name and nesting depth of the element being skipped
+    skipping: Option<(String, usize)>,
+    in_head: bool,
+    pre_depth: usize,
+}
+
+impl<'a> HtmlRenderer<'a> {
+    pub(crate) fn new(options: &'a TextOptions) -> Self {
+        Self {
+            writer: TextWriter::new(&options.cell_separator),
+            strip_ixbrl_header: options.strip_ixbrl_header,
+            skipping: None,
+            in_head: false,
+            pre_depth: 0,
+        }
+    }
+
+    /// Feed a token that starts at `offset` in the input, see
+    /// [`HtmlRenderer::finish_lines`]
+    pub(crate) fn token_at(&mut self, token: Token, offset: usize) {
+        self.writer.offset = offset;
+        self.token(token);
+    }
+
+    pub(crate) fn token(&mut self, token: Token) {
+        if let Some((name, depth)) = &mut self.skipping {
+            match token {
+                Token::StartTag(tag) if !tag.self_closing && tag.is(name) => *depth += 1,
+                Token::EndTag(end) if end.eq_ignore_ascii_case(name.as_bytes()) => {
+                    *depth -= 1;
+                    if *depth == 0 {
+                        self.skipping = None;
+                    }
+                }
+                _ => {}
+            }
+            return;
+        }
+        if self.in_head && self.head_token(&token) {
+            return;
+        }
+
+        match token {
+            Token::StartTag(tag) => {
+                let name = tag.lowercase_name();
+                if name == "head" {
+                    self.in_head = !tag.self_closing;
+                    return;
+                }
+                let skip = SKIPPED.contains(&name.as_str())
+                    || (self.strip_ixbrl_header && name == "ix:header");
+                if skip {
+                    if !tag.self_closing {
+                        self.skipping = Some((name, 1));
+                    }
+                    return;
+                }
+
+                match name.as_str() {
+                    "tr" => self.writer.start_row(),
+                    "td" | "th" => self.writer.start_cell(),
+                    "pre" => self.pre_depth += 1,
+                    _ => {}
+                }
+                self.writer.block(&name);
+            }
+            Token::EndTag(end) => {
+                let name = String::from_utf8_lossy(end).to_ascii_lowercase();
+                if name == "pre" {
+                    self.pre_depth = self.pre_depth.saturating_sub(1);
+                }
+                self.writer.block(&name);
+            }
+            Token::Text(text) => {
+                self.writer
+                    .text(&decode_html_text(text), self.pre_depth > 0);
+            }
+            Token::Comment(_) => {}
+        }
+    }
+
+    /// Handle a token inside `<head>`; returns `false` once the head has
+    /// ended and the token belongs to the body
+    fn head_token(&mut self, token: &Token) -> bool {
+        match token {
+            Token::StartTag(tag) => {
+                let name = tag.lowercase_name();
+                if !HEAD_ELEMENTS.contains(&name.as_str()) {
+                    self.in_head = false;
+                    return false;
+                }
+                if !tag.self_closing && !VOID_HEAD_ELEMENTS.contains(&name.as_str()) {
+                    self.skipping = Some((name, 1));
+                }
+            }
+            Token::EndTag(end) if end.eq_ignore_ascii_case(b"head") => self.in_head = false,
+            Token::Text(text) if !text.trim_ascii().is_empty() => {
+                self.in_head = false;
+                return false;
+            }
+            _ => {}
+        }
+        true
+    }
+
+    pub(crate) fn finish(self) -> String {
+        self.writer.finish()
+    }
+
+    /// Finish as trimmed, non-blank lines, each with the input offset of
+    /// the token its text starts in
+    pub(crate) fn finish_lines(mut self) -> Vec<(String, usize)> {
+        let line_starts = std::mem::take(&mut self.writer.line_starts);
+        let text = self.writer.finish();
+
+        let mut lines = Vec::new();
+        let mut start = 0;
+        for line in text.split('\n') {
+            let recorded = line_starts.partition_point(|&(pos, _)| pos <= start);
+            let offset = recorded.checked_sub(1).map_or(0, |i| line_starts[i].1);
+            let line_text = line.trim();
+            if !line_text.is_empty() {
+                lines.push((line_text.to_string(), offset));
+            }
+            start += line.len() + 1;
+        }
+        lines
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Pending {
+    None,
+    Space,
+    Cell,
+    Line,
+    Paragraph,
+}
+
+struct TextWriter<'a> {
+    out: String,
+    pending: Pending,
+    cell_separator: &'a str,
+    row_has_cell: bool,
+    /// Text was written since the row started
+    row_has_text: bool,
+    /// Cell separators owed before the next text, one per cell boundary
+    cells: usize,
+    /// Input offset of the token being written
+    offset: usize,
+    /// Output position and input offset where each line starts
+    line_starts: Vec<(usize, usize)>,
+}
+
+impl<'a> TextWriter<'a> {
+    fn new(cell_separator: &'a str) -> Self {
+        Self {
+            out: String::new(),
+            pending: Pending::None,
+            cell_separator,
+            row_has_cell: false,
+            row_has_text: false,
+            cells: 0,
+            offset: 0,
+            line_starts: Vec::new(),
+        }
+    }
+
+    fn request(&mut self, pending: Pending) {
+        self.pending = self.pending.max(pending);
+    }
+
+    fn block(&mut self, name: &str) {
+        if PARAGRAPHS.contains(&name) {
+            self.request(Pending::Paragraph);
+        } else if LINES.contains(&name) {
+            self.request(Pending::Line);
+        }
+    }
+
+    fn start_row(&mut self) {
+        self.row_has_cell = false;
+        self.row_has_text = false;
+        self.cells = 0;
+    }
+
+    fn start_cell(&mut self) {
+        // Every boundary gets a separator, so empty cells keep columns aligned
+        if self.row_has_cell {
+            self.request(Pending::Cell);
+            self.cells += 1;
+        }
+        self.row_has_cell = true;
+    }
+
+    fn flush(&mut self) {
+        if !self.out.is_empty() {
+            match self.pending {
+                Pending::None | Pending::Cell => {}
+                Pending::Space => self.out.push(' '),
+                Pending::Line => self.out.push('\n'),
+                Pending::Paragraph => self.out.push_str("\n\n"),
+            }
+        }
+        if self.out.is_empty() || self.pending >= Pending::Line {
+            self.line_starts.push((self.out.len(), self.offset));
+        }
+        // After a line break only empty cells at the start of a row are owed
+        if self.pending == Pending::Cell || !self.row_has_text {
+            for _ in 0..self.cells {
+                self.out.push_str(self.cell_separator);
+            }
+        }
+        self.cells = 0;
+        self.row_has_text = true;
+        self.pending = Pending::None;
+    }
+
+    fn text(&mut self, text: &str, preformatted: bool) {
+        if preformatted {
+            if !text.is_empty() {
+                self.flush();
+                let start = self.out.len();
+                self.out.push_str(text);
+                for (i, _) in text.match_indices('\n') {
+                    self.line_starts.push((start + i + 1, self.offset));
+                }
+            }
+            return;
+        }
+
+        for c in text.chars() {
+            if c.is_whitespace() {
+                self.request(Pending::Space);
+            } else if c != '\u{ad}' {
+                self.flush();
+                self.out.push(c);
+            }
+        }
+    }
+
+    fn finish(mut self) -> String {
+        let end = self.out.trim_end().len();
+        self.out.truncate(end);
+        if !self.out.is_empty() {
+            self.out.push('\n');
+        }
+        self.out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_html_to_text() {
+        let html = br#"<html><head><title>10-K</title><style>p { color: red }</style></head>
+<body>
+<div style="display:none"><ix:header><ix:resources>context-1</ix:resources></ix:header></div>
+<h1>Item&nbsp;1.   Business</h1>
+<p>Apple&#146;s   products
+include <b>iPhone</b>&mdash;and more.</p>
+<script>var x = "<p>hidden</p>";</script>
+<table>
+<tr><td>Net sales</td><td>$</td><td>383,285</td></tr>
+<tr><td>Net income</td><td></td><td>96,995</td></tr>
+</table>
+<pre>  indented
+    block</pre>
+</body></html>"#;
+
+        let text = html_to_text(html, &TextOptions::default());
+        assert_eq!(
+            text,
+            "Item 1. Business\n\nApple\u{2019}s products include iPhone\u{2014}and more.\n\nNet sales\t$\t383,285\nNet income\t\t96,995\n\n  indented\n    block\n"
+        );
+    }
+
+    #[test]
+    fn test_keep_ixbrl_header() {
+        let html = b"<html><body><ix:header>context-1</ix:header><p>Body</p></body></html>";
+        let options = TextOptions {
+            strip_ixbrl_header: false,
+            ..TextOptions::default()
+        };
+        assert_eq!(html_to_text(html, &options), "context-1\n\nBody\n");
+        assert_eq!(html_to_text(html, &TextOptions::default()), "Body\n");
+    }
+
+    #[test]
+    fn test_plain_text_is_kept() {
+        let text = b"ANNUAL REPORT\n\n    Item 1.  Business\n";
+        assert_eq!(
+            render_text(text, &TextOptions::default()),
+            "ANNUAL REPORT\n\n    Item 1.  Business\n"
+        );
+        // Windows-1252 bytes are decoded rather than replaced
+        assert_eq!(
+            render_text(b"Apple\x92s", &TextOptions::default()),
+            "Apple\u{2019}s"
+        );
+    }
+
+    #[test]
+    fn test_head_without_closing_tag() {
+        let html =
+            b"<html><head><title>10-K</title><meta charset=utf-8><body><p>Body</p></body></html>";
+        assert_eq!(html_to_text(html, &TextOptions::default()), "Body\n");
+
+        // No <body> either: the first non-head element ends the head
+        let html = b"<html><head><title>10-K</title><div>Body</div></html>";
+        assert_eq!(html_to_text(html, &TextOptions::default()), "Body\n");
+    }
+
+    #[test]
+    fn test_empty_cells_keep_columns() {
+        let html = b"<table><tr><td></td><td>2023</td><td></td><td>2022</td></tr><tr><td>Sales</td><td>10</td><td></td><td>9</td></tr></table>";
+        assert_eq!(
+            html_to_text(html, &TextOptions::default()),
+            "\t2023\t\t2022\nSales\t10\t\t9\n"
+        );
+    }
+
+    #[test]
+    fn test_entities_in_windows_1252_text() {
+        // The raw \x92 makes the text Windows-1252; the entity is decoded
+        // afterwards so its UTF-8 is not reinterpreted
+        let html = b"<p>Apple\x92s &#8220;iPhone&#8221; &amp; more</p>";
+        assert_eq!(
+            html_to_text(html, &TextOptions::default()),
+            "Apple\u{2019}s \u{201c}iPhone\u{201d} & more\n"
+        );
+    }
+}
@@ -1,9 +1,19 @@
+use rustc_hash::FxHashMap;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::error::Error;
 use std::fmt;
 use std::path::PathBuf;
 
+/// Hash map used throughout the parsers
+pub type FastMap<K, V> = FxHashMap<K, V>;
+
+/// Create a `FastMap` with pre-allocated capacity
+#[inline]
+pub fn fast_map_with_capacity<K, V>(capacity: usize) -> FastMap<K, V> {
+    FastMap::with_capacity_and_hasher(capacity, Default::default())
+}
+
 #[derive(Debug)]
 pub enum ParseError {
     Io(std::io::Error),
@@ -11,6 +21,30 @@ pub enum ParseError {
     UnknownSubmissionType(String),
     InvalidContent(String),
     NoInput,
+    /// Input larger than [`ParseLimits::max_input_size`]
+    InputTooLarge {
+        size: usize,
+        limit: usize,
+    },
+    /// More documents than [`ParseLimits::max_documents`]
+    TooManyDocuments {
+        limit: usize,
+    },
+    /// Header nested deeper than [`ParseLimits::max_header_depth`]
+    HeaderTooDeep {
+        depth: usize,
+        limit: usize,
+    },
+    /// Document content larger than [`ParseLimits::max_decoded_size`]
+    DocumentTooLarge {
+        size: usize,
+        limit: usize,
+    },
+    /// Line longer than [`ParseLimits::max_line_length`]
+    LineTooLong {
+        length: usize,
+        limit: usize,
+    },
 }
 
 impl fmt::Display for ParseError {
@@ -21,6 +55,25 @@ impl fmt::Display for ParseError {
             Self::UnknownSubmissionType(s) => write!(f, "Unknown submission type: {}", s),
             Self::InvalidContent(s) => write!(f, "Invalid content: {}", s),
             Self::NoInput => write!(f, "Either filepath or content must be provided"),
+            Self::InputTooLarge { size, limit } => {
+                write!(f, "Input of {} bytes exceeds the limit of {}", size, limit)
+            }
+            Self::TooManyDocuments { limit } => {
+                write!(f, "Submission has more than {} documents", limit)
+            }
+            Self::HeaderTooDeep { depth, limit } => {
+                write!(f, "Header depth {} exceeds the limit of {}", depth, limit)
+            }
+            Self::DocumentTooLarge { size, limit } => {
+                write!(
+                    f,
+                    "Document of {} bytes exceeds the limit of {}",
+                    size, limit
+                )
+            }
+            Self::LineTooLong { length, limit } => {
+                write!(f, "Line of {} bytes exceeds the limit of {}", length, limit)
+            }
         }
     }
 }
@@ -52,7 +105,7 @@ impl From<serde_json::Error> for ParseError {
 pub enum MetadataValue {
     Text(String),
     List(Vec<MetadataValue>),
-    Dict(HashMap<String, MetadataValue>),
+    Dict(MetadataDict),
 }
 
 impl MetadataValue {
@@ -63,7 +116,7 @@ impl MetadataValue {
         }
     }
 
-    pub fn as_dict(&self) -> Option<&HashMap<String, MetadataValue>> {
+    pub fn as_dict(&self) -> Option<&MetadataDict> {
         match self {
             Self::Dict(dict) => Some(dict),
             _ => None,
@@ -80,29 +133,176 @@ impl MetadataValue {
 
 pub type MetadataDict = HashMap<String, MetadataValue>;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum SubmissionType {
     DashedDefault,
     TabPrivacy,
     TabDefault,
+    /// Header wrapped in `<SEC-HEADER>`, as in `.hdr.sgml` files
+    SecHeader,
+    /// Header wrapped in `<IMS-HEADER>`, used by early EDGAR filings
+    ImsHeader,
+    /// `-index-headers.html` page with an HTML-escaped header
+    IndexHeaders,
 }
 
-#[derive(Debug)]
+/// Separator used when a document header value continues on following lines
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ContinuationJoin {
+    /// Join continuation lines with a single space
+    #[default]
+    Space,
+    /// Keep line breaks between continuation lines
+    Newline,
+}
+
+impl ContinuationJoin {
+    pub fn separator(&self) -> &'static str {
+        match self {
+            Self::Space => " ",
+            Self::Newline => "\n",
+        }
+    }
+}
+
+/// Options controlling how a submission is parsed
+#[derive(Debug, Clone, Default)]
+pub struct ParseOptions {
+    /// Force a submission type instead of detecting it
+    pub submission_type: Option<SubmissionType>,
+    /// How continuation lines of document header values are joined
+    pub continuation_join: ContinuationJoin,
+    /// Always emit repeatable header tags as lists, see [`REPEATABLE_TAGS`]
+    ///
+    /// [`REPEATABLE_TAGS`]: crate::REPEATABLE_TAGS
+    pub always_list: bool,
+    /// Check UU-decoded attachments and record the result under each
+    /// document's `attachment` key, see [`verify_attachment`]
+    ///
+    /// [`verify_attachment`]: crate::verify_attachment
+    pub verify_attachments: bool,
+    /// Bounds on the work done for untrusted input
+    pub limits: ParseLimits,
+}
+
+/// Bounds on the work done for one submission
+///
+/// Only the header depth is limited by default, see
+/// [`ParseLimits::DEFAULT_MAX_HEADER_DEPTH`]. Exceeding a limit fails the
+/// parse with the matching [`ParseError`] variant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseLimits {
+    /// Maximum input size in bytes
+    pub max_input_size: Option<usize>,
+    /// Maximum number of documents
+    pub max_documents: Option<usize>,
+    /// Maximum nesting depth of the header metadata, counting the top
+    /// level as 1
+    ///
+    /// Dropping and serializing [`MetadataDict`] recurse, so with no limit a
+    /// deeply nested header can overflow the stack.
+    pub max_header_depth: Option<usize>,
+    /// Maximum size of one document's content after UU decoding
+    pub max_decoded_size: Option<usize>,
+    /// Maximum length of one line, excluding the newline
+    pub max_line_length: Option<usize>,
+}
+
+impl Default for ParseLimits {
+    fn default() -> Self {
+        Self {
+            max_input_size: None,
+            max_documents: None,
+            max_header_depth: Some(Self::DEFAULT_MAX_HEADER_DEPTH),
+            max_decoded_size: None,
+            max_line_length: None,
+        }
+    }
+}
+
+impl ParseLimits {
+    /// Default header depth limit; EDGAR headers nest at most a few levels
+    pub const DEFAULT_MAX_HEADER_DEPTH: usize = 64;
+
+    pub(crate) fn check_input_size(&self, size: usize) -> Result<(), ParseError> {
+        match self.max_input_size {
+            Some(limit) if size > limit => Err(ParseError::InputTooLarge { size, limit }),
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn check_documents(&self, count: usize) -> Result<(), ParseError> {
+        match self.max_documents {
+            Some(limit) if count > limit => Err(ParseError::TooManyDocuments { limit }),
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn check_header_depth(&self, depth: usize) -> Result<(), ParseError> {
+        match self.max_header_depth {
+            Some(limit) if depth > limit => Err(ParseError::HeaderTooDeep { depth, limit }),
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn check_decoded_size(&self, size: usize) -> Result<(), ParseError> {
+        match self.max_decoded_size {
+            Some(limit) if size > limit => Err(ParseError::DocumentTooLarge { size, limit }),
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn check_line_length(&self, length: usize) -> Result<(), ParseError> {
+        match self.max_line_length {
+            Some(limit) if length > limit => Err(ParseError::LineTooLong { length, limit }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check every line of a whole input
+    pub(crate) fn check_line_lengths(&self, data: &[u8]) -> Result<(), ParseError> {
+        if self.max_line_length.is_none() {
+            return Ok(());
+        }
+        let mut line_start = 0;
+        for line_end in memchr::memchr_iter(b'\n', data).chain(std::iter::once(data.len())) {
+            self.check_line_length(line_end - line_start)?;
+            line_start = line_end + 1;
+        }
+        Ok(())
+    }
+}
+
+/// Half-open byte range into a buffer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    #[inline]
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    #[inline]
+    pub fn slice<'a>(&self, data: &'a [u8]) -> &'a [u8] {
+        &data[self.start..self.end]
+    }
+}
+
+#[derive(Debug, Default)]
 pub struct DocumentIndex {
     pub document_positions: Vec<(usize, usize)>,
     pub text_positions: Vec<(usize, usize)>,
     pub header_end: usize,
-    pub text_leftovers: HashMap<usize, String>,
+    pub text_leftovers: FastMap<usize, Span>,
 }
 
 impl DocumentIndex {
     pub fn new() -> Self {
-        Self {
-            document_positions: Vec::new(),
-            text_positions: Vec::new(),
-            header_end: 0,
-            text_leftovers: HashMap::new(),
-        }
+        Self::default()
     }
 }
 
@@ -110,5 +310,4 @@ impl DocumentIndex {
 pub struct DocumentInfo {
     pub filename: PathBuf,
     pub content: Vec<u8>,
-    pub metadata: MetadataDict,
 }
@@ -1,5 +1,13 @@
+use std::borrow::Cow;
+use std::collections::hash_map::Entry;
 use std::collections::HashSet;
 
+use memchr::memmem;
+use smallvec::SmallVec;
+
+use crate::types::{ContinuationJoin, MetadataDict, MetadataValue};
+use crate::uu_decoder;
+
 /// Detect if a line starts with "begin" (UU encoded content)
 pub fn detect_uu(first_line: &str) -> bool {
     first_line.trim().starts_with("begin")
@@ -40,26 +48,51 @@ pub fn clean_lines(lines: &[String]) -> Vec<String> {
 }
 
 /// Process text content, handling UU encoding if necessary
-pub fn process_text_content(lines: &[String]) -> Vec<u8> {
+///
+/// Returns the content and whether it was UU-decoded.
+pub fn process_text_content(lines: &[String]) -> (Vec<u8>, bool) {
     let cleaned_lines = clean_lines(lines);
 
     if cleaned_lines.is_empty() {
-        return Vec::new();
+        return (Vec::new(), false);
     }
 
     if detect_uu(&cleaned_lines[0]) {
-        // Use uuencode crate for UU decoding
+        // The line length byte bounds each line, so the last line is not padded
         let content = cleaned_lines.join("\n");
-        if let Some((decoded, _)) = uuencode::uudecode(&content) {
-            return decoded;
-        }
-        return Vec::new();
+        let decoded = uu_decoder::decode(content.as_bytes());
+        (decoded, true)
     } else {
         // For regular text content
-        cleaned_lines.join("\n").into_bytes()
+        (cleaned_lines.join("\n").into_bytes(), false)
     }
 }
 
+/// Map `f` over `items`, keeping their order
+///
+/// With the `parallel` feature the items are spread over the rayon thread
+/// pool; the result is the same as the sequential map.
+#[cfg(feature = "parallel")]
+pub fn map_in_order<T, R, F>(items: &[T], f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync + Send,
+{
+    use rayon::prelude::*;
+
+    items.par_iter().map(f).collect()
+}
+
+/// Map `f` over `items`, keeping their order
+#[cfg(not(feature = "parallel"))]
+pub fn map_in_order<T, R, F>(items: &[T], f: F) -> Vec<R>
+where
+    F: Fn(&T) -> R,
+{
+    items.iter().map(f).collect()
+}
+
 /// Generate a safe filename from a string
 pub fn safe_filename(name: &str) -> String {
     name.chars()
@@ -81,3 +114,288 @@ pub fn default_filename(index: usize, is_binary: bool) -> String {
         format!("doc_{}.txt", index + 1)
     }
 }
+
+/// Insert a value, turning repeated keys into a list
+pub fn insert_metadata_value(dict: &mut MetadataDict, key: String, value: MetadataValue) {
+    match dict.entry(key) {
+        Entry::Occupied(mut entry) => match entry.get_mut() {
+            MetadataValue::List(list) => list.push(value),
+            existing => {
+                let previous = std::mem::replace(existing, MetadataValue::List(Vec::new()));
+                *existing = MetadataValue::List(vec![previous, value]);
+            }
+        },
+        Entry::Vacant(entry) => {
+            entry.insert(value);
+        }
+    }
+}
+
+/// Detect if a line opens an HTML page
+pub fn is_html_start(line: &[u8]) -> bool {
+    let line = line.trim_ascii_start();
+    let prefix = &line[..line.len().min(9)];
+    prefix.eq_ignore_ascii_case(b"<!DOCTYPE")
+        || prefix.len() >= 5 && prefix[..5].eq_ignore_ascii_case(b"<html")
+}
+
+/// Extract the `<SEC-HEADER>` block from an `-index-headers.html` page
+///
+/// The page shows the header HTML-escaped inside `<pre>` markup. Literal
+/// markup is dropped and entities decoded, so the result can be parsed as a
+/// header-only submission.
+pub fn extract_index_headers(page: &[u8]) -> Option<Vec<u8>> {
+    let start = memmem::find(page, b"&lt;SEC-HEADER&gt;")
+        .or_else(|| memmem::find(page, b"&lt;IMS-HEADER&gt;"))?;
+    let end = [&b"&lt;/SEC-HEADER&gt;"[..], b"&lt;/IMS-HEADER&gt;"]
+        .iter()
+        .filter_map(|tag| memmem::find(&page[start..], tag).map(|pos| start + pos + tag.len()))
+        .min()
+        .unwrap_or(page.len());
+
+    let mut text = Vec::with_capacity(end - start);
+    let mut in_markup = false;
+    for &b in &page[start..end] {
+        match b {
+            b'<' => in_markup = true,
+            b'>' if in_markup => in_markup = false,
+            _ if !in_markup => text.push(b),
+            _ => {}
+        }
+    }
+
+    Some(decode_entities(&text))
+}
+
+/// Decode the HTML entities that appear in EDGAR index pages and documents
+pub fn decode_entities(text: &[u8]) -> Vec<u8> {
+    let mut result = Vec::with_capacity(text.len());
+    let mut pos = 0;
+
+    while pos < text.len() {
+        if text[pos] == b'&' {
+            if let Some(len) = text[pos..].iter().take(10).position(|&b| b == b';') {
+                let entity = &text[pos + 1..pos + len];
+                let decoded = named_entity(entity).or_else(|| parse_numeric_entity(entity));
+                if let Some(c) = decoded {
+                    let mut buf = [0u8; 4];
+                    result.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                    pos += len + 1;
+                    continue;
+                }
+            }
+        }
+        result.push(text[pos]);
+        pos += 1;
+    }
+
+    result
+}
+
+fn named_entity(entity: &[u8]) -> Option<char> {
+    Some(match entity {
+        b"lt" => '<',
+        b"gt" => '>',
+        b"amp" => '&',
+        b"quot" => '"',
+        b"apos" => '\'',
+        b"nbsp" => ' ',
+        b"ensp" | b"emsp" | b"thinsp" => ' ',
+        b"shy" => '\u{ad}',
+        b"ndash" => '\u{2013}',
+        b"mdash" => '\u{2014}',
+        b"lsquo" => '\u{2018}',
+        b"rsquo" => '\u{2019}',
+        b"ldquo" => '\u{201c}',
+        b"rdquo" => '\u{201d}',
+        b"bull" => '\u{2022}',
+        b"hellip" => '\u{2026}',
+        b"middot" => '\u{b7}',
+        b"copy" => '\u{a9}',
+        b"reg" => '\u{ae}',
+        b"trade" => '\u{2122}',
+        b"sect" => '\u{a7}',
+        b"para" => '\u{b6}',
+        b"deg" => '\u{b0}',
+        b"plusmn" => '\u{b1}',
+        b"times" => '\u{d7}',
+        b"divide" => '\u{f7}',
+        b"frac14" => '\u{bc}',
+        b"frac12" => '\u{bd}',
+        b"frac34" => '\u{be}',
+        b"cent" => '\u{a2}',
+        b"pound" => '\u{a3}',
+        b"yen" => '\u{a5}',
+        b"euro" => '\u{20ac}',
+        _ => return None,
+    })
+}
+
+fn parse_numeric_entity(entity: &[u8]) -> Option<char> {
+    let digits = std::str::from_utf8(entity.strip_prefix(b"#")?).ok()?;
+    let code = match digits.strip_prefix(['x', 'X']) {
+        Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+        None => digits.parse().ok()?,
+    };
+    // Many EDGAR documents write Windows-1252 code points, e.g. &#146;
+    match u8::try_from(code) {
+        Ok(byte @ 0x80..=0x9f) => Some(cp1252_char(byte)),
+        _ => char::from_u32(code),
+    }
+}
+
+/// Map a byte of Windows-1252 text to its character
+pub fn cp1252_char(byte: u8) -> char {
+    const HIGH: [char; 32] = [
+        '\u{20ac}', '\u{81}', '\u{201a}', '\u{192}', '\u{201e}', '\u{2026}', '\u{2020}',
+        '\u{2021}', '\u{2c6}', '\u{2030}', '\u{160}', '\u{2039}', '\u{152}', '\u{8d}', '\u{17d}',
+        '\u{8f}', '\u{90}', '\u{2018}', '\u{2019}', '\u{201c}', '\u{201d}', '\u{2022}', '\u{2013}',
+        '\u{2014}', '\u{2dc}', '\u{2122}', '\u{161}', '\u{203a}', '\u{153}', '\u{9d}', '\u{17e}',
+        '\u{178}',
+    ];
+    match byte {
+        0x80..=0x9f => HIGH[usize::from(byte - 0x80)],
+        _ => char::from(byte),
+    }
+}
+
+/// Decode document bytes as UTF-8, falling back to Windows-1252
+///
+/// Older filings are often Latin-1 or Windows-1252 rather than UTF-8.
+pub fn decode_text(bytes: &[u8]) -> Cow<'_, str> {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => Cow::Borrowed(text),
+        Err(_) => Cow::Owned(bytes.iter().map(|&b| cp1252_char(b)).collect()),
+    }
+}
+
+/// Decode HTML text: the bytes first, then the entities
+///
+/// Decoding entities first would mix their UTF-8 with Windows-1252 bytes,
+/// which then fall back to Windows-1252 as a whole.
+pub fn decode_html_text(bytes: &[u8]) -> Cow<'_, str> {
+    let text = decode_text(bytes);
+    if memchr::memchr(b'&', text.as_bytes()).is_none() {
+        return text;
+    }
+    // Entities decode to UTF-8, so valid text stays valid
+    Cow::Owned(String::from_utf8_lossy(&decode_entities(text.as_bytes())).into_owned())
+}
+
+/// Destination of the shared header and document metadata routines
+///
+/// Implemented by [`MetadataDict`] and by the borrowed `DictRef`, so the
+/// owned and borrowed parsers read each format with the same code. Keys are
+/// passed as written; sinks decide on case.
+pub trait MetadataSink<'a>: Default {
+    /// Add a text value, keeping earlier values under the same key
+    fn push_text(&mut self, key: &'a [u8], value: Cow<'a, str>);
+
+    /// Add a nested dict, keeping earlier values under the same key
+    fn push_dict(&mut self, key: &'a [u8], dict: Self);
+
+    /// Set a text value, replacing any earlier value under the key
+    fn set_text(&mut self, key: &'a [u8], value: Cow<'a, str>);
+}
+
+/// Convert ASCII bytes to lowercase without allocating for short tags
+#[inline]
+fn ascii_to_lowercase(bytes: &[u8]) -> SmallVec<[u8; 64]> {
+    let mut result = SmallVec::<[u8; 64]>::with_capacity(bytes.len());
+    result.extend(bytes.iter().map(u8::to_ascii_lowercase));
+    result
+}
+
+fn lowercase_key(key: &[u8]) -> String {
+    String::from_utf8_lossy(&ascii_to_lowercase(key)).into_owned()
+}
+
+impl<'a> MetadataSink<'a> for MetadataDict {
+    fn push_text(&mut self, key: &'a [u8], value: Cow<'a, str>) {
+        insert_metadata_value(
+            self,
+            lowercase_key(key),
+            MetadataValue::Text(value.into_owned()),
+        );
+    }
+
+    fn push_dict(&mut self, key: &'a [u8], dict: Self) {
+        insert_metadata_value(self, lowercase_key(key), MetadataValue::Dict(dict));
+    }
+
+    fn set_text(&mut self, key: &'a [u8], value: Cow<'a, str>) {
+        self.insert(lowercase_key(key), MetadataValue::Text(value.into_owned()));
+    }
+}
+
+/// Trim a possibly borrowed string without copying borrowed text
+pub fn trim_cow(text: Cow<'_, str>) -> Cow<'_, str> {
+    match text {
+        Cow::Borrowed(text) => Cow::Borrowed(text.trim()),
+        Cow::Owned(text) => Cow::Owned(text.trim().to_string()),
+    }
+}
+
+/// Builds document metadata from tag lines and their continuation lines
+///
+/// Both engines feed header lines through this so values come out the same:
+/// - a tag line (`<KEY>value`) starts a new key; the value is everything after
+///   the first `>`, trimmed, and a repeated key replaces the earlier value
+/// - any other non-blank line continues the most recent key, trimmed, joined
+///   with the configured separator, or becomes the value if it was empty
+/// - blank lines and lines before the first tag are ignored
+/// - keys that end up with no value are left out
+///
+/// Values stay borrowed unless continuation lines were joined.
+pub struct DocumentMetadataBuilder<'a, S> {
+    metadata: S,
+    current: Option<(&'a [u8], Cow<'a, str>)>,
+    join: ContinuationJoin,
+}
+
+impl<'a, S: MetadataSink<'a>> DocumentMetadataBuilder<'a, S> {
+    pub fn new(join: ContinuationJoin) -> Self {
+        Self {
+            metadata: S::default(),
+            current: None,
+            join,
+        }
+    }
+
+    /// Start a new key with the text following its tag
+    pub fn tag(&mut self, key: &'a [u8], value: Cow<'a, str>) {
+        self.flush();
+        self.current = Some((key, trim_cow(value)));
+    }
+
+    /// Append a continuation line to the most recent key
+    pub fn continuation(&mut self, line: Cow<'a, str>) {
+        let line = trim_cow(line);
+        if line.is_empty() {
+            return;
+        }
+        if let Some((_, value)) = &mut self.current {
+            if value.is_empty() {
+                *value = line;
+            } else {
+                let value = value.to_mut();
+                value.push_str(self.join.separator());
+                value.push_str(&line);
+            }
+        }
+    }
+
+    pub fn finish(mut self) -> S {
+        self.flush();
+        self.metadata
+    }
+
+    /// Store the current key; an empty value never replaces earlier data
+    fn flush(&mut self) {
+        if let Some((key, value)) = self.current.take() {
+            if !value.is_empty() {
+                self.metadata.set_text(key, value);
+            }
+        }
+    }
+}
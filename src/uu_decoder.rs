@@ -1,57 +1,45 @@
-use memchr::memchr;
-use smallvec::SmallVec;
+use memchr::{memchr, memchr_iter};
+
+/// 6-bit value of each byte
+///
+/// The alphabet is `' '..='`'`, with both `' '` and `` '`' `` meaning zero.
+/// Bytes outside it are masked to six bits like traditional decoders do.
+const DECODE: [u8; 256] = {
+    let mut table = [0; 256];
+    let mut c = 0;
+    while c < 256 {
+        table[c] = (c as u8).wrapping_sub(b' ') & 0x3F;
+        c += 1;
+    }
+    table
+};
 
 /// UU-decode a byte array
+///
+/// Decoding starts after the `begin` line and stops at `end`. Characters
+/// outside the UU alphabet are masked to six bits, as traditional decoders
+/// do.
 pub fn decode(input: &[u8]) -> Vec<u8> {
     // Find the "begin" line and skip it
-    let begin_pos = find_begin_line(input);
-    if begin_pos.is_none() {
+    let Some(start) = find_begin_line(input) else {
         return Vec::new();
-    }
+    };
+    let body = &input[start..];
+
+    // Each 61-byte line of 60 characters decodes to 45 bytes
+    let mut result = Vec::with_capacity(body.len() / 4 * 3);
+    let mut line_start = 0;
+    let line_ends = memchr_iter(b'\n', body).chain(std::iter::once(body.len()));
+
+    for line_end in line_ends {
+        let line = body[line_start..line_end].trim_ascii();
+        line_start = line_end + 1;
 
-    // Process the content
-    let mut result = Vec::with_capacity(input.len() / 4 * 3); // Pre-allocate with reasonable capacity
-    let mut pos = begin_pos.unwrap();
-    let mut line_start = pos;
-
-    while pos < input.len() {
-        // Find the end of the line
-        match memchr(b'\n', &input[pos..]) {
-            Some(offset) => {
-                let line_end = pos + offset;
-                let line = &input[line_start..line_end];
-
-                // Skip empty lines
-                if !line.is_empty() {
-                    let trimmed = trim_ascii(line);
-
-                    // Check for "end" marker
-                    if trimmed == b"end" {
-                        break;
-                    }
-
-                    // Decode the line
-                    if let Some(decoded) = decode_uu_line(trimmed) {
-                        result.extend_from_slice(&decoded);
-                    }
-                }
-
-                pos = line_end + 1;
-                line_start = pos;
-            }
-            None => {
-                // Last line without newline
-                let line = &input[line_start..];
-                let trimmed = trim_ascii(line);
-
-                if trimmed != b"end" && !trimmed.is_empty() {
-                    if let Some(decoded) = decode_uu_line(trimmed) {
-                        result.extend_from_slice(&decoded);
-                    }
-                }
-                break;
-            }
+        // Check for "end" marker
+        if line == b"end" {
+            break;
         }
+        decode_line_into(line, &mut result);
     }
 
     result
@@ -59,128 +47,54 @@ pub fn decode(input: &[u8]) -> Vec<u8> {
 
 /// Find the "begin" line and return the position after it
 fn find_begin_line(input: &[u8]) -> Option<usize> {
-    let begin = b"begin";
-    let begin_finder = memchr::memmem::Finder::new(begin);
-
-    if let Some(pos) = begin_finder.find(input) {
-        // Find the end of this line
-        if let Some(end_pos) = memchr(b'\n', &input[pos..]) {
-            return Some(pos + end_pos + 1);
-        }
-    }
-
-    None
+    let pos = memchr::memmem::find(input, b"begin")?;
+    // Find the end of this line
+    memchr(b'\n', &input[pos..]).map(|end_pos| pos + end_pos + 1)
 }
 
-/// Trim ASCII whitespace from a byte slice
-fn trim_ascii(input: &[u8]) -> &[u8] {
-    let mut start = 0;
-    let mut end = input.len();
-
-    // Trim start
-    while start < end
-        && (input[start] == b' '
-            || input[start] == b'\t'
-            || input[start] == b'\r'
-            || input[start] == b'\n')
-    {
-        start += 1;
-    }
-
-    // Trim end
-    while end > start
-        && (input[end - 1] == b' '
-            || input[end - 1] == b'\t'
-            || input[end - 1] == b'\r'
-            || input[end - 1] == b'\n')
-    {
-        end -= 1;
-    }
-
-    &input[start..end]
-}
-
-/// Decode a single UU-encoded line
-fn decode_uu_line(line: &[u8]) -> Option<SmallVec<[u8; 128]>> {
-    if line.is_empty() {
-        return None;
-    }
-
-    // First byte indicates the decoded length
-    let nbytes = (line[0] - 32) & 0x3F;
-    if nbytes == 0 {
-        // Empty line
-        return Some(SmallVec::new());
-    }
-
-    // Calculate how many encoded bytes we need to process (includes the length byte)
-    let needed_bytes = (nbytes as usize * 4 + 2) / 3 + 1;
-
-    // Check if we have enough bytes and handle broken encoders
-    let effective_bytes = if needed_bytes <= line.len() {
-        needed_bytes
-    } else {
-        // Handle truncated lines with workaround for broken uuencoders
-        std::cmp::min(line.len(), needed_bytes)
+/// Decode one trimmed line, appending to `out`
+///
+/// The first character gives the decoded length. Missing characters of the
+/// last group count as zero: encoders that use spaces rather than
+/// backticks lose them to trimming. Lines shorter than their length byte,
+/// from broken encoders, decode the groups that are there.
+pub(crate) fn decode_line_into(line: &[u8], out: &mut Vec<u8>) {
+    let Some((&length_char, chars)) = line.split_first() else {
+        return;
     };
 
-    // Prepare the output buffer with capacity
-    let mut result = SmallVec::<[u8; 128]>::with_capacity(nbytes as usize);
-
-    // Process 4 bytes at a time (3 decoded bytes)
-    let mut i = 1; // Skip the length byte
-    while i + 3 < effective_bytes && result.len() < nbytes as usize {
-        // Decode a group of 4 bytes into 3 output bytes
-        let c1 = (line[i] - 32) & 0x3F;
-        let c2 = (line[i + 1] - 32) & 0x3F;
-        let c3 = (line[i + 2] - 32) & 0x3F;
-        let c4 = (line[i + 3] - 32) & 0x3F;
-
-        // Output byte 1: 6 bits from c1 + 2 high bits from c2
-        result.push((c1 << 2) | (c2 >> 4));
-
-        // Output byte 2: 4 low bits from c2 + 4 high bits from c3
-        if result.len() < nbytes as usize {
-            result.push(((c2 & 0x0F) << 4) | (c3 >> 2));
-        }
-
-        // Output byte 3: 2 low bits from c3 + 6 bits from c4
-        if result.len() < nbytes as usize {
-            result.push(((c3 & 0x03) << 6) | c4);
-        }
-
-        i += 4;
+    let nbytes = DECODE[length_char as usize] as usize;
+    let groups = nbytes.div_ceil(3).min(chars.len().div_ceil(4));
+    let decoded_len = nbytes.min(groups * 3);
+
+    // Write the groups straight into the output
+    let start = out.len();
+    out.resize(start + groups * 3, 0);
+    let dst = &mut out[start..];
+    let full_groups = groups.min(chars.len() / 4);
+    for (dst, group) in dst.chunks_exact_mut(3).zip(chars.chunks_exact(4)) {
+        dst.copy_from_slice(&decode_group([
+            DECODE[group[0] as usize],
+            DECODE[group[1] as usize],
+            DECODE[group[2] as usize],
+            DECODE[group[3] as usize],
+        ]));
     }
-
-    // Handle any remaining bytes for incomplete groups
-    if i < effective_bytes && result.len() < nbytes as usize {
-        let remaining = effective_bytes - i;
-
-        if remaining >= 1 {
-            let c1 = (line[i] - 32) & 0x3F;
-
-            if remaining >= 2 {
-                let c2 = (line[i + 1] - 32) & 0x3F;
-                // Output byte 1
-                result.push((c1 << 2) | (c2 >> 4));
-
-                if remaining >= 3 {
-                    let c3 = (line[i + 2] - 32) & 0x3F;
-                    // Output byte 2
-                    if result.len() < nbytes as usize {
-                        result.push(((c2 & 0x0F) << 4) | (c3 >> 2));
-                    }
-                }
-            }
+    if full_groups < groups {
+        let mut values = [0; 4];
+        for (value, &c) in values.iter_mut().zip(&chars[full_groups * 4..]) {
+            *value = DECODE[c as usize];
         }
+        dst[full_groups * 3..].copy_from_slice(&decode_group(values));
     }
 
-    // Ensure we don't exceed the expected length
-    if result.len() > nbytes as usize {
-        result.truncate(nbytes as usize);
-    }
+    out.truncate(start + decoded_len);
+}
 
-    Some(result)
+/// Decode four 6-bit values into three bytes
+#[inline(always)]
+fn decode_group([c1, c2, c3, c4]: [u8; 4]) -> [u8; 3] {
+    [(c1 << 2) | (c2 >> 4), (c2 << 4) | (c3 >> 2), (c3 << 6) | c4]
 }
 
 #[cfg(test)]
@@ -208,10 +122,30 @@ mod tests {
     #[test]
     fn test_uu_decode_partial_line() {
         // Line with partial group at the end
-        let input = b"begin 644 partial.txt\n#0V\n`\nend\n";
+        let input = b"begin 644 partial.txt\n!00\n`\nend\n";
         let expected = b"A";
 
         let result = decode(input);
         assert_eq!(result, expected);
     }
+
+    #[test]
+    fn test_uu_decode_masks_characters_outside_alphabet() {
+        // Lowercase letters sit 64 above their alphabet counterparts
+        let input = b"begin 644 a.txt\n#86)C\n#x6)C\n`\nend\n</PDF>\n";
+        assert_eq!(decode(input), b"abcabc");
+
+        // A corrupt line still adds its bytes rather than vanishing
+        let mut out = Vec::new();
+        decode_line_into(b"\x01abc", &mut out);
+        assert_eq!(out.len(), 3);
+    }
+
+    #[test]
+    fn test_uu_decode_trimmed_trailing_space() {
+        // "payload 18" ends in a group whose last character is a space
+        let encoded = uuencode::uuencode("a.bin", b"payload 18");
+        assert!(encoded.lines().nth(1).unwrap().ends_with(' '));
+        assert_eq!(decode(encoded.as_bytes()), b"payload 18");
+    }
 }
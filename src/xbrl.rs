@@ -0,0 +1,549 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::ixbrl::is_inline_xbrl;
+use crate::types::{FastMap, MetadataDict, MetadataValue, ParseError};
+use crate::xml::{parse_xml, Element};
+
+const STANDARD_LABEL_ROLE: &str = "http://www.xbrl.org/2003/role/label";
+
+/// Reporting period of a context
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Period {
+    Instant { date: String },
+    Duration { start: String, end: String },
+    Forever,
+}
+
+/// Resolved `xbrli:context`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Context {
+    pub id: String,
+    pub entity: String,
+    pub scheme: String,
+    pub period: Period,
+    /// Dimension/member pairs from the segment or scenario
+    pub dimensions: Vec<(String, String)>,
+}
+
+/// Resolved `xbrli:unit`, e.g. `iso4217:USD` or `iso4217:USD/xbrli:shares`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Unit {
+    pub id: String,
+    pub measure: String,
+}
+
+/// Fact with its context and unit resolved
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fact {
+    pub concept: String,
+    pub context_id: String,
+    pub entity: String,
+    pub period: Period,
+    pub dimensions: Vec<(String, String)>,
+    pub unit: Option<String>,
+    pub decimals: Option<String>,
+    pub value: String,
+    /// The Inline XBRL `format` transform was unknown or found no value,
+    /// or the `scale` was out of range, so `value` is the text as displayed
+    pub untransformed: bool,
+}
+
+/// Fact as it appears in an instance, before resolution
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawFact {
+    pub concept: String,
+    pub context_ref: String,
+    pub unit_ref: Option<String>,
+    pub decimals: Option<String>,
+    pub value: String,
+    /// The Inline XBRL `format` transform was unknown or found no value,
+    /// or the `scale` was out of range, so `value` is the text as displayed
+    pub untransformed: bool,
+}
+
+/// Arc of a calculation or presentation linkbase
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Relationship {
+    /// Extended link role the arc belongs to
+    pub role: String,
+    pub from: String,
+    pub to: String,
+    pub order: Option<f64>,
+    /// Calculation weight, `None` for presentation arcs
+    pub weight: Option<f64>,
+}
+
+/// Element declared in the filing's taxonomy schema
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConceptDefinition {
+    pub id: Option<String>,
+    pub name: String,
+    pub data_type: Option<String>,
+    pub period_type: Option<String>,
+    pub substitution_group: Option<String>,
+}
+
+/// XBRL instance and linkbases of one submission
+#[derive(Debug, Clone, Default)]
+pub struct XbrlFiling {
+    pub contexts: HashMap<String, Context>,
+    pub units: HashMap<String, Unit>,
+    pub raw_facts: Vec<RawFact>,
+    /// Standard label of each concept from the label linkbase
+    pub labels: HashMap<String, String>,
+    pub calculations: Vec<Relationship>,
+    pub presentations: Vec<Relationship>,
+    pub concepts: Vec<ConceptDefinition>,
+}
+
+impl XbrlFiling {
+    /// Collect the `EX-101.*` documents of a parsed submission
+    ///
+    /// `metadata` and `documents` are the output of either parser. Inline
+    /// XBRL documents of any type are read as instances too. Returns `None`
+    /// when the submission has neither an `EX-101.INS` instance nor an
+    /// inline XBRL document.
+    pub fn from_submission(
+        metadata: &MetadataDict,
+        documents: &[Vec<u8>],
+    ) -> Result<Option<Self>, ParseError> {
+        let mut filing = Self::default();
+        let mut has_instance = false;
+
+        for (doc_type, content) in document_types(metadata).zip(documents) {
+            match doc_type.as_deref() {
+                Some("EX-101.INS") => {
+                    filing.add_instance(content)?;
+                    has_instance = true;
+                }
+                Some("EX-101.SCH") => filing.add_schema(content)?,
+                Some("EX-101.LAB") => filing.add_labels(content)?,
+                Some("EX-101.CAL") => {
+                    let arcs = parse_arcs(content, "calculationArc")?;
+                    filing.calculations.extend(arcs);
+                }
+                Some("EX-101.PRE") => {
+                    let arcs = parse_arcs(content, "presentationArc")?;
+                    filing.presentations.extend(arcs);
+                }
+                _ if is_inline_xbrl(content) => {
+                    filing.add_inline(content)?;
+                    has_instance = true;
+                }
+                _ => {}
+            }
+        }
+
+        Ok(has_instance.then_some(filing))
+    }
+
+    /// Add the contexts, units and facts of an instance document
+    pub fn add_instance(&mut self, content: &[u8]) -> Result<(), ParseError> {
+        let document = parse_xml(content)?;
+        let root = document
+            .find("xbrl")
+            .ok_or_else(|| ParseError::InvalidContent("No xbrl root element".to_string()))?;
+
+        for element in root.elements() {
+            match element.local_name() {
+                "context" => {
+                    if let Some(context) = parse_context(element) {
+                        self.contexts.insert(context.id.clone(), context);
+                    }
+                }
+                "unit" => {
+                    if let Some(unit) = parse_unit(element) {
+                        self.units.insert(unit.id.clone(), unit);
+                    }
+                }
+                _ => {
+                    if let Some(context_ref) = element.attr("contextRef") {
+                        self.raw_facts.push(RawFact {
+                            concept: element.name.clone(),
+                            context_ref: context_ref.to_string(),
+                            unit_ref: element.attr("unitRef").map(str::to_string),
+                            decimals: element.attr("decimals").map(str::to_string),
+                            value: element.text().trim().to_string(),
+                            untransformed: false,
+                        });
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Add the element declarations of a taxonomy schema
+    pub fn add_schema(&mut self, content: &[u8]) -> Result<(), ParseError> {
+        let document = parse_xml(content)?;
+        for element in document.descendants() {
+            if element.local_name() != "element" {
+                continue;
+            }
+            if let Some(name) = element.attr("name") {
+                self.concepts.push(ConceptDefinition {
+                    id: element.attr("id").map(str::to_string),
+                    name: name.to_string(),
+                    data_type: element.attr("type").map(str::to_string),
+                    period_type: element.attr("periodType").map(str::to_string),
+                    substitution_group: element.attr("substitutionGroup").map(str::to_string),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Add labels from a label linkbase, preferring the standard role
+    pub fn add_labels(&mut self, content: &[u8]) -> Result<(), ParseError> {
+        let document = parse_xml(content)?;
+
+        for link in document.descendants() {
+            if link.local_name() != "labelLink" {
+                continue;
+            }
+
+            let locators = locators(link);
+            let mut resources: FastMap<&str, Vec<(&str, String)>> = FastMap::default();
+            for label in link.children_named("label") {
+                if let Some(key) = label.attr("label") {
+                    let role = label.attr("role").unwrap_or(STANDARD_LABEL_ROLE);
+                    resources
+                        .entry(key)
+                        .or_default()
+                        .push((role, label.text().trim().to_string()));
+                }
+            }
+
+            for arc in link.children_named("labelArc") {
+                let (Some(from), Some(to)) = (arc.attr("from"), arc.attr("to")) else {
+                    continue;
+                };
+                let (Some(concept), Some(labels)) = (locators.get(from), resources.get(to)) else {
+                    continue;
+                };
+                for (role, text) in labels {
+                    if *role == STANDARD_LABEL_ROLE || !self.labels.contains_key(concept) {
+                        self.labels.insert(concept.clone(), text.clone());
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Iterate over facts with their contexts and units resolved
+    ///
+    /// Facts whose context is missing from the instance are skipped.
+    pub fn facts(&self) -> impl Iterator<Item = Fact> + '_ {
+        self.raw_facts.iter().filter_map(|raw| {
+            let context = self.contexts.get(&raw.context_ref)?;
+            Some(resolve_fact(raw, context, &self.units))
+        })
+    }
+
+    /// Standard label of a concept, accepting `prefix:Name` or `prefix_Name`
+    pub fn label(&self, concept: &str) -> Option<&str> {
+        self.labels
+            .get(concept)
+            .or_else(|| self.labels.get(&concept.replacen('_', ":", 1)))
+            .map(String::as_str)
+    }
+}
+
+/// Resolve a raw fact against its context and the known units
+pub fn resolve_fact(raw: &RawFact, context: &Context, units: &HashMap<String, Unit>) -> Fact {
+    Fact {
+        concept: raw.concept.clone(),
+        context_id: context.id.clone(),
+        entity: context.entity.clone(),
+        period: context.period.clone(),
+        dimensions: context.dimensions.clone(),
+        unit: raw.unit_ref.as_ref().map(|unit_ref| {
+            units
+                .get(unit_ref)
+                .map_or_else(|| unit_ref.clone(), |unit| unit.measure.clone())
+        }),
+        decimals: raw.decimals.clone(),
+        value: raw.value.clone(),
+        untransformed: raw.untransformed,
+    }
+}
+
+/// Document `<TYPE>` values in submission order
+fn document_types(metadata: &MetadataDict) -> impl Iterator<Item = Option<String>> + '_ {
+    metadata
+        .get("documents")
+        .and_then(MetadataValue::as_list)
+        .into_iter()
+        .flatten()
+        .map(|doc| {
+            doc.as_dict()
+                .and_then(|doc| doc.get("type"))
+                .and_then(MetadataValue::as_text)
+                .map(|doc_type| doc_type.trim().to_uppercase())
+        })
+}
+
+/// Parse an `xbrli:context` element
+pub fn parse_context(element: &Element) -> Option<Context> {
+    let id = element.attr("id")?.to_string();
+    let entity = element.child("entity");
+    let identifier = entity.and_then(|entity| entity.child("identifier"));
+
+    let period = element.child("period").map_or(Period::Forever, |period| {
+        if let Some(instant) = period.child_text("instant") {
+            Period::Instant { date: instant }
+        } else if let (Some(start), Some(end)) =
+            (period.child_text("startDate"), period.child_text("endDate"))
+        {
+            Period::Duration { start, end }
+        } else {
+            Period::Forever
+        }
+    });
+
+    let mut dimensions = Vec::new();
+    let containers = [
+        entity.and_then(|entity| entity.child("segment")),
+        element.child("scenario"),
+    ];
+    for container in containers.into_iter().flatten() {
+        for member in container.elements() {
+            if let Some(dimension) = member.attr("dimension") {
+                dimensions.push((dimension.to_string(), member.text().trim().to_string()));
+            }
+        }
+    }
+
+    Some(Context {
+        id,
+        entity: identifier.map_or_else(String::new, |id| id.text().trim().to_string()),
+        scheme: identifier
+            .and_then(|id| id.attr("scheme"))
+            .unwrap_or_default()
+            .to_string(),
+        period,
+        dimensions,
+    })
+}
+
+/// Parse an `xbrli:unit` element
+pub fn parse_unit(element: &Element) -> Option<Unit> {
+    let id = element.attr("id")?.to_string();
+    let measures = |parent: &Element| {
+        parent
+            .children_named("measure")
+            .map(|measure| measure.text().trim().to_string())
+            .collect::<Vec<_>>()
+            .join("*")
+    };
+
+    let measure = match element.child("divide") {
+        Some(divide) => {
+            let numerator = divide.child("unitNumerator").map(measures);
+            let denominator = divide.child("unitDenominator").map(measures);
+            format!(
+                "{}/{}",
+                numerator.unwrap_or_default(),
+                denominator.unwrap_or_default()
+            )
+        }
+        None => measures(element),
+    };
+
+    Some(Unit { id, measure })
+}
+
+/// Map locator labels to concept names taken from their `href` fragment
+fn locators(link: &Element) -> FastMap<&str, String> {
+    link.children_named("loc")
+        .filter_map(|loc| {
+            let label = loc.attr("label")?;
+            let fragment = loc.attr("href")?.rsplit_once('#')?.1;
+            Some((label, fragment.replacen('_', ":", 1)))
+        })
+        .collect()
+}
+
+/// Parse the arcs of a calculation or presentation linkbase
+fn parse_arcs(content: &[u8], arc_name: &str) -> Result<Vec<Relationship>, ParseError> {
+    let document = parse_xml(content)?;
+    let mut relationships = Vec::new();
+
+    for link in document.descendants() {
+        if !link.local_name().ends_with("Link") || link.child(arc_name).is_none() {
+            continue;
+        }
+
+        let role = link.attr("role").unwrap_or_default();
+        let locators = locators(link);
+
+        for arc in link.children_named(arc_name) {
+            let (Some(from), Some(to)) = (
+                arc.attr("from").and_then(|from| locators.get(from)),
+                arc.attr("to").and_then(|to| locators.get(to)),
+            ) else {
+                continue;
+            };
+
+            relationships.push(Relationship {
+                role: role.to_string(),
+                from: from.clone(),
+                to: to.clone(),
+                order: arc.attr("order").and_then(|order| order.parse().ok()),
+                weight: arc.attr("weight").and_then(|weight| weight.parse().ok()),
+            });
+        }
+    }
+
+    Ok(relationships)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_sgml_bytes;
+
+    const INSTANCE: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<xbrli:xbrl xmlns:xbrli="http://www.xbrl.org/2003/instance" xmlns:us-gaap="http://fasb.org/us-gaap/2023" xmlns:xbrldi="http://xbrl.org/2006/xbrldi">
+  <xbrli:context id="FY2023">
+    <xbrli:entity><xbrli:identifier scheme="http://www.sec.gov/CIK">0000320193</xbrli:identifier></xbrli:entity>
+    <xbrli:period><xbrli:startDate>2022-09-25</xbrli:startDate><xbrli:endDate>2023-09-30</xbrli:endDate></xbrli:period>
+  </xbrli:context>
+  <xbrli:context id="FY2023_Products">
+    <xbrli:entity>
+      <xbrli:identifier scheme="http://www.sec.gov/CIK">0000320193</xbrli:identifier>
+      <xbrli:segment><xbrldi:explicitMember dimension="srt:ProductOrServiceAxis">us-gaap:ProductMember</xbrldi:explicitMember></xbrli:segment>
+    </xbrli:entity>
+    <xbrli:period><xbrli:startDate>2022-09-25</xbrli:startDate><xbrli:endDate>2023-09-30</xbrli:endDate></xbrli:period>
+  </xbrli:context>
+  <xbrli:context id="I2023">
+    <xbrli:entity><xbrli:identifier scheme="http://www.sec.gov/CIK">0000320193</xbrli:identifier></xbrli:entity>
+    <xbrli:period><xbrli:instant>2023-09-30</xbrli:instant></xbrli:period>
+  </xbrli:context>
+  <xbrli:unit id="usd"><xbrli:measure>iso4217:USD</xbrli:measure></xbrli:unit>
+  <xbrli:unit id="usdPerShare">
+    <xbrli:divide>
+      <xbrli:unitNumerator><xbrli:measure>iso4217:USD</xbrli:measure></xbrli:unitNumerator>
+      <xbrli:unitDenominator><xbrli:measure>xbrli:shares</xbrli:measure></xbrli:unitDenominator>
+    </xbrli:divide>
+  </xbrli:unit>
+  <us-gaap:Revenues contextRef="FY2023" unitRef="usd" decimals="-6">383285000000</us-gaap:Revenues>
+  <us-gaap:Revenues contextRef="FY2023_Products" unitRef="usd" decimals="-6">298085000000</us-gaap:Revenues>
+  <us-gaap:EarningsPerShareBasic contextRef="FY2023" unitRef="usdPerShare" decimals="2">6.16</us-gaap:EarningsPerShareBasic>
+  <us-gaap:Cash contextRef="I2023" unitRef="usd" decimals="-6">29965000000</us-gaap:Cash>
+</xbrli:xbrl>
+"#;
+
+    const LABELS: &str = r#"<link:linkbase xmlns:link="http://www.xbrl.org/2003/linkbase" xmlns:xlink="http://www.w3.org/1999/xlink">
+  <link:labelLink xlink:type="extended" xlink:role="http://www.xbrl.org/2003/role/link">
+    <link:loc xlink:type="locator" xlink:href="https://xbrl.fasb.org/us-gaap-2023.xsd#us-gaap_Revenues" xlink:label="loc_Revenues"/>
+    <link:label xlink:type="resource" xlink:label="lab_Revenues" xlink:role="http://www.xbrl.org/2003/role/terseLabel">Net sales</link:label>
+    <link:label xlink:type="resource" xlink:label="lab_Revenues" xlink:role="http://www.xbrl.org/2003/role/label">Revenues</link:label>
+    <link:labelArc xlink:type="arc" xlink:from="loc_Revenues" xlink:to="lab_Revenues"/>
+  </link:labelLink>
+</link:linkbase>
+"#;
+
+    const CALCULATION: &str = r#"<link:linkbase xmlns:link="http://www.xbrl.org/2003/linkbase" xmlns:xlink="http://www.w3.org/1999/xlink">
+  <link:calculationLink xlink:type="extended" xlink:role="http://example.com/role/Income">
+    <link:loc xlink:href="us-gaap.xsd#us-gaap_GrossProfit" xlink:label="gp"/>
+    <link:loc xlink:href="us-gaap.xsd#us-gaap_Revenues" xlink:label="rev"/>
+    <link:loc xlink:href="us-gaap.xsd#us-gaap_CostOfRevenue" xlink:label="cost"/>
+    <link:calculationArc xlink:from="gp" xlink:to="rev" order="1" weight="1.0"/>
+    <link:calculationArc xlink:from="gp" xlink:to="cost" order="2" weight="-1.0"/>
+  </link:calculationLink>
+</link:linkbase>
+"#;
+
+    fn submission() -> Vec<u8> {
+        let mut data = b"<SUBMISSION>\n<TYPE>10-K\n".to_vec();
+        for (doc_type, content) in [
+            ("EX-101.INS", INSTANCE),
+            ("EX-101.LAB", LABELS),
+            ("EX-101.CAL", CALCULATION),
+        ] {
+            data.extend_from_slice(
+                format!(
+                    "<DOCUMENT>\n<TYPE>{}\n<TEXT>\n<XBRL>\n{}</XBRL>\n</TEXT>\n</DOCUMENT>\n",
+                    doc_type, content
+                )
+                .as_bytes(),
+            );
+        }
+        data.extend_from_slice(b"</SUBMISSION>\n");
+        data
+    }
+
+    #[test]
+    fn test_facts_from_submission() {
+        let (metadata, documents) = parse_sgml_bytes(&submission()).unwrap();
+        let filing = XbrlFiling::from_submission(&metadata, &documents)
+            .unwrap()
+            .unwrap();
+
+        let facts: Vec<Fact> = filing.facts().collect();
+        assert_eq!(facts.len(), 4);
+
+        let revenue = &facts[0];
+        assert_eq!(revenue.concept, "us-gaap:Revenues");
+        assert_eq!(revenue.entity, "0000320193");
+        assert_eq!(
+            revenue.period,
+            Period::Duration {
+                start: "2022-09-25".to_string(),
+                end: "2023-09-30".to_string()
+            }
+        );
+        assert_eq!(revenue.unit.as_deref(), Some("iso4217:USD"));
+        assert_eq!(revenue.decimals.as_deref(), Some("-6"));
+        assert_eq!(revenue.value, "383285000000");
+        assert!(revenue.dimensions.is_empty());
+
+        assert_eq!(
+            facts[1].dimensions,
+            vec![(
+                "srt:ProductOrServiceAxis".to_string(),
+                "us-gaap:ProductMember".to_string()
+            )]
+        );
+        assert_eq!(facts[2].unit.as_deref(), Some("iso4217:USD/xbrli:shares"));
+        assert_eq!(
+            facts[3].period,
+            Period::Instant {
+                date: "2023-09-30".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_linkbases() {
+        let (metadata, documents) = parse_sgml_bytes(&submission()).unwrap();
+        let filing = XbrlFiling::from_submission(&metadata, &documents)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(filing.label("us-gaap:Revenues"), Some("Revenues"));
+        assert_eq!(filing.label("us-gaap_Revenues"), Some("Revenues"));
+
+        assert_eq!(filing.calculations.len(), 2);
+        let cost = &filing.calculations[1];
+        assert_eq!(cost.role, "http://example.com/role/Income");
+        assert_eq!(cost.from, "us-gaap:GrossProfit");
+        assert_eq!(cost.to, "us-gaap:CostOfRevenue");
+        assert_eq!(cost.weight, Some(-1.0));
+    }
+
+    #[test]
+    fn test_no_instance() {
+        let (metadata, documents) = parse_sgml_bytes(b"<SUBMISSION>\n<TYPE>8-K\n").unwrap();
+        assert!(XbrlFiling::from_submission(&metadata, &documents)
+            .unwrap()
+            .is_none());
+    }
+}
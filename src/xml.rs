@@ -0,0 +1,260 @@
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+use crate::types::ParseError;
+use crate::utils::decode_entities;
+
+/// Minimal element tree for the XML documents embedded in submissions
+///
+/// Names keep their prefix as written (`xbrli:context`). Parsing is lenient:
+/// unmatched end tags are ignored, unclosed elements are closed at the end
+/// of input, and entity decoding accepts the HTML entities common in
+/// EDGAR-generated XHTML.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct Element {
+    pub name: String,
+    pub attributes: Vec<(String, String)>,
+    pub children: Vec<Node>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Node {
+    Element(Element),
+    Text(String),
+}
+
+impl Element {
+    /// Name without its namespace prefix
+    pub fn local_name(&self) -> &str {
+        local_name(&self.name)
+    }
+
+    /// Attribute value by local name, ignoring any prefix
+    pub fn attr(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name || local_name(key) == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Child elements
+    pub fn elements(&self) -> impl Iterator<Item = &Element> {
+        self.children.iter().filter_map(|node| match node {
+            Node::Element(element) => Some(element),
+            Node::Text(_) => None,
+        })
+    }
+
+    /// Child elements with a given local name
+    pub fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> {
+        self.elements()
+            .filter(move |element| element.local_name() == name)
+    }
+
+    /// First child element with a given local name
+    pub fn child<'a>(&'a self, name: &'a str) -> Option<&'a Element> {
+        self.children_named(name).next()
+    }
+
+    /// Trimmed text of the first child element with a given local name
+    pub fn child_text(&self, name: &str) -> Option<String> {
+        self.child(name)
+            .map(|child| child.text().trim().to_string())
+    }
+
+    /// Concatenated text of this element and its descendants
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+        self.collect_text(&mut text);
+        text
+    }
+
+    fn collect_text(&self, text: &mut String) {
+        for node in &self.children {
+            match node {
+                Node::Text(value) => text.push_str(value),
+                Node::Element(element) => element.collect_text(text),
+            }
+        }
+    }
+
+    /// Depth-first search for the first element with a given local name
+    pub fn find(&self, name: &str) -> Option<&Element> {
+        if self.local_name() == name {
+            return Some(self);
+        }
+        self.elements().find_map(|element| element.find(name))
+    }
+
+    /// All descendant elements in document order, excluding this one
+    pub fn descendants(&self) -> Vec<&Element> {
+        let mut result = Vec::new();
+        let mut stack: Vec<&Element> = self.elements().collect();
+        stack.reverse();
+        while let Some(element) = stack.pop() {
+            result.push(element);
+            let start = stack.len();
+            stack.extend(element.elements());
+            stack[start..].reverse();
+        }
+        result
+    }
+}
+
+/// Strip the namespace prefix from a qualified name
+pub(crate) fn local_name(name: &str) -> &str {
+    name.rsplit_once(':').map_or(name, |(_, local)| local)
+}
+
+/// Deepest element nesting accepted
+///
+/// The tree is walked and dropped recursively, so deeper input is rejected
+/// rather than risk overflowing the stack. Real documents stay far below it.
+const MAX_DEPTH: usize = 512;
+
+/// Parse XML bytes into a synthetic root element holding the top-level nodes
+///
+/// Fails with [`ParseError::InvalidContent`] on elements nested deeper than
+/// [`MAX_DEPTH`].
+pub(crate) fn parse_xml(data: &[u8]) -> Result<Element, ParseError> {
+    let mut reader = Reader::from_reader(data);
+    let config = reader.config_mut();
+    config.check_end_names = false;
+    config.allow_unmatched_ends = true;
+
+    let mut stack = vec![Element::default()];
+
+    loop {
+        let event = reader.read_event().map_err(|err| {
+            ParseError::InvalidContent(format!(
+                "XML error at byte {}: {}",
+                reader.error_position(),
+                err
+            ))
+        })?;
+
+        match event {
+            Event::Start(start) => {
+                // The synthetic root sits at the bottom of the stack
+                if stack.len() > MAX_DEPTH {
+                    return Err(ParseError::InvalidContent(format!(
+                        "XML nested deeper than {} elements at byte {}",
+                        MAX_DEPTH,
+                        reader.buffer_position()
+                    )));
+                }
+                stack.push(start_element(&start));
+            }
+            Event::Empty(start) => {
+                let element = start_element(&start);
+                push_node(&mut stack, Node::Element(element));
+            }
+            Event::End(end) => {
+                let name = String::from_utf8_lossy(end.name().as_ref()).into_owned();
+                // Close up to the matching element; ignore end tags with no match
+                if let Some(pos) = stack.iter().skip(1).rposition(|e| e.name == name) {
+                    while stack.len() > pos + 1 {
+                        close_element(&mut stack);
+                    }
+                }
+            }
+            Event::Text(text) => {
+                let text = decode_entities(&text);
+                push_node(
+                    &mut stack,
+                    Node::Text(String::from_utf8_lossy(&text).into_owned()),
+                );
+            }
+            Event::CData(cdata) => {
+                let text = String::from_utf8_lossy(&cdata).into_owned();
+                push_node(&mut stack, Node::Text(text));
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    while stack.len() > 1 {
+        close_element(&mut stack);
+    }
+
+    Ok(stack.pop().unwrap_or_default())
+}
+
+fn start_element(start: &BytesStart) -> Element {
+    let attributes = start
+        .html_attributes()
+        .with_checks(false)
+        .filter_map(Result::ok)
+        .map(|attr| {
+            let key = String::from_utf8_lossy(attr.key.as_ref()).into_owned();
+            let value = String::from_utf8_lossy(&decode_entities(&attr.value)).into_owned();
+            (key, value)
+        })
+        .collect();
+
+    Element {
+        name: String::from_utf8_lossy(start.name().as_ref()).into_owned(),
+        attributes,
+        children: Vec::new(),
+    }
+}
+
+fn push_node(stack: &mut [Element], node: Node) {
+    if let Some(parent) = stack.last_mut() {
+        parent.children.push(node);
+    }
+}
+
+fn close_element(stack: &mut Vec<Element>) {
+    if let Some(element) = stack.pop() {
+        push_node(stack, Node::Element(element));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_document() {
+        let root = parse_xml(
+            b"<?xml version=\"1.0\"?>\n<a:root xmlns:a=\"urn:a\"><a:item id=\"1\">x &amp; y</a:item><b/></a:root>",
+        )
+        .unwrap();
+
+        let item_root = root.find("root").unwrap();
+        assert_eq!(item_root.name, "a:root");
+        let item = item_root.child("item").unwrap();
+        assert_eq!(item.attr("id"), Some("1"));
+        assert_eq!(item.text(), "x & y");
+        assert_eq!(item_root.elements().count(), 2);
+    }
+
+    #[test]
+    fn test_lenient_html_entities_and_unclosed_elements() {
+        let root = parse_xml(b"<XML>\n<doc><p>a&nbsp;b</i></p><p>open").unwrap();
+
+        let doc = root.find("doc").unwrap();
+        let paragraphs: Vec<_> = doc.children_named("p").collect();
+        assert_eq!(paragraphs.len(), 2);
+        assert_eq!(paragraphs[0].text(), "a b");
+        assert_eq!(paragraphs[1].text(), "open");
+    }
+
+    #[test]
+    fn test_nesting_depth_limit() {
+        let nested = |depth: usize| format!("{}x", "<div>".repeat(depth));
+
+        let root = parse_xml(nested(MAX_DEPTH).as_bytes()).unwrap();
+        assert_eq!(root.text(), "x");
+        assert!(root.find("missing").is_none());
+
+        for depth in [MAX_DEPTH + 1, 200_000] {
+            assert!(matches!(
+                parse_xml(nested(depth).as_bytes()),
+                Err(ParseError::InvalidContent(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,67 @@
+//! Replays the checked-in fuzz regression corpus
+//!
+//! Each directory under `tests/fuzz_corpus` is named after a target in
+//! `fuzz/fuzz_targets`. Minimized crashes found by fuzzing go there. Needs
+//! the `fuzzing` feature.
+
+use std::fs;
+use std::panic;
+use std::path::Path;
+
+fn replay(target: &str, run: fn(&[u8])) {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fuzz_corpus")
+        .join(target);
+    let mut inputs: Vec<_> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    inputs.sort();
+    assert!(!inputs.is_empty(), "no inputs in {}", dir.display());
+
+    let failures: Vec<_> = inputs
+        .iter()
+        .filter(|path| {
+            let data = fs::read(path).unwrap();
+            panic::catch_unwind(|| run(&data)).is_err()
+        })
+        .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+        .collect();
+    assert!(failures.is_empty(), "{} panicked on {:?}", target, failures);
+}
+
+#[test]
+fn parse_sgml_into_memory() {
+    replay(
+        "parse_sgml_into_memory",
+        secsgml::fuzzing::parse_sgml_into_memory,
+    );
+}
+
+#[test]
+fn parse_sgml_bytes() {
+    replay("parse_sgml_bytes", secsgml::fuzzing::parse_sgml_bytes);
+}
+
+#[test]
+fn parse_tab_header() {
+    replay("parse_tab_header", secsgml::fuzzing::parse_tab_header);
+}
+
+#[test]
+fn parse_dashed_default_header() {
+    replay(
+        "parse_dashed_default_header",
+        secsgml::fuzzing::parse_dashed_default_header,
+    );
+}
+
+#[test]
+fn parse_xml() {
+    replay("parse_xml", secsgml::fuzzing::parse_xml);
+}
+
+#[test]
+fn uu_decode() {
+    replay("uu_decode", secsgml::fuzzing::uu_decode);
+}
@@ -0,0 +1,163 @@
+//! Golden-file tests for whole submissions
+//!
+//! Each directory under `tests/golden` holds a `submission.txt` with the
+//! expected `metadata.json` and `documents.json` (size and SHA-256 of each
+//! written document). The submission goes through
+//! [`parse_sgml_submission`], so both parsing and the written output are
+//! covered. Run with `UPDATE_GOLDEN=1` to rewrite the expected files after
+//! an intended change, then review the diff.
+//!
+//! The byte engine ([`parse_sgml_bytes`]) must match the line engine
+//! behind [`parse_sgml_submission`] on every fixture, and the borrowed
+//! [`ParsedSubmission`] must match the byte engine.
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use secsgml::{parse_sgml_bytes, parse_sgml_into_memory, parse_sgml_submission, ParsedSubmission};
+
+fn golden_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden")
+}
+
+fn fixtures() -> Vec<PathBuf> {
+    let mut fixtures: Vec<_> = fs::read_dir(golden_dir())
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.is_dir())
+        .collect();
+    fixtures.sort();
+    assert!(!fixtures.is_empty());
+    fixtures
+}
+
+/// Parse a fixture into a scratch directory and describe the output
+fn run_fixture(fixture: &Path) -> (Value, Value) {
+    let name = fixture.file_name().unwrap().to_string_lossy();
+    let output_dir =
+        env::temp_dir().join(format!("secsgml-golden-{}-{}", name, std::process::id()));
+    if output_dir.exists() {
+        fs::remove_dir_all(&output_dir).unwrap();
+    }
+
+    parse_sgml_submission(None, Some(&fixture.join("submission.txt")), &output_dir).unwrap();
+
+    let metadata: Value =
+        serde_json::from_slice(&fs::read(output_dir.join("metadata.json")).unwrap()).unwrap();
+    let mut documents = BTreeMap::new();
+    for entry in fs::read_dir(&output_dir).unwrap() {
+        let path = entry.unwrap().path();
+        let filename = path.file_name().unwrap().to_string_lossy().into_owned();
+        if filename == "metadata.json" {
+            continue;
+        }
+        let content = fs::read(&path).unwrap();
+        let sha256: String = Sha256::digest(&content)
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+        documents.insert(filename, json!({ "size": content.len(), "sha256": sha256 }));
+    }
+
+    fs::remove_dir_all(&output_dir).unwrap();
+    (metadata, json!(documents))
+}
+
+fn check_golden(path: &Path, actual: &Value, update: bool) -> Option<String> {
+    if update {
+        let mut text = serde_json::to_string_pretty(actual).unwrap();
+        text.push('\n');
+        fs::write(path, text).unwrap();
+        return None;
+    }
+
+    let expected: Value = match fs::read(path) {
+        Ok(bytes) => serde_json::from_slice(&bytes).unwrap(),
+        Err(_) => return Some(format!("{} is missing", path.display())),
+    };
+    (expected != *actual).then(|| {
+        format!(
+            "{} differs, got:\n{}",
+            path.display(),
+            serde_json::to_string_pretty(actual).unwrap()
+        )
+    })
+}
+
+#[test]
+fn golden_submissions() {
+    let update = env::var_os("UPDATE_GOLDEN").is_some();
+
+    let mut failures = Vec::new();
+    for fixture in &fixtures() {
+        let (metadata, documents) = run_fixture(fixture);
+        failures.extend(check_golden(
+            &fixture.join("metadata.json"),
+            &metadata,
+            update,
+        ));
+        failures.extend(check_golden(
+            &fixture.join("documents.json"),
+            &documents,
+            update,
+        ));
+    }
+    assert!(
+        failures.is_empty(),
+        "{}\n\nRun with UPDATE_GOLDEN=1 if the change is intended",
+        failures.join("\n\n")
+    );
+}
+
+#[test]
+fn golden_byte_engine_matches_line_engine() {
+    for fixture in fixtures() {
+        let path = fixture.join("submission.txt");
+        let (metadata, documents) = parse_sgml_bytes(&fs::read(&path).unwrap()).unwrap();
+        let (expected_metadata, expected_documents) =
+            parse_sgml_into_memory(None, Some(&path)).unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&metadata).unwrap(),
+            serde_json::to_value(&expected_metadata).unwrap(),
+            "{}",
+            fixture.display()
+        );
+        // The line engine drops the newline that ends each plain document
+        assert_eq!(
+            documents.len(),
+            expected_documents.len(),
+            "{}",
+            fixture.display()
+        );
+        for (document, expected) in documents.iter().zip(&expected_documents) {
+            assert!(
+                document == expected || document.strip_suffix(b"\n") == Some(expected),
+                "{}",
+                fixture.display()
+            );
+        }
+    }
+}
+
+#[test]
+fn golden_borrowed_matches_byte_engine() {
+    for fixture in fixtures() {
+        let data = fs::read(fixture.join("submission.txt")).unwrap();
+        let (metadata, documents) = ParsedSubmission::parse(&data).unwrap().to_owned_parts();
+        let (expected_metadata, expected_documents) = parse_sgml_bytes(&data).unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&metadata).unwrap(),
+            serde_json::to_value(&expected_metadata).unwrap(),
+            "{}",
+            fixture.display()
+        );
+        assert!(documents == expected_documents, "{}", fixture.display());
+    }
+}